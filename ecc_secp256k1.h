@@ -1,3 +1,9 @@
+/**
+ * A hash function for ECDH, receives the coordinates of the shared point and writes the result into output.
+ * Should return 1 on success and 0 on failure.
+ */
+typedef int (*EcdhHashFunction)(unsigned char *output, const unsigned char *x32, const unsigned char *y32, void *data);
+
 /**
  * Sign an ECDSA Signature
  * The message should be a hashed 32 bytes.
@@ -55,3 +61,28 @@ int ecc_secp256k1_schnorr_verify(const unsigned char *sig,
                                  const unsigned char *msg,
                                  const unsigned char *pubkey,
                                  int compressed);
+
+/**
+ * Compute an EC Diffie-Hellman secret.
+ * Accepts either compressed(33 btes) or uncompressed(65 bytes) public key. using the flag (1==compressed, 0==uncompressed).
+ * Input: pubkey -> pointer to 33 or 65 bytes pubkey depending on the compressed flag.
+ * compressed -> 1 for compressed, 0 for uncompressed.
+ * privkey -> pointer to 32 bytes private key.
+ * hashfp -> the hash function, if NULL the default SHA256(parity || x) is used and output must be 32 bytes.
+ * data -> arbitrary data passed to the hash function.
+ * Output: output -> pointer to a buffer for the result of the hash function, at least 32 bytes for the default one.
+ * Returns:
+ * 1 - Finished successfully.
+ * 0 - The hash function failed.
+ * -1 - Some other problem (a NULL pointer, an invalid public key, a compressed flag other than 0 or 1,
+ * or a private key that is zero or not below the order).
+ * # Safety
+ * `pubkey` and `privkey` must point to readable buffers of the sizes above, and `output` to a buffer that is large enough
+ * for the hash function's result (32 bytes for the default one).
+ */
+int ecc_secp256k1_ecdh(unsigned char *output,
+                       const unsigned char *pubkey,
+                       int compressed,
+                       const unsigned char *privkey,
+                       EcdhHashFunction hashfp,
+                       void *data);
@@ -0,0 +1,372 @@
+//! Adaptor signatures, for atomic swaps and PTLCs.
+//!
+//! A pre-signature is "encrypted" under an adaptor point `T = t*G`: it can be verified by anyone knowing `T`,
+//! but only turns into a valid signature when decrypted with `t`, and the pair (pre-signature, signature) reveals `t`.
+//!
+//! The ECDSA variant follows the secp256k1-zkp `ecdsa_adaptor` module and the DLC specification.
+
+use crate::dleq::{hardened_nonce, DleqProof};
+use crate::field::FieldElement;
+use crate::hash::HashDigest;
+use crate::jacobi::{self, Jacobi};
+use crate::point::Point;
+use crate::secp256k1::{
+    get_context, get_e, get_hashed_message_if, scalar_mod_order, PrivateKey, PublicKey, SchnorrSignature, Signature,
+};
+
+/// A Schnorr pre-signature, compatible with [`PrivateKey::sign_schnorr`]/[`PublicKey::verify_schnorr`].
+///
+/// `R' = k*G + T` is stored with its full y coordinate, if it doesn't have a quadratic residue y
+/// then the final nonce is `-R'` and the adaptor secret is subtracted instead of added.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SchnorrPreSignature {
+    r: Point,
+    s: FieldElement,
+}
+
+impl SchnorrPreSignature {
+    /// Creates a pre-signature over `msg` that can be completed with the discrete log of `adaptor`.
+    /// Fails only if the derived nonce is zero.
+    #[allow(non_snake_case)]
+    pub fn sign(key: &PrivateKey, msg: &[u8], adaptor: &PublicKey, to_hash: bool) -> Result<SchnorrPreSignature, &'static str> {
+        let m = get_hashed_message_if(msg, to_hash);
+        let G = get_context().generator();
+        let mut k = deterministic_k_adaptor(key, &m, adaptor)?;
+        let R = &k.num * G + adaptor.point.clone();
+        if !has_square_y(&R) {
+            k = &get_context().order - k;
+        }
+        let e = get_e(R.x.clone(), key.generate_pubkey(), m);
+        let s = k + e * &key.scalar;
+        Ok(SchnorrPreSignature { r: R, s })
+    }
+
+    /// Checks that decrypting with the discrete log of `adaptor` will produce a valid signature under `pubkey`.
+    #[allow(non_snake_case)]
+    pub fn verify(&self, pubkey: &PublicKey, msg: &[u8], adaptor: &PublicKey, to_hash: bool) -> bool {
+        let m = get_hashed_message_if(msg, to_hash);
+        let G = get_context().generator();
+        let mut e = get_e(self.r.x.clone(), pubkey.clone(), m);
+        e.reflect();
+        // k*G = s'*G - e*P
+        let kG = (&self.s.num * G) + (e.num * &pubkey.point);
+        let mut expected = self.r.clone() + -adaptor.point.clone();
+        if !has_square_y(&self.r) {
+            expected = -expected;
+        }
+        !kG.is_on_infinity() && kG == expected
+    }
+
+    /// Completes the pre-signature using the adaptor secret.
+    pub fn decrypt(&self, adaptor_secret: &PrivateKey) -> SchnorrSignature {
+        let mut s = self.s.clone();
+        if has_square_y(&self.r) {
+            s = s + &adaptor_secret.scalar;
+        } else {
+            s = s - &adaptor_secret.scalar;
+        }
+        SchnorrSignature::new(&self.r.x.clone().serialize_num(), &s.serialize_num())
+    }
+
+    /// Extracts the adaptor secret from the pre-signature and the signature that was published from it.
+    pub fn recover(&self, sig: &SchnorrSignature) -> Result<PrivateKey, &'static str> {
+        let order = &get_context().order;
+        let sig = sig.serialize();
+        if sig[..32] != self.r.x.clone().serialize_num() {
+            return Err("The signature wasn't decrypted from this pre-signature");
+        }
+        let s = FieldElement::from_serialize(&sig[32..], order.clone());
+        let t = if has_square_y(&self.r) { s - &self.s } else { self.s.clone() - s };
+        Ok(PrivateKey::new(t.num))
+    }
+
+    /// Serializes as the compressed `R'` followed by `s'`.
+    pub fn serialize(&self) -> [u8; 65] {
+        let mut res = [0u8; 65];
+        res[..33].copy_from_slice(&PublicKey::from(self.r.clone()).compressed());
+        res[33..].copy_from_slice(&self.s.clone().serialize_num());
+        res
+    }
+
+    pub fn parse(ser: &[u8]) -> Result<SchnorrPreSignature, &'static str> {
+        let order = &get_context().order;
+        if ser.len() != 65 {
+            return Err("A Schnorr pre-signature must be 65 bytes");
+        }
+        let r = PublicKey::from_compressed(&ser[..33])?.point;
+        let s = FieldElement::from_serialize(&ser[33..], order.clone());
+        if &s.num >= order {
+            return Err("s is not a scalar");
+        }
+        Ok(SchnorrPreSignature { r, s })
+    }
+}
+
+/// An ECDSA pre-signature (called an adaptor signature in secp256k1-zkp), serialized as 162 bytes.
+///
+/// Contains `R = k*Y`, `R' = k*G`, `s' = k⁻¹(m + R.x*x)` and a DLEQ proof that `R` and `R'` share the same `k`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct EcdsaPreSignature {
+    r: Point,
+    r_prime: Point,
+    s: FieldElement,
+    proof: DleqProof,
+}
+
+impl EcdsaPreSignature {
+    /// Creates a pre-signature over `msg` encrypted under `enckey`, using the default secp256k1-zkp nonce function.
+    /// Fails only if the derived nonce is zero.
+    #[allow(non_snake_case)]
+    pub fn sign(key: &PrivateKey, msg: &[u8], enckey: &PublicKey, to_hash: bool) -> Result<EcdsaPreSignature, &'static str> {
+        let m = get_hashed_message_if(msg, to_hash);
+        let order = &get_context().order;
+        let G = get_context().generator();
+        let k = hardened_nonce(b"ECDSAadaptor/non", &key.serialize(), &enckey.clone().compressed(), &m)?;
+
+        let r_prime = &k.num * G;
+        let r = &k.num * enckey.point.clone();
+        let proof = DleqProof::prove_raw(&k, &enckey.point, &r_prime, &r)?;
+
+        let sigr = x_mod_order(&r);
+        let z = FieldElement::from_serialize(&m, order.clone());
+        let s = (z + (sigr * &key.scalar)) / k;
+        Ok(EcdsaPreSignature { r, r_prime, s, proof })
+    }
+
+    /// Checks the DLEQ proof and that decrypting with the discrete log of `enckey` will produce a valid signature.
+    pub fn verify(&self, pubkey: &PublicKey, msg: &[u8], enckey: &PublicKey, to_hash: bool) -> bool {
+        let m = get_hashed_message_if(msg, to_hash);
+        if !self.proof.verify_raw(&self.r_prime, &enckey.point, &self.r) {
+            return false;
+        }
+        let z = FieldElement::from_serialize(&m, get_context().order.clone());
+        let derived = pubkey.ecdsa_nonce_point(z, x_mod_order(&self.r), &self.s);
+        !derived.is_on_infinity() && derived == self.r_prime
+    }
+
+    /// Completes the pre-signature using the decryption key, the result is always low-s.
+    pub fn decrypt(&self, deckey: &PrivateKey) -> Signature {
+        let order = &get_context().order;
+        let mut s = self.s.clone() / FieldElement::new(deckey.scalar.clone(), order.clone());
+        if s.num > order >> 1 {
+            s = order - s;
+        }
+        Signature::new(&x_mod_order(&self.r).serialize_num(), &s.serialize_num())
+    }
+
+    /// Extracts the decryption key from the pre-signature and the signature that was published from it.
+    pub fn recover(&self, sig: &Signature, enckey: &PublicKey) -> Result<PrivateKey, &'static str> {
+        let secp = get_context();
+        let sig = sig.serialize();
+        if sig[..32] != x_mod_order(&self.r).serialize_num() {
+            return Err("The signature wasn't decrypted from this pre-signature");
+        }
+        let s = FieldElement::from_serialize(&sig[32..], secp.order.clone());
+        if s.is_zero() {
+            return Err("Invalid signature");
+        }
+        let mut deckey = self.s.clone() / s;
+        let implied = &deckey.num * secp.generator();
+        if implied.x != enckey.point.x {
+            return Err("The signature doesn't match the encryption key");
+        }
+        // The signature might have been negated to make it low-s.
+        if implied.y != enckey.point.y {
+            deckey = &secp.order - deckey;
+        }
+        Ok(PrivateKey::new(deckey.num))
+    }
+
+    /// Serializes as `R || R' || s' || DLEQ proof`.
+    pub fn serialize(&self) -> [u8; 162] {
+        let mut res = [0u8; 162];
+        res[..33].copy_from_slice(&PublicKey::from(self.r.clone()).compressed());
+        res[33..66].copy_from_slice(&PublicKey::from(self.r_prime.clone()).compressed());
+        res[66..98].copy_from_slice(&self.s.clone().serialize_num());
+        res[98..].copy_from_slice(&self.proof.serialize());
+        res
+    }
+
+    pub fn parse(ser: &[u8]) -> Result<EcdsaPreSignature, &'static str> {
+        let order = &get_context().order;
+        if ser.len() != 162 {
+            return Err("An ECDSA pre-signature must be 162 bytes");
+        }
+        let r = PublicKey::from_compressed(&ser[..33])?.point;
+        if x_mod_order(&r).is_zero() {
+            return Err("R.x is zero modulo the order");
+        }
+        let r_prime = PublicKey::from_compressed(&ser[33..66])?.point;
+        let s = FieldElement::from_serialize(&ser[66..98], order.clone());
+        if s.is_zero() || &s.num >= order {
+            return Err("s' is not a valid scalar");
+        }
+        let proof = DleqProof::parse(&ser[98..])?;
+        Ok(EcdsaPreSignature { r, r_prime, s, proof })
+    }
+}
+
+fn x_mod_order(point: &Point) -> FieldElement {
+    scalar_mod_order(&point.x.clone().serialize_num())
+}
+
+fn has_square_y(point: &Point) -> bool {
+    jacobi::jacobi_symbol(point.y.num.clone(), get_context().modulo.clone()) == Jacobi::One
+}
+
+// Like `deterministic_k_schnorr` but commits to the adaptor too, so the same nonce is never used with different adaptors.
+fn deterministic_k_adaptor(key: &PrivateKey, m: &[u8; 32], adaptor: &PublicKey) -> Result<FieldElement, &'static str> {
+    let mut k = HashDigest::tagged(b"SchnorrAdaptor/nonce");
+    k.input(&key.serialize());
+    k.input(m);
+    k.input(&adaptor.clone().compressed());
+    let k = scalar_mod_order(&k.result());
+    if k.is_zero() {
+        return Err("The nonce is zero");
+    }
+    Ok(k)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rustc_hex::FromHex;
+
+    fn hex(hex: &str) -> Vec<u8> {
+        hex.from_hex().unwrap()
+    }
+
+    const MSG: &str = "8131e6f4b45754f2c90bd06688ceeabc0c45055460729928b4eecf11026a9e2d";
+    const PUBKEY: &str = "035be5e9478209674a96e60f1f037f6176540fd001fa1d64694770c56a7709c42c";
+
+    #[test]
+    fn test_schnorr_adaptor() {
+        let key = PrivateKey::new(0x1337_u32);
+        let pubkey = key.generate_pubkey();
+        let mut parities = (false, false);
+        for i in 1u32..=6 {
+            let t = PrivateKey::new(0xadab_7000_u64 + u64::from(i));
+            let adaptor = t.generate_pubkey();
+            let msg = [i as u8; 32];
+
+            let pre_sig = SchnorrPreSignature::sign(&key, &msg, &adaptor, false).unwrap();
+            assert_eq!(SchnorrPreSignature::parse(&pre_sig.serialize()).unwrap(), pre_sig);
+            assert!(pre_sig.verify(&pubkey, &msg, &adaptor, false));
+            assert!(!pre_sig.verify(&pubkey, &msg, &pubkey, false));
+            assert!(!pre_sig.verify(&pubkey, &[0xff; 32], &adaptor, false));
+            if has_square_y(&pre_sig.r) {
+                parities.0 = true;
+            } else {
+                parities.1 = true;
+            }
+
+            let sig = pre_sig.decrypt(&t);
+            assert!(pubkey.verify_schnorr(&msg, SchnorrSignature::parse(sig.serialize()), false));
+            assert_eq!(pre_sig.recover(&sig).unwrap().generate_pubkey(), adaptor);
+
+            let other = key.sign_schnorr(&msg, false);
+            assert!(pre_sig.recover(&other).is_err());
+        }
+        assert_eq!(parities, (true, true));
+    }
+
+    // Test vectors from the DLC specification, as used by secp256k1-zkp.
+    #[test]
+    fn test_ecdsa_adaptor_vectors() {
+        let pubkey = PublicKey::from_compressed(&hex(PUBKEY)).unwrap();
+        // (adaptor signature, encryption key, decryption key, signature, valid)
+        let verify_vectors = [
+            ("03424d14a5471c048ab87b3b83f6085d125d5864249ae4297a57c84e74710bb6730223f325042fce535d040fee52ec13231bf709ccd84233c6944b90317e62528b2527dff9d659a96db4c99f9750168308633c1867b70f3a18fb0f4539a1aecedcd1fc0148fc22f36b6303083ece3f872b18e35d368b3958efe5fb081f7716736ccb598d269aa3084d57e1855e1ea9a45efc10463bbf32ae378029f5763ceb40173f",
+             "02c2662c97488b07b6e819124b8989849206334a4c2fbdf691f7b34d2b16e9c293",
+             "0b2aba63b885a0f0e96fa0f303920c7fb7431ddfa94376ad94d969fbf4109dc8",
+             "424d14a5471c048ab87b3b83f6085d125d5864249ae4297a57c84e74710bb67329e80e0ee60e57af3e625bbae1672b1ecaa58effe613426b024fa1621d903394", true),
+            // The decrypted signature is high so it must be negated, and so must the recovered key.
+            ("036035c89860ec62ad153f69b5b3077bcd08fbb0d28dc7f7f6df4a05cca35455be037043b63c56f6317d9928e8f91007335748c49824220db14ad10d80a5d00a9654af0996c1824c64c90b951bb2734aaecf78d4b36131a47238c3fa2ba25e2ced54255b06df696de1483c3767242a3728826e05f79e3981e12553355bba8a0131cd370e63e3da73106f638576a5aab0ea6d45c042574c0c8d0b14b8c7c01cfe9072",
+             "024eee18be9a5a5224000f916c80b393447989e7194bc0b0f1ad7a03369702bb51",
+             "db2debddb002473a001dd70b06f6c97bdcd1c46ba1001237fe0ee1aeffb2b6c4",
+             "6035c89860ec62ad153f69b5b3077bcd08fbb0d28dc7f7f6df4a05cca35455be4ceacf921546c03dd1be596723ad1e7691bdac73d88cc36c421c5e7f08384305", true),
+            // The DLEQ proof is wrong.
+            ("03f94dca206d7582c015fb9bffe4e43b14591b30ef7d2b464d103ec5e116595dba03127f8ac3533d249280332474339000922eb6a58e3b9bf4fc7e01e4b4df2b7a4100a1e089f16e5d70bb89f961516f1de0684cc79db978495df2f399b0d01ed7240fa6e3252aedb58bdc6b5877b0c602628a235dd1ccaebdddcbe96198c0c21bead7b05f423b673d14d206fa1507b2dbe2722af792b8c266fc25a2d901d7e2c335",
+             "0214ccb756249ad6e733c80285ea7ac2ee12ffebbcee4e556e6810793a60c45ad4",
+             "1dfcfc0880e72509768ab46f2545b33168b8b8df8e4f5feb5059aa3750ee59d0",
+             "424d14a5471c048ab87b3b83f6085d125d5864249ae4297a57c84e74710bb67329e80e0ee60e57af3e625bbae1672b1ecaa58effe613426b024fa1621d903394", false),
+        ];
+        for (adaptor_sig, enckey, deckey, sig, valid) in verify_vectors.iter() {
+            let pre_sig = EcdsaPreSignature::parse(&hex(adaptor_sig)).unwrap();
+            let enckey = PublicKey::from_compressed(&hex(enckey)).unwrap();
+            let deckey = PrivateKey::from_serialized(&hex(deckey));
+            let sig = Signature::parse_slice(&hex(sig));
+            assert_eq!(pre_sig.verify(&pubkey, &hex(MSG), &enckey, false), *valid);
+            assert_eq!(pre_sig.decrypt(&deckey) == sig, *valid);
+            assert_eq!(pre_sig.recover(&sig, &enckey).ok().map(|k| k.serialize()) == Some(deckey.serialize()), *valid);
+        }
+
+        // (adaptor signature, encryption key, decryption key, signature)
+        let recover_vectors = [
+            ("03f2db6e9ed33092cc0b898fd6b282e99bdaeccb3de85c2d2512d8d507f9abab290210c01b5bed7094a12664aeaab3402d8709a8f362b140328d1b36dd7cb420d02fb66b1230d61c16d0cd0a2a02246d5ac7848dcd6f04fe627053cd3c7015a7d4aa6ac2b04347348bd67da43be8722515d99a7985fbfa66f0365c701de76ff0400dffdc9fa84dddf413a729823b16af60aa6361bc32e7cfd6701e32957c72ace67b",
+             "027ee4f899bc9c5f2b626fa1a9b37ce291c0388b5227e90b0fd8f4fa576164ede7",
+             "9cf3ea9be594366b78c457162908af3c2ea177058177e9c6bf99047927773a06",
+             "f2db6e9ed33092cc0b898fd6b282e99bdaeccb3de85c2d2512d8d507f9abab2921811fe7b53becf3b7affa9442abaa93c0ab8a8e45cd7ee2ea8d258bfc25d464"),
+            // Recovery from a high s signature, decrypting gives the low s one.
+            ("032c637cd797dd8c2ce261907ed43e82d6d1a48cbabbbece801133dd8d70a01b1403eb615a3e59b1cbbf4f87acaf645be1eda32a066611f35dd5557802802b14b19c81c04c3fefac5783b2077bd43fa0a39ab8a64d4d78332a5d621ea23eca46bc011011ab82dda6deb85699f508744d70d4134bea03f784d285b5c6c15a56e4e1fab4bc356abbdebb3b8fe1e55e6dd6d2a9ea457e91b2e6642fae69f9dbb5258854",
+             "02042537e913ad74c4bbd8da9607ad3b9cb297d08e014afc51133083f1bd687a62",
+             "324719b51ff2474c9438eb76494b0dc0bcceeb529f0a5428fd198ad8f886e99c",
+             "2c637cd797dd8c2ce261907ed43e82d6d1a48cbabbbece801133dd8d70a01b14b5f24321f550b7b9dd06ee4fcfd82bdad8b142ff93a790cc4d9f7962b38c6a3b"),
+        ];
+        for (i, (adaptor_sig, enckey, deckey, sig)) in recover_vectors.iter().enumerate() {
+            let pre_sig = EcdsaPreSignature::parse(&hex(adaptor_sig)).unwrap();
+            let enckey = PublicKey::from_compressed(&hex(enckey)).unwrap();
+            let deckey = PrivateKey::from_serialized(&hex(deckey));
+            let sig = Signature::parse_slice(&hex(sig));
+            assert_eq!(pre_sig.decrypt(&deckey) == sig, i == 0);
+            assert_eq!(pre_sig.recover(&sig, &enckey).unwrap().serialize(), deckey.serialize());
+        }
+
+        // The R value of the signature doesn't match.
+        let pre_sig = EcdsaPreSignature::parse(&hex("03aa86d78059a91059c29ec1a757c4dc029ff636a1e6c1142fefe1e9d7339617c003a8153e50c0c8574a38d389e61bbb0b5815169e060924e4b5f2e78ff13aa7ad858e0c27c4b9eed9d60521b3f54ff83ca4774be5fb3a680f820a35e8840f4aaf2de88e7c5cff38a37b78725904ef97bb82341328d55987019bd38ae1745e3efe0f8ea8bdfede0d378fc1f96e944a7505249f41e93781509ee0bade77290d39cd12")).unwrap();
+        let enckey = PublicKey::from_compressed(&hex("035176d24129741b0fcaa5fd6750727ce30860447e0a92c9ebebdeb7c3f93995ed")).unwrap();
+        let sig = Signature::parse_slice(&hex("f7f7fe6bd056fc4abd70d335f72d0aa1e8406bba68f3e579e4789475323564a452c46176c7fb40aa37d5651341f55697dab27d84a213b30c93011a7790bace8c"));
+        assert!(pre_sig.recover(&sig, &enckey).is_err());
+
+        // (adaptor signature, valid encoding)
+        let serialization_vectors = [
+            ("03e6d51da7bc2bf24cf9dfd9acc6c4f0a3e74d8a6273ee5a573ed6818e3095b60903f33bc98f9d2ea3511f2e24f3358557c815abd7713c9318af9f4dfab4441898ecd619acb1cb75c1a5946fbaf716d227199a6479a678d10a6d95512d674fb7703d85b58980b8e6c54bd20616bdb9461dccd8eebb7d7e7c83a91452cc20edf53be5b0fe0db44dddaaafbe737678c684b6e89b9b4b679b1855aa6ed644498b89c918", true),
+            // R can be above the curve order.
+            ("03fffffffffffffffffffffffffffffffffffffffffffffffffffffffefffffc2c03f33bc98f9d2ea3511f2e24f3358557c815abd7713c9318af9f4dfab4441898ecd619acb1cb75c1a5946fbaf716d227199a6479a678d10a6d95512d674fb7703d85b58980b8e6c54bd20616bdb9461dccd8eebb7d7e7c83a91452cc20edf53be5b0fe0db44dddaaafbe737678c684b6e89b9b4b679b1855aa6ed644498b89c918", true),
+            // R' can be above the curve order.
+            ("03e6d51da7bc2bf24cf9dfd9acc6c4f0a3e74d8a6273ee5a573ed6818e3095b60903fffffffffffffffffffffffffffffffffffffffffffffffffffffffefffffc2cd619acb1cb75c1a5946fbaf716d227199a6479a678d10a6d95512d674fb7703d85b58980b8e6c54bd20616bdb9461dccd8eebb7d7e7c83a91452cc20edf53be5b0fe0db44dddaaafbe737678c684b6e89b9b4b679b1855aa6ed644498b89c918", true),
+            // s' can't be zero.
+            ("03e6d51da7bc2bf24cf9dfd9acc6c4f0a3e74d8a6273ee5a573ed6818e3095b60903f33bc98f9d2ea3511f2e24f3358557c815abd7713c9318af9f4dfab4441898ec000000000000000000000000000000000000000000000000000000000000000085b58980b8e6c54bd20616bdb9461dccd8eebb7d7e7c83a91452cc20edf53be5b0fe0db44dddaaafbe737678c684b6e89b9b4b679b1855aa6ed644498b89c918", false),
+            // s' is too high.
+            ("03e6d51da7bc2bf24cf9dfd9acc6c4f0a3e74d8a6273ee5a573ed6818e3095b60903f33bc98f9d2ea3511f2e24f3358557c815abd7713c9318af9f4dfab4441898ecfffffffffffffffffffffffffffffffebaaedce6af48a03bbfd25e8cd036414185b58980b8e6c54bd20616bdb9461dccd8eebb7d7e7c83a91452cc20edf53be5b0fe0db44dddaaafbe737678c684b6e89b9b4b679b1855aa6ed644498b89c918", false),
+        ];
+        for (adaptor_sig, valid) in serialization_vectors.iter() {
+            let ser = hex(adaptor_sig);
+            let parsed = EcdsaPreSignature::parse(&ser);
+            assert_eq!(parsed.is_ok(), *valid);
+            if let Ok(pre_sig) = parsed {
+                assert_eq!(&pre_sig.serialize()[..], &ser[..]);
+            }
+        }
+    }
+
+    #[test]
+    fn test_ecdsa_adaptor() {
+        let key = PrivateKey::new(0xec_d5a_u32);
+        let pubkey = key.generate_pubkey();
+        let deckey = PrivateKey::new(0x5ec_2e7_u32);
+        let enckey = deckey.generate_pubkey();
+        let msg = b"Atomic swap";
+
+        let pre_sig = EcdsaPreSignature::sign(&key, msg, &enckey, true).unwrap();
+        assert_eq!(EcdsaPreSignature::parse(&pre_sig.serialize()).unwrap(), pre_sig);
+        assert!(pre_sig.verify(&pubkey, msg, &enckey, true));
+        assert!(!pre_sig.verify(&pubkey, msg, &pubkey, true));
+        assert!(!pre_sig.verify(&enckey, msg, &enckey, true));
+
+        let sig = pre_sig.decrypt(&deckey);
+        assert!(pubkey.verify(msg, Signature::parse(sig.serialize()), true));
+        assert_eq!(pre_sig.recover(&sig, &enckey).unwrap().serialize(), deckey.serialize());
+        assert!(pre_sig.recover(&key.sign(msg, true), &enckey).is_err());
+    }
+}
@@ -0,0 +1,395 @@
+//! Bitcoin addresses: P2PKH and P2SH in Base58Check, segwit outputs in bech32 (v0) and bech32m (v1+).
+
+use crate::base58;
+use crate::bech32::{self, Variant};
+use crate::hash::hash160;
+use crate::network::Network;
+use crate::secp256k1::PublicKey;
+use std::{fmt, str::FromStr};
+
+const P2PKH_MAINNET: u8 = 0x00;
+const P2SH_MAINNET: u8 = 0x05;
+const P2PKH_TESTNET: u8 = 0x6F;
+const P2SH_TESTNET: u8 = 0xC4;
+
+const OP_0: u8 = 0x00;
+const OP_1: u8 = 0x51;
+const OP_DUP: u8 = 0x76;
+const OP_EQUAL: u8 = 0x87;
+const OP_EQUALVERIFY: u8 = 0x88;
+const OP_HASH160: u8 = 0xA9;
+const OP_CHECKSIG: u8 = 0xAC;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Error {
+    Base58(base58::Error),
+    Bech32(bech32::Error),
+    /// A Base58Check address payload should be 21 bytes.
+    InvalidLength(usize),
+    /// The Base58Check version byte isn't a known P2PKH or P2SH version.
+    UnknownVersion(u8),
+    /// The bech32 human readable part isn't `bc` or `tb`.
+    UnknownHrp,
+    /// Witness versions go from 0 to 16.
+    InvalidWitnessVersion(u8),
+    /// Witness programs are 2 to 40 bytes, and 20 or 32 bytes for version 0.
+    InvalidWitnessProgramLength(usize),
+    /// Version 0 uses bech32 and later versions use bech32m.
+    WrongVariant,
+    /// The taproot tweak is out of range or gives the point at infinity, see [`PublicKey::taproot_output_key`].
+    InvalidTaprootTweak,
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Error::Base58(e) => write!(f, "{}", e),
+            Error::Bech32(e) => write!(f, "{}", e),
+            Error::InvalidLength(len) => write!(f, "A Base58Check address should be 21 bytes, got {}", len),
+            Error::UnknownVersion(version) => write!(f, "Unknown address version byte {:#04x}", version),
+            Error::UnknownHrp => write!(f, "Unknown human readable part"),
+            Error::InvalidWitnessVersion(v) => write!(f, "Invalid witness version {}", v),
+            Error::InvalidWitnessProgramLength(len) => write!(f, "Invalid witness program length {}", len),
+            Error::WrongVariant => write!(f, "Witness version 0 should use bech32 and later versions bech32m"),
+            Error::InvalidTaprootTweak => write!(f, "Invalid taproot tweak"),
+        }
+    }
+}
+
+impl From<base58::Error> for Error {
+    fn from(e: base58::Error) -> Self {
+        Error::Base58(e)
+    }
+}
+
+impl From<bech32::Error> for Error {
+    fn from(e: bech32::Error) -> Self {
+        Error::Bech32(e)
+    }
+}
+
+/// What an output is locked to.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum Payload {
+    PubkeyHash([u8; 20]),
+    ScriptHash([u8; 20]),
+    WitnessProgram { version: u8, program: Vec<u8> },
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Address {
+    pub(crate) network: Network,
+    pub(crate) payload: Payload,
+}
+
+impl Address {
+    /// Checks the witness version and program length, so every address has a script and an encoding.
+    pub fn new(network: Network, payload: Payload) -> Result<Address, Error> {
+        if let Payload::WitnessProgram { version, program } = &payload {
+            check_witness_program(*version, program)?;
+        }
+        Ok(Address { network, payload })
+    }
+
+    pub fn network(&self) -> Network {
+        self.network
+    }
+
+    pub fn payload(&self) -> &Payload {
+        &self.payload
+    }
+
+    /// Pay to public key hash, of the compressed key.
+    pub fn p2pkh(pubkey: &PublicKey, network: Network) -> Address {
+        Address { network, payload: Payload::PubkeyHash(pubkey.hash160()) }
+    }
+
+    /// P2WPKH nested in P2SH, for wallets that can't send to bech32 addresses.
+    pub fn p2sh_p2wpkh(pubkey: &PublicKey, network: Network) -> Address {
+        let redeem_script = witness_script(0, &pubkey.hash160());
+        Address { network, payload: Payload::ScriptHash(hash160(&redeem_script)) }
+    }
+
+    /// Pay to witness public key hash.
+    pub fn p2wpkh(pubkey: &PublicKey, network: Network) -> Address {
+        Address { network, payload: Payload::WitnessProgram { version: 0, program: pubkey.hash160().to_vec() } }
+    }
+
+    /// Pay to taproot, `internal_key` is tweaked with the merkle root of the script tree (BIP341).
+    pub fn p2tr(internal_key: &PublicKey, merkle_root: Option<&[u8; 32]>, network: Network) -> Result<Address, Error> {
+        let output_key = internal_key.taproot_output_key(merkle_root).map_err(|_| Error::InvalidTaprootTweak)?;
+        Ok(Address { network, payload: Payload::WitnessProgram { version: 1, program: output_key.x_only().to_vec() } })
+    }
+
+    /// The output script locking to this address.
+    pub fn script_pubkey(&self) -> Vec<u8> {
+        match &self.payload {
+            Payload::PubkeyHash(hash) => {
+                let mut script = vec![OP_DUP, OP_HASH160, 20];
+                script.extend_from_slice(hash);
+                script.extend_from_slice(&[OP_EQUALVERIFY, OP_CHECKSIG]);
+                script
+            }
+            Payload::ScriptHash(hash) => {
+                let mut script = vec![OP_HASH160, 20];
+                script.extend_from_slice(hash);
+                script.push(OP_EQUAL);
+                script
+            }
+            Payload::WitnessProgram { version, program } => witness_script(*version, program),
+        }
+    }
+
+    fn from_base58(s: &str) -> Result<Address, Error> {
+        let data = base58::decode_check(s)?;
+        if data.len() != 21 {
+            return Err(Error::InvalidLength(data.len()));
+        }
+        let mut hash = [0u8; 20];
+        hash.copy_from_slice(&data[1..]);
+        let (network, payload) = match data[0] {
+            P2PKH_MAINNET => (Network::Mainnet, Payload::PubkeyHash(hash)),
+            P2SH_MAINNET => (Network::Mainnet, Payload::ScriptHash(hash)),
+            P2PKH_TESTNET => (Network::Testnet, Payload::PubkeyHash(hash)),
+            P2SH_TESTNET => (Network::Testnet, Payload::ScriptHash(hash)),
+            version => return Err(Error::UnknownVersion(version)),
+        };
+        Ok(Address { network, payload })
+    }
+
+    fn from_bech32(s: &str) -> Result<Address, Error> {
+        let (hrp, data, variant) = bech32::decode(s)?;
+        let network = match hrp.as_str() {
+            "bc" => Network::Mainnet,
+            "tb" => Network::Testnet,
+            _ => return Err(Error::UnknownHrp),
+        };
+        let (&version, data) = data.split_first().ok_or(Error::InvalidWitnessProgramLength(0))?;
+        let program = bech32::convert_bits(data, 5, 8, false)?;
+        check_witness_program(version, &program)?;
+        if variant != witness_variant(version) {
+            return Err(Error::WrongVariant);
+        }
+        Ok(Address { network, payload: Payload::WitnessProgram { version, program } })
+    }
+}
+
+impl fmt::Display for Address {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let (prefix, hash) = match (&self.payload, self.network) {
+            (Payload::PubkeyHash(hash), Network::Mainnet) => (P2PKH_MAINNET, hash),
+            (Payload::ScriptHash(hash), Network::Mainnet) => (P2SH_MAINNET, hash),
+            (Payload::PubkeyHash(hash), Network::Testnet) => (P2PKH_TESTNET, hash),
+            (Payload::ScriptHash(hash), Network::Testnet) => (P2SH_TESTNET, hash),
+            (Payload::WitnessProgram { version, program }, network) => {
+                let hrp = match network {
+                    Network::Mainnet => "bc",
+                    Network::Testnet => "tb",
+                };
+                let mut data = vec![*version];
+                data.extend(bech32::convert_bits(program, 8, 5, true).map_err(|_| fmt::Error)?);
+                return write!(f, "{}", bech32::encode(hrp, &data, witness_variant(*version)).map_err(|_| fmt::Error)?);
+            }
+        };
+        let mut data = vec![prefix];
+        data.extend_from_slice(hash);
+        write!(f, "{}", base58::encode_check(&data))
+    }
+}
+
+impl FromStr for Address {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        // Known HRPs go to bech32 for its errors, other strings only if they have a valid bech32 checksum,
+        // so an unknown HRP is reported as such instead of as an invalid Base58 character.
+        let lower = s.to_lowercase();
+        if lower.starts_with("bc1") || lower.starts_with("tb1") || bech32::decode(s).is_ok() {
+            Address::from_bech32(s)
+        } else {
+            Address::from_base58(s)
+        }
+    }
+}
+
+fn witness_variant(version: u8) -> Variant {
+    if version == 0 {
+        Variant::Bech32
+    } else {
+        Variant::Bech32m
+    }
+}
+
+fn check_witness_program(version: u8, program: &[u8]) -> Result<(), Error> {
+    if version > 16 {
+        return Err(Error::InvalidWitnessVersion(version));
+    }
+    let valid = match version {
+        0 => program.len() == 20 || program.len() == 32,
+        _ => (2..=40).contains(&program.len()),
+    };
+    if !valid {
+        return Err(Error::InvalidWitnessProgramLength(program.len()));
+    }
+    Ok(())
+}
+
+// `OP_n <program>`
+fn witness_script(version: u8, program: &[u8]) -> Vec<u8> {
+    let mut script = vec![if version == 0 { OP_0 } else { OP_1 + version - 1 }, program.len() as u8];
+    script.extend_from_slice(program);
+    script
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bip32::ExtendedPrivKey;
+    use crate::bip39::Mnemonic;
+    use crate::PrivateKey;
+    use rustc_hex::ToHex;
+
+    // BIP350 valid addresses, with their scriptPubKeys.
+    #[test]
+    fn test_valid_segwit_addresses() {
+        let vectors = [
+            ("BC1QW508D6QEJXTDG4Y5R3ZARVARY0C5XW7KV8F3T4", "0014751e76e8199196d454941c45d1b3a323f1433bd6"),
+            (
+                "tb1qrp33g0q5c5txsp9arysrx4k6zdkfs4nce4xj0gdcccefvpysxf3q0sl5k7",
+                "00201863143c14c5166804bd19203356da136c985678cd4d27a1b8c6329604903262",
+            ),
+            (
+                "bc1pw508d6qejxtdg4y5r3zarvary0c5xw7kw508d6qejxtdg4y5r3zarvary0c5xw7kt5nd6y",
+                "5128751e76e8199196d454941c45d1b3a323f1433bd6751e76e8199196d454941c45d1b3a323f1433bd6",
+            ),
+            ("BC1SW50QGDZ25J", "6002751e"),
+            ("bc1zw508d6qejxtdg4y5r3zarvaryvaxxpcs", "5210751e76e8199196d454941c45d1b3a323"),
+            (
+                "tb1qqqqqp399et2xygdj5xreqhjjvcmzhxw4aywxecjdzew6hylgvsesrxh6hy",
+                "0020000000c4a5cad46221b2a187905e5266362b99d5e91c6ce24d165dab93e86433",
+            ),
+            (
+                "tb1pqqqqp399et2xygdj5xreqhjjvcmzhxw4aywxecjdzew6hylgvsesf3hn0c",
+                "5120000000c4a5cad46221b2a187905e5266362b99d5e91c6ce24d165dab93e86433",
+            ),
+            (
+                "bc1p0xlxvlhemja6c4dqv22uapctqupfhlxm9h8z3k2e72q4k9hcz7vqzk5jj0",
+                "512079be667ef9dcbbac55a06295ce870b07029bfcdb2dce28d959f2815b16f81798",
+            ),
+        ];
+        for (address, script_pubkey) in vectors.iter() {
+            let parsed = Address::from_str(address).unwrap();
+            assert_eq!(parsed.script_pubkey().to_hex::<String>(), *script_pubkey);
+            assert_eq!(parsed.to_string(), address.to_lowercase());
+        }
+    }
+
+    // BIP173/BIP350 invalid addresses.
+    #[test]
+    fn test_invalid_segwit_addresses() {
+        use bech32::Error::*;
+        let vectors = [
+            ("tc1p0xlxvlhemja6c4dqv22uapctqupfhlxm9h8z3k2e72q4k9hcz7vq5zuyut", Error::UnknownHrp),
+            ("tc1qw508d6qejxtdg4y5r3zarvary0c5xw7kg3g4ty", Error::UnknownHrp),
+            ("bc1qw508d6qejxtdg4y5r3zarvary0c5xw7kv8f3t5", Error::Bech32(InvalidChecksum)),
+            ("bc1p0xlxvlhemja6c4dqv22uapctqupfhlxm9h8z3k2e72q4k9hcz7vqh2y7hd", Error::WrongVariant),
+            ("tb1z0xlxvlhemja6c4dqv22uapctqupfhlxm9h8z3k2e72q4k9hcz7vqglt7rf", Error::WrongVariant),
+            ("BC1S0XLXVLHEMJA6C4DQV22UAPCTQUPFHLXM9H8Z3K2E72Q4K9HCZ7VQ54WELL", Error::WrongVariant),
+            ("bc1qw508d6qejxtdg4y5r3zarvary0c5xw7kemeawh", Error::WrongVariant),
+            ("tb1q0xlxvlhemja6c4dqv22uapctqupfhlxm9h8z3k2e72q4k9hcz7vq24jc47", Error::WrongVariant),
+            ("bc1p38j9r5y49hruaue7wxjce0updqjuyyx0kh56v8s25huc6995vvpql3jow4", Error::Bech32(InvalidCharacter('o'))),
+            ("BC130XLXVLHEMJA6C4DQV22UAPCTQUPFHLXM9H8Z3K2E72Q4K9HCZ7VQ7ZWS8R", Error::InvalidWitnessVersion(17)),
+            ("BC13W508D6QEJXTDG4Y5R3ZARVARY0C5XW7KN40WF2", Error::InvalidWitnessVersion(17)),
+            ("bc1pw5dgrnzv", Error::InvalidWitnessProgramLength(1)),
+            ("bc1rw5uspcuh", Error::InvalidWitnessProgramLength(1)),
+            ("bc1p0xlxvlhemja6c4dqv22uapctqupfhlxm9h8z3k2e72q4k9hcz7v8n0nx0muaewav253zgeav", Error::InvalidWitnessProgramLength(41)),
+            ("bc10w508d6qejxtdg4y5r3zarvary0c5xw7kw508d6qejxtdg4y5r3zarvary0c5xw7kw5rljs90", Error::InvalidWitnessProgramLength(41)),
+            ("BC1QR508D6QEJXTDG4Y5R3ZARVARYV98GJ9P", Error::InvalidWitnessProgramLength(16)),
+            ("tb1p0xlxvlhemja6c4dqv22uapctqupfhlxm9h8z3k2e72q4k9hcz7vq47Zagq", Error::Bech32(MixedCase)),
+            ("tb1qrp33g0q5c5txsp9arysrx4k6zdkfs4nce4xj0gdcccefvpysxf3q0sL5k7", Error::Bech32(MixedCase)),
+            ("bc1p0xlxvlhemja6c4dqv22uapctqupfhlxm9h8z3k2e72q4k9hcz7v07qwwzcrf", Error::Bech32(InvalidPadding)),
+            ("bc1zw508d6qejxtdg4y5r3zarvaryvqyzf3du", Error::Bech32(InvalidPadding)),
+            ("tb1p0xlxvlhemja6c4dqv22uapctqupfhlxm9h8z3k2e72q4k9hcz7vpggkg4j", Error::Bech32(InvalidPadding)),
+            ("tb1qrp33g0q5c5txsp9arysrx4k6zdkfs4nce4xj0gdcccefvpysxf3pjxtptv", Error::Bech32(InvalidPadding)),
+            ("bc1gmk9yu", Error::InvalidWitnessProgramLength(0)),
+        ];
+        for (address, err) in vectors.iter() {
+            assert_eq!(Address::from_str(address), Err(*err), "{}", address);
+        }
+    }
+
+    #[test]
+    fn test_base58_addresses() {
+        let pubkey = PrivateKey::new(1u8).generate_pubkey();
+        let address = Address::p2pkh(&pubkey, Network::Mainnet);
+        assert_eq!(address.to_string(), "1BgGZ9tcN4rm9KBzDn7KprQz87SZ26SAMH");
+        assert_eq!(address.script_pubkey().to_hex::<String>(), "76a914751e76e8199196d454941c45d1b3a323f1433bd688ac");
+        assert_eq!(Address::from_str("1BgGZ9tcN4rm9KBzDn7KprQz87SZ26SAMH").unwrap(), address);
+
+        let address = Address::from_str("2Mww8dCYPUpKHofjgcXcBCEGmniw9CoaiD2").unwrap();
+        assert_eq!(address.network, Network::Testnet);
+        assert!(matches!(address.payload, Payload::ScriptHash(_)));
+        assert_eq!(address.script_pubkey()[..2], [OP_HASH160, 20]);
+
+        assert_eq!(Address::from_str("1BgGZ9tcN4rm9KBzDn7KprQz87SZ26SAMh"), Err(Error::Base58(base58::Error::BadChecksum)));
+        // A WIF private key.
+        assert_eq!(Address::from_str("KwDiBf89QgGbjEhKnhXJuH7LrciVrZi3qYjgd9M7rFU73sVHnoWn"), Err(Error::InvalidLength(34)));
+    }
+
+    // The first receive address of the BIP44, BIP49, BIP84 and BIP86 test vectors.
+    #[test]
+    fn test_derived_addresses() {
+        let mnemonic: Mnemonic =
+            "abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon about".parse().unwrap();
+        let master = mnemonic.to_master_key("", Network::Mainnet).unwrap();
+        let derive =
+            |master: &ExtendedPrivKey, path: &str| master.derive_priv(&path.parse().unwrap()).unwrap().private_key.generate_pubkey();
+
+        let pubkey = derive(&master, "m/44'/0'/0'/0/0");
+        assert_eq!(Address::p2pkh(&pubkey, Network::Mainnet).to_string(), "1LqBGSKuX5yYUonjxT5qGfpUsXKYYWeabA");
+        let pubkey = derive(&master, "m/49'/0'/0'/0/0");
+        assert_eq!(Address::p2sh_p2wpkh(&pubkey, Network::Mainnet).to_string(), "37VucYSaXLCAsxYyAPfbSi9eh4iEcbShgf");
+        let pubkey = derive(&master, "m/49'/1'/0'/0/0");
+        assert_eq!(Address::p2sh_p2wpkh(&pubkey, Network::Testnet).to_string(), "2Mww8dCYPUpKHofjgcXcBCEGmniw9CoaiD2");
+        let pubkey = derive(&master, "m/84'/0'/0'/0/0");
+        assert_eq!(Address::p2wpkh(&pubkey, Network::Mainnet).to_string(), "bc1qcr8te4kr609gcawutmrza0j4xv80jy8z306fyu");
+        let pubkey = derive(&master, "m/86'/0'/0'/0/0");
+        assert_eq!(pubkey.x_only().to_hex::<String>(), "cc8a4bc64d897bddc5fbc2f670f7a8ba0b386779106cf1223c6fc5d7cd6fc115");
+        let address = Address::p2tr(&pubkey, None, Network::Mainnet).unwrap();
+        assert_eq!(address.to_string(), "bc1p5cyxnuxmeuwuvkwfem96lqzszd02n6xdcjrs20cac6yqjjwudpxqkedrcr");
+        assert_eq!(Address::from_str("bc1p5cyxnuxmeuwuvkwfem96lqzszd02n6xdcjrs20cac6yqjjwudpxqkedrcr").unwrap(), address);
+    }
+
+    #[test]
+    fn test_new() {
+        let program = vec![0xab; 32];
+        let payload = Payload::WitnessProgram { version: 16, program: program.clone() };
+        let address = Address::new(Network::Mainnet, payload.clone()).unwrap();
+        assert_eq!(address.payload(), &payload);
+        assert_eq!(Address::from_str(&address.to_string()).unwrap(), address);
+        assert_eq!(address.script_pubkey()[0], 0x60);
+
+        let payload = Payload::WitnessProgram { version: 17, program: program.clone() };
+        assert_eq!(Address::new(Network::Mainnet, payload), Err(Error::InvalidWitnessVersion(17)));
+        let payload = Payload::WitnessProgram { version: 0xff, program };
+        assert_eq!(Address::new(Network::Testnet, payload), Err(Error::InvalidWitnessVersion(0xff)));
+        let payload = Payload::WitnessProgram { version: 0, program: vec![0xab; 21] };
+        assert_eq!(Address::new(Network::Testnet, payload), Err(Error::InvalidWitnessProgramLength(21)));
+        assert!(Address::new(Network::Testnet, Payload::ScriptHash([0xab; 20])).is_ok());
+    }
+
+    #[test]
+    fn test_p2tr_script_tree() {
+        let pubkey = PrivateKey::new(1u8).generate_pubkey();
+        let merkle_root: Vec<u8> = (0..32).collect();
+        let mut root = [0u8; 32];
+        root.copy_from_slice(&merkle_root);
+        let address = Address::p2tr(&pubkey, Some(&root), Network::Mainnet).unwrap();
+        assert_eq!(address.to_string(), "bc1pqrgc0xqflyg548k5nsmpg6h4tw4633n7zmf5mc6u7g2m07hhvlrs6ggfwq");
+        assert_eq!(
+            Address::p2tr(&pubkey, None, Network::Mainnet).unwrap().to_string(),
+            "bc1pmfr3p9j00pfxjh0zmgp99y8zftmd3s5pmedqhyptwy6lm87hf5sspknck9"
+        );
+        assert_eq!(Address::p2wpkh(&pubkey, Network::Testnet).to_string(), "tb1qw508d6qejxtdg4y5r3zarvary0c5xw7kxpjzsx");
+    }
+}
@@ -0,0 +1,130 @@
+//! Base58 and Base58Check as used by Bitcoin, the checksum is the first 4 bytes of SHA256d of the payload.
+
+use crate::hash::sha256d;
+use std::fmt;
+
+const ALPHABET: &[u8; 58] = b"123456789ABCDEFGHJKLMNPQRSTUVWXYZabcdefghijkmnopqrstuvwxyz";
+const CHECKSUM_SIZE: usize = 4;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Error {
+    /// A character outside of the Base58 alphabet.
+    InvalidCharacter(char),
+    /// The Base58Check checksum doesn't match the payload.
+    BadChecksum,
+    /// The data is too short to contain a checksum.
+    TooShort,
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Error::InvalidCharacter(c) => write!(f, "Invalid Base58 character {:?}", c),
+            Error::BadChecksum => write!(f, "Invalid Base58Check checksum"),
+            Error::TooShort => write!(f, "The data is too short for Base58Check"),
+        }
+    }
+}
+
+pub fn encode(data: &[u8]) -> String {
+    let zeros = data.iter().take_while(|b| **b == 0).count();
+    // Little endian base 58 digits.
+    let mut digits: Vec<u8> = Vec::with_capacity(data.len() * 138 / 100 + 1);
+    for &byte in &data[zeros..] {
+        let mut carry = u32::from(byte);
+        for digit in digits.iter_mut() {
+            carry += u32::from(*digit) << 8;
+            *digit = (carry % 58) as u8;
+            carry /= 58;
+        }
+        while carry > 0 {
+            digits.push((carry % 58) as u8);
+            carry /= 58;
+        }
+    }
+    let mut res = String::with_capacity(zeros + digits.len());
+    res.extend(std::iter::repeat('1').take(zeros));
+    res.extend(digits.iter().rev().map(|d| ALPHABET[*d as usize] as char));
+    res
+}
+
+pub fn decode(s: &str) -> Result<Vec<u8>, Error> {
+    let zeros = s.bytes().take_while(|c| *c == b'1').count();
+    // Little endian base 256 digits.
+    let mut bytes: Vec<u8> = Vec::with_capacity(s.len() * 733 / 1000 + 1);
+    for c in s.chars().skip(zeros) {
+        let mut carry = ALPHABET.iter().position(|a| *a as char == c).ok_or(Error::InvalidCharacter(c))? as u32;
+        for byte in bytes.iter_mut() {
+            carry += u32::from(*byte) * 58;
+            *byte = carry as u8;
+            carry >>= 8;
+        }
+        while carry > 0 {
+            bytes.push(carry as u8);
+            carry >>= 8;
+        }
+    }
+    let mut res = vec![0u8; zeros];
+    res.extend(bytes.iter().rev());
+    Ok(res)
+}
+
+/// Appends the checksum and encodes.
+pub fn encode_check(data: &[u8]) -> String {
+    let mut payload = data.to_vec();
+    payload.extend_from_slice(&sha256d(data)[..CHECKSUM_SIZE]);
+    encode(&payload)
+}
+
+/// Decodes and verifies the checksum, returning the payload without it.
+pub fn decode_check(s: &str) -> Result<Vec<u8>, Error> {
+    let mut data = decode(s)?;
+    if data.len() < CHECKSUM_SIZE {
+        return Err(Error::TooShort);
+    }
+    let checksum = data.split_off(data.len() - CHECKSUM_SIZE);
+    if sha256d(&data)[..CHECKSUM_SIZE] != checksum[..] {
+        return Err(Error::BadChecksum);
+    }
+    Ok(data)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rustc_hex::FromHex;
+
+    #[test]
+    fn test_base58() {
+        let vectors = [
+            ("", ""),
+            ("61", "2g"),
+            ("626262", "a3gV"),
+            ("636363", "aPEr"),
+            ("73696d706c792061206c6f6e6720737472696e67", "2cFupjhnEsSn59qHXstmK2ffpLv2"),
+            ("00eb15231dfceb60925886b67d065299925915aeb172c06647", "1NS17iag9jJgTHD1VXjvLCEnZuQ3rJDE9L"),
+            ("516b6fcd0f", "ABnLTmg"),
+            ("bf4f89001e670274dd", "3SEo3LWLoPntC"),
+            ("572e4794", "3EFU7m"),
+            ("ecac89cad93923c02321", "EJDM8drfXA6uyA"),
+            ("10c8511e", "Rt5zm"),
+            ("00000000000000000000", "1111111111"),
+        ];
+        for (hex, encoded) in vectors.iter() {
+            let data: Vec<u8> = hex.from_hex().unwrap();
+            assert_eq!(encode(&data), *encoded);
+            assert_eq!(decode(encoded).unwrap(), data);
+        }
+        assert_eq!(decode("0OIl"), Err(Error::InvalidCharacter('0')));
+    }
+
+    #[test]
+    fn test_base58_check() {
+        let data: Vec<u8> = "00f54a5851e9372b87810a8e60cdd2e7cfd80b6e31".from_hex().unwrap();
+        assert_eq!(encode_check(&data), "1PMycacnJaSqwwJqjawXBErnLsZ7RkXUAs");
+        assert_eq!(decode_check("1PMycacnJaSqwwJqjawXBErnLsZ7RkXUAs").unwrap(), data);
+        assert_eq!(decode_check("1PMycacnJaSqwwJqjawXBErnLsZ7RkXUAt"), Err(Error::BadChecksum));
+        assert_eq!(decode_check("1111"), Err(Error::BadChecksum));
+        assert_eq!(decode_check("111"), Err(Error::TooShort));
+    }
+}
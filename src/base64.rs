@@ -0,0 +1,75 @@
+//! Standard padded Base64 ([RFC 4648](https://www.rfc-editor.org/rfc/rfc4648#section-4)), used by signed messages and PEM.
+
+const ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+const PAD: u8 = b'=';
+
+pub fn encode(data: &[u8]) -> String {
+    let mut res = String::with_capacity((data.len() + 2) / 3 * 4);
+    for chunk in data.chunks(3) {
+        let group = chunk.iter().enumerate().fold(0u32, |acc, (i, b)| acc | u32::from(*b) << (16 - 8 * i));
+        for i in 0..4 {
+            if i <= chunk.len() {
+                res.push(ALPHABET[((group >> (18 - 6 * i)) & 63) as usize] as char);
+            } else {
+                res.push(PAD as char);
+            }
+        }
+    }
+    res
+}
+
+/// Strict decoding, the input must be padded and the unused bits must be zero.
+pub fn decode(s: &str) -> Result<Vec<u8>, &'static str> {
+    let s = s.as_bytes();
+    if s.len() % 4 != 0 {
+        return Err("Base64 should be padded to a multiple of 4 characters");
+    }
+    let mut res = Vec::with_capacity(s.len() / 4 * 3);
+    for (i, chunk) in s.chunks(4).enumerate() {
+        let is_last = i == s.len() / 4 - 1;
+        let padding = chunk.iter().rev().take_while(|c| **c == PAD).count();
+        if padding > 2 || (padding > 0 && !is_last) {
+            return Err("Invalid Base64 padding");
+        }
+        let mut group = 0u32;
+        for (j, c) in chunk[..4 - padding].iter().enumerate() {
+            let value = ALPHABET.iter().position(|a| a == c).ok_or("Invalid Base64 character")?;
+            group |= (value as u32) << (18 - 6 * j);
+        }
+        let bytes = group.to_be_bytes();
+        if bytes[4 - padding..].iter().any(|b| *b != 0) {
+            return Err("Invalid Base64 padding");
+        }
+        res.extend_from_slice(&bytes[1..4 - padding]);
+    }
+    Ok(res)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // RFC 4648 section 10
+    #[test]
+    fn test_base64() {
+        let vectors = [
+            ("", ""),
+            ("f", "Zg=="),
+            ("fo", "Zm8="),
+            ("foo", "Zm9v"),
+            ("foob", "Zm9vYg=="),
+            ("fooba", "Zm9vYmE="),
+            ("foobar", "Zm9vYmFy"),
+        ];
+        for (data, encoded) in vectors.iter() {
+            assert_eq!(encode(data.as_bytes()), *encoded);
+            assert_eq!(decode(encoded).unwrap(), data.as_bytes());
+        }
+        assert_eq!(encode(&[0xfb, 0xff]), "+/8=");
+        assert!(decode("Zm9").is_err());
+        assert!(decode("Zg=a").is_err());
+        assert!(decode("Zg==Zm9v").is_err());
+        assert!(decode("Zh==").is_err());
+        assert!(decode("Zm9*").is_err());
+    }
+}
@@ -0,0 +1,357 @@
+//! Bech32 and Bech32m, following [BIP173](https://github.com/bitcoin/bips/blob/master/bip-0173.mediawiki)
+//! and [BIP350](https://github.com/bitcoin/bips/blob/master/bip-0350.mediawiki).
+//!
+//! The data part is passed around as 5 bit values, `convert_bits` regroups bytes into them and back.
+//! Other encodings can be built on top, e.g. Nostr keys are `encode("npub", &convert_bits(&x_only, 8, 5, true)?, Variant::Bech32)`,
+//! while BOLT11 invoices need `decode_with_limit` as they're longer than 90 characters.
+
+use std::{collections::HashMap, fmt};
+
+const CHARSET: &[u8; 32] = b"qpzry9x8gf2tvdw0s3jn54khce6mua7l";
+const GENERATOR: [u32; 5] = [0x3b6a_57b2, 0x2650_8e6d, 0x1ea1_19fa, 0x3d42_33dd, 0x2a14_62b3];
+const CHECKSUM_LENGTH: usize = 6;
+/// The BIP173 limit, which the checksum guarantees are designed for.
+pub const MAX_LENGTH: usize = 90;
+const MAX_HRP_LENGTH: usize = 83;
+
+/// Bech32m only differs by the constant the checksum is XORed with.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Variant {
+    Bech32,
+    Bech32m,
+}
+
+impl Variant {
+    fn constant(self) -> u32 {
+        match self {
+            Variant::Bech32 => 1,
+            Variant::Bech32m => 0x2bc8_30a3,
+        }
+    }
+
+    fn from_residue(residue: u32) -> Option<Variant> {
+        [Variant::Bech32, Variant::Bech32m].iter().copied().find(|v| v.constant() == residue)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Error {
+    /// Upper and lower case characters can't be mixed.
+    MixedCase,
+    /// A character outside of the charset in the data part, or outside of ASCII 33-126 in the HRP.
+    InvalidCharacter(char),
+    /// There's no `1` separating the HRP from the data.
+    MissingSeparator,
+    /// The HRP should be 1 to 83 characters.
+    InvalidHrpLength(usize),
+    /// The data part is shorter than the checksum.
+    TooShort,
+    /// The whole string is longer than the limit, 90 characters by default.
+    TooLong(usize),
+    InvalidChecksum,
+    /// More than 4 bits of padding, or non-zero padding, when converting from 5 to 8 bits.
+    InvalidPadding,
+    /// A value doesn't fit in the number of bits it should have.
+    InvalidValue(u8),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Error::MixedCase => write!(f, "Mixed case"),
+            Error::InvalidCharacter(c) => write!(f, "Invalid bech32 character {:?}", c),
+            Error::MissingSeparator => write!(f, "Missing separator"),
+            Error::InvalidHrpLength(len) => write!(f, "Invalid human readable part length: {}", len),
+            Error::TooShort => write!(f, "The data is too short for a checksum"),
+            Error::TooLong(len) => write!(f, "The bech32 string is too long: {} characters", len),
+            Error::InvalidChecksum => write!(f, "Invalid bech32 checksum"),
+            Error::InvalidPadding => write!(f, "Invalid padding"),
+            Error::InvalidValue(value) => write!(f, "Invalid value {}", value),
+        }
+    }
+}
+
+fn polymod(values: impl IntoIterator<Item = u8>) -> u32 {
+    values.into_iter().fold(1, polymod_step)
+}
+
+fn polymod_step(chk: u32, value: u8) -> u32 {
+    let top = chk >> 25;
+    let mut chk = ((chk & 0x01ff_ffff) << 5) ^ u32::from(value);
+    for (i, g) in GENERATOR.iter().enumerate() {
+        if (top >> i) & 1 == 1 {
+            chk ^= g;
+        }
+    }
+    chk
+}
+
+fn hrp_expand(hrp: &str) -> Vec<u8> {
+    let mut res: Vec<u8> = hrp.bytes().map(|c| c >> 5).collect();
+    res.push(0);
+    res.extend(hrp.bytes().map(|c| c & 31));
+    res
+}
+
+fn check_hrp(hrp: &str) -> Result<(), Error> {
+    if hrp.is_empty() || hrp.len() > MAX_HRP_LENGTH {
+        return Err(Error::InvalidHrpLength(hrp.len()));
+    }
+    match hrp.chars().find(|c| !(33..=126).contains(&(*c as u32))) {
+        Some(c) => Err(Error::InvalidCharacter(c)),
+        None => Ok(()),
+    }
+}
+
+/// Encodes 5 bit values, the HRP is lowercased.
+pub fn encode(hrp: &str, data: &[u8], variant: Variant) -> Result<String, Error> {
+    encode_with_limit(hrp, data, variant, MAX_LENGTH)
+}
+
+/// Encodes with a length limit other than 90 characters.
+pub fn encode_with_limit(hrp: &str, data: &[u8], variant: Variant, limit: usize) -> Result<String, Error> {
+    check_hrp(hrp)?;
+    if let Some(value) = data.iter().find(|d| **d >= 32) {
+        return Err(Error::InvalidValue(*value));
+    }
+    let hrp = hrp.to_lowercase();
+    let len = hrp.len() + 1 + data.len() + CHECKSUM_LENGTH;
+    if len > limit {
+        return Err(Error::TooLong(len));
+    }
+    let mut values = hrp_expand(&hrp);
+    values.extend_from_slice(data);
+    values.extend_from_slice(&[0u8; CHECKSUM_LENGTH]);
+    let checksum = polymod(values) ^ variant.constant();
+
+    let mut res = hrp;
+    res.push('1');
+    res.extend(data.iter().map(|d| CHARSET[*d as usize] as char));
+    res.extend((0..CHECKSUM_LENGTH).map(|i| CHARSET[((checksum >> (5 * (5 - i))) & 31) as usize] as char));
+    Ok(res)
+}
+
+/// Decodes into the lowercase HRP and the 5 bit values without the checksum.
+pub fn decode(s: &str) -> Result<(String, Vec<u8>, Variant), Error> {
+    decode_with_limit(s, MAX_LENGTH)
+}
+
+/// Decodes with a length limit other than 90 characters.
+pub fn decode_with_limit(s: &str, limit: usize) -> Result<(String, Vec<u8>, Variant), Error> {
+    let (hrp, mut data) = parse(s, limit)?;
+    let mut values = hrp_expand(&hrp);
+    values.extend_from_slice(&data);
+    let variant = Variant::from_residue(polymod(values)).ok_or(Error::InvalidChecksum)?;
+    data.truncate(data.len() - CHECKSUM_LENGTH);
+    Ok((hrp, data, variant))
+}
+
+/// Finds the positions in `s` of up to 2 substituted characters in the data part, the most a checksum can locate unambiguously.
+/// Returns no positions if the checksum is valid, and `InvalidChecksum` if there are more errors.
+pub fn locate_errors(s: &str, variant: Variant) -> Result<Vec<usize>, Error> {
+    let (hrp, data) = parse(s, MAX_LENGTH)?;
+    let mut values = hrp_expand(&hrp);
+    values.extend_from_slice(&data);
+    let residue = polymod(values) ^ variant.constant();
+    if residue == 0 {
+        return Ok(Vec::new());
+    }
+
+    // The checksum is linear, so an error `e` at a position contributes the checksum of `e` followed by zeros,
+    // independently of the rest of the string. `syndromes[i][e - 1]` is that contribution at `data[i]`.
+    let mut syndromes = vec![[0u32; 31]; data.len()];
+    let mut current: Vec<u32> = (1..32).collect();
+    for syndrome in syndromes.iter_mut().rev() {
+        syndrome.copy_from_slice(&current);
+        current.iter_mut().for_each(|c| *c = polymod_step(*c, 0));
+    }
+    let position = |i: usize| hrp.len() + 1 + i;
+
+    let mut single = HashMap::new();
+    for (i, syndrome) in syndromes.iter().enumerate() {
+        for s in syndrome.iter() {
+            if *s == residue {
+                return Ok(vec![position(i)]);
+            }
+            single.insert(*s, i);
+        }
+    }
+    for (i, syndrome) in syndromes.iter().enumerate() {
+        for s in syndrome.iter() {
+            match single.get(&(residue ^ s)) {
+                Some(&j) if j > i => return Ok(vec![position(i), position(j)]),
+                _ => (),
+            }
+        }
+    }
+    Err(Error::InvalidChecksum)
+}
+
+// Splits into the lowercase HRP and the 5 bit values including the checksum.
+fn parse(s: &str, limit: usize) -> Result<(String, Vec<u8>), Error> {
+    if s.len() > limit {
+        return Err(Error::TooLong(s.len()));
+    }
+    if s.chars().any(|c| c.is_ascii_lowercase()) && s.chars().any(|c| c.is_ascii_uppercase()) {
+        return Err(Error::MixedCase);
+    }
+    let s = s.to_lowercase();
+    let separator = s.rfind('1').ok_or(Error::MissingSeparator)?;
+    let (hrp, data) = (&s[..separator], &s[separator + 1..]);
+    check_hrp(hrp)?;
+    if data.len() < CHECKSUM_LENGTH {
+        return Err(Error::TooShort);
+    }
+    let data = data
+        .chars()
+        .map(|c| CHARSET.iter().position(|x| *x as char == c).map(|d| d as u8).ok_or(Error::InvalidCharacter(c)))
+        .collect::<Result<Vec<u8>, _>>()?;
+    Ok((hrp.to_owned(), data))
+}
+
+/// Regroups `from` bit values into `to` bit values, `pad` adds zero bits to complete the last value,
+/// otherwise the leftover bits should be fewer than `from` and zero.
+pub fn convert_bits(data: &[u8], from: u32, to: u32, pad: bool) -> Result<Vec<u8>, Error> {
+    let mut acc = 0u32;
+    let mut bits = 0u32;
+    let max = (1u32 << to) - 1;
+    let max_acc = (1u32 << (from + to - 1)) - 1;
+    let mut res = Vec::with_capacity((data.len() * from as usize + to as usize - 1) / to as usize);
+    for value in data {
+        if u32::from(*value) >> from != 0 {
+            return Err(Error::InvalidValue(*value));
+        }
+        acc = ((acc << from) | u32::from(*value)) & max_acc;
+        bits += from;
+        while bits >= to {
+            bits -= to;
+            res.push(((acc >> bits) & max) as u8);
+        }
+    }
+    if pad {
+        if bits > 0 {
+            res.push(((acc << (to - bits)) & max) as u8);
+        }
+    } else if bits >= from || (acc << (to - bits)) & max != 0 {
+        return Err(Error::InvalidPadding);
+    }
+    Ok(res)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rustc_hex::FromHex;
+
+    #[test]
+    fn test_valid_strings() {
+        // BIP173 and BIP350 valid strings.
+        let vectors = [
+            ("A12UEL5L", Variant::Bech32),
+            ("a12uel5l", Variant::Bech32),
+            ("an83characterlonghumanreadablepartthatcontainsthenumber1andtheexcludedcharactersbio1tt5tgs", Variant::Bech32),
+            ("abcdef1qpzry9x8gf2tvdw0s3jn54khce6mua7lmqqqxw", Variant::Bech32),
+            ("11qqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqc8247j", Variant::Bech32),
+            ("split1checkupstagehandshakeupstreamerranterredcaperred2y9e3w", Variant::Bech32),
+            ("?1ezyfcl", Variant::Bech32),
+            ("A1LQFN3A", Variant::Bech32m),
+            ("a1lqfn3a", Variant::Bech32m),
+            ("an83characterlonghumanreadablepartthatcontainsthetheexcludedcharactersbioandnumber11sg7hg6", Variant::Bech32m),
+            ("abcdef1l7aum6echk45nj3s0wdvt2fg8x9yrzpqzd3ryx", Variant::Bech32m),
+            ("11llllllllllllllllllllllllllllllllllllllllllllllllllllllllllllllllllllllllllllllllllludsr8", Variant::Bech32m),
+            ("split1checkupstagehandshakeupstreamerranterredcaperredlc445v", Variant::Bech32m),
+            ("?1v759aa", Variant::Bech32m),
+        ];
+        for (s, variant) in vectors.iter() {
+            let (hrp, data, decoded_variant) = decode(s).unwrap();
+            assert_eq!(decoded_variant, *variant);
+            assert_eq!(encode(&hrp, &data, *variant).unwrap(), s.to_lowercase());
+        }
+    }
+
+    #[test]
+    fn test_invalid_strings() {
+        // BIP173 and BIP350 invalid strings.
+        let vectors = [
+            ("\u{20}1nwldj5", Error::InvalidCharacter(' ')),
+            ("\u{7f}1axkwrx", Error::InvalidCharacter('\u{7f}')),
+            ("\u{80}1eym55h", Error::InvalidCharacter('\u{80}')),
+            ("an84characterslonghumanreadablepartthatcontainsthenumber1andtheexcludedcharactersbio1569pvx", Error::TooLong(91)),
+            ("pzry9x0s0muk", Error::MissingSeparator),
+            ("1pzry9x0s0muk", Error::InvalidHrpLength(0)),
+            ("x1b4n0q5v", Error::InvalidCharacter('b')),
+            ("li1dgmt3", Error::TooShort),
+            ("de1lg7wt\u{ff}", Error::InvalidCharacter('\u{ff}')),
+            ("A1G7SGD8", Error::InvalidChecksum),
+            ("10a06t8", Error::InvalidHrpLength(0)),
+            ("1qzzfhee", Error::InvalidHrpLength(0)),
+            ("\u{20}1xj0phk", Error::InvalidCharacter(' ')),
+            ("\u{7f}1g6xzxy", Error::InvalidCharacter('\u{7f}')),
+            ("\u{80}1vctc34", Error::InvalidCharacter('\u{80}')),
+            ("an84characterslonghumanreadablepartthatcontainsthetheexcludedcharactersbioandnumber11d6pts4", Error::TooLong(91)),
+            ("qyrz8wqd2c9m", Error::MissingSeparator),
+            ("1qyrz8wqd2c9m", Error::InvalidHrpLength(0)),
+            ("y1b0jsk6g", Error::InvalidCharacter('b')),
+            ("lt1igcx5c0", Error::InvalidCharacter('i')),
+            ("in1muywd", Error::TooShort),
+            ("mm1crxm3i", Error::InvalidCharacter('i')),
+            ("au1s5cgom", Error::InvalidCharacter('o')),
+            ("M1VUXWEZ", Error::InvalidChecksum),
+            ("16plkw9", Error::InvalidHrpLength(0)),
+            ("1p2gdwpf", Error::InvalidHrpLength(0)),
+        ];
+        for (s, err) in vectors.iter() {
+            assert_eq!(decode(s), Err(*err), "{:?}", s);
+        }
+    }
+
+    // NIP-19 keys.
+    #[test]
+    fn test_nostr_keys() {
+        let pubkey: Vec<u8> = "3bf0c63fcb93463407af97a5e5ee64fa883d107ef9e558472c4eb9aaaefa459d".from_hex().unwrap();
+        let npub = encode("npub", &convert_bits(&pubkey, 8, 5, true).unwrap(), Variant::Bech32).unwrap();
+        assert_eq!(npub, "npub180cvv07tjdrrgpa0j7j7tmnyl2yr6yr7l8j4s3evf6u64th6gkwsyjh6w6");
+        let (hrp, data, variant) = decode(&npub).unwrap();
+        assert_eq!((hrp.as_str(), variant), ("npub", Variant::Bech32));
+        assert_eq!(convert_bits(&data, 5, 8, false).unwrap(), pubkey);
+
+        let seckey: Vec<u8> = "67dea2ed018072d675f5415ecfaed7d2597555e202d85b3d65ea4e58d2d92ffa".from_hex().unwrap();
+        let nsec = encode("nsec", &convert_bits(&seckey, 8, 5, true).unwrap(), Variant::Bech32).unwrap();
+        assert_eq!(nsec, "nsec1vl029mgpspedva04g90vltkh6fvh240zqtv9k0t9af8935ke9laqsnlfe5");
+    }
+
+    #[test]
+    fn test_length_limit() {
+        let data = vec![0u8; 200];
+        assert_eq!(encode("lnbc", &data, Variant::Bech32), Err(Error::TooLong(211)));
+        let s = encode_with_limit("lnbc", &data, Variant::Bech32, 1023).unwrap();
+        assert_eq!(decode(&s), Err(Error::TooLong(211)));
+        assert_eq!(decode_with_limit(&s, 1023).unwrap(), ("lnbc".to_owned(), data, Variant::Bech32));
+
+        assert_eq!(encode("a", &[32], Variant::Bech32), Err(Error::InvalidValue(32)));
+        assert_eq!(convert_bits(&[32], 5, 8, true), Err(Error::InvalidValue(32)));
+        assert_eq!(encode(&"a".repeat(84), &[], Variant::Bech32), Err(Error::InvalidHrpLength(84)));
+    }
+
+    #[test]
+    fn test_locate_errors() {
+        let valid = "bc1qw508d6qejxtdg4y5r3zarvary0c5xw7kv8f3t4";
+        assert_eq!(locate_errors(valid, Variant::Bech32), Ok(vec![]));
+
+        let substitute = |positions: &[usize]| -> String {
+            let mut s = valid.as_bytes().to_vec();
+            for &i in positions {
+                s[i] = if s[i] == b'q' { b'p' } else { b'q' };
+            }
+            String::from_utf8(s).unwrap()
+        };
+        for errors in [vec![3], vec![20], vec![41], vec![4, 5], vec![3, 41], vec![10, 30]].iter() {
+            let corrupted = substitute(errors);
+            assert_eq!(decode(&corrupted), Err(Error::InvalidChecksum));
+            assert_eq!(locate_errors(&corrupted, Variant::Bech32).as_ref(), Ok(errors), "{}", corrupted);
+        }
+
+        // The checksum is only valid as bech32, so as bech32m it looks like too many errors.
+        assert_eq!(locate_errors(valid, Variant::Bech32m), Err(Error::InvalidChecksum));
+    }
+}
@@ -0,0 +1,771 @@
+//! Hierarchical deterministic keys, following [BIP32](https://github.com/bitcoin/bips/blob/master/bip-0032.mediawiki).
+//!
+//! Extended keys are displayed and parsed as their Base58Check `xprv`/`xpub` (`tprv`/`tpub` on testnet) encoding.
+
+use crate::base58;
+use crate::hmac_sha2::HmacSha512;
+use crate::network::Network;
+use crate::secp256k1::{get_context, PrivateKey, PublicKey};
+use num_bigint::{BigInt, Sign};
+use std::{fmt, str::FromStr};
+
+const XPRV: [u8; 4] = [0x04, 0x88, 0xAD, 0xE4];
+const XPUB: [u8; 4] = [0x04, 0x88, 0xB2, 0x1E];
+const TPRV: [u8; 4] = [0x04, 0x35, 0x83, 0x94];
+const TPUB: [u8; 4] = [0x04, 0x35, 0x87, 0xCF];
+const HARDENED: u32 = 1 << 31;
+const ENCODED_SIZE: usize = 78;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Error {
+    /// The seed should be between 16 and 64 bytes.
+    InvalidSeedLength,
+    /// The derived key is invalid (probability lower than 1 in 2^127), the next index should be used instead.
+    InvalidDerivation,
+    /// Public derivation can't derive hardened children.
+    CannotDeriveHardened,
+    /// The key is already at depth 255.
+    MaxDepthExceeded,
+    /// A child index should be lower than 2^31 before applying the hardened flag.
+    InvalidChildNumber,
+    /// A derivation path should look like `m/84'/0'/0'/0/5`.
+    InvalidDerivationPath,
+    Base58(base58::Error),
+    /// An encoded extended key should be 78 bytes.
+    InvalidLength,
+    UnknownVersion,
+    /// A master key (depth 0) with a parent fingerprint or a child number.
+    InvalidMasterKey,
+    InvalidPrivateKey,
+    InvalidPublicKey,
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let s = match self {
+            Error::InvalidSeedLength => "The seed should be between 16 and 64 bytes",
+            Error::InvalidDerivation => "The derived key is invalid",
+            Error::CannotDeriveHardened => "Can't derive a hardened child from a public key",
+            Error::MaxDepthExceeded => "The maximum depth was exceeded",
+            Error::InvalidChildNumber => "Invalid child number",
+            Error::InvalidDerivationPath => "Invalid derivation path",
+            Error::Base58(e) => return write!(f, "{}", e),
+            Error::InvalidLength => "An extended key should be 78 bytes",
+            Error::UnknownVersion => "Unknown extended key version",
+            Error::InvalidMasterKey => "A master key can't have a parent fingerprint or a child number",
+            Error::InvalidPrivateKey => "Invalid private key",
+            Error::InvalidPublicKey => "Invalid public key",
+        };
+        write!(f, "{}", s)
+    }
+}
+
+impl From<base58::Error> for Error {
+    fn from(e: base58::Error) -> Self {
+        Error::Base58(e)
+    }
+}
+
+/// The index of a child key, hardened children can only be derived from the private key.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ChildNumber {
+    Normal(u32),
+    Hardened(u32),
+}
+
+impl ChildNumber {
+    pub fn from_normal_idx(index: u32) -> Result<ChildNumber, Error> {
+        if index & HARDENED != 0 {
+            return Err(Error::InvalidChildNumber);
+        }
+        Ok(ChildNumber::Normal(index))
+    }
+
+    pub fn from_hardened_idx(index: u32) -> Result<ChildNumber, Error> {
+        if index & HARDENED != 0 {
+            return Err(Error::InvalidChildNumber);
+        }
+        Ok(ChildNumber::Hardened(index))
+    }
+
+    pub fn is_hardened(&self) -> bool {
+        matches!(self, ChildNumber::Hardened(_))
+    }
+}
+
+/// From the serialized index, where the top bit is the hardened flag.
+impl From<u32> for ChildNumber {
+    fn from(index: u32) -> Self {
+        if index & HARDENED != 0 {
+            ChildNumber::Hardened(index ^ HARDENED)
+        } else {
+            ChildNumber::Normal(index)
+        }
+    }
+}
+
+impl From<ChildNumber> for u32 {
+    fn from(child: ChildNumber) -> Self {
+        match child {
+            ChildNumber::Normal(index) => index,
+            ChildNumber::Hardened(index) => index | HARDENED,
+        }
+    }
+}
+
+impl fmt::Display for ChildNumber {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ChildNumber::Normal(index) => write!(f, "{}", index),
+            ChildNumber::Hardened(index) => write!(f, "{}'", index),
+        }
+    }
+}
+
+impl FromStr for ChildNumber {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (index, hardened) = match s.strip_suffix(|c| c == '\'' || c == 'h' || c == 'H') {
+            Some(index) => (index, true),
+            None => (s, false),
+        };
+        if index.is_empty() || !index.bytes().all(|c| c.is_ascii_digit()) {
+            return Err(Error::InvalidDerivationPath);
+        }
+        let index = index.parse().map_err(|_| Error::InvalidChildNumber)?;
+        if hardened {
+            ChildNumber::from_hardened_idx(index)
+        } else {
+            ChildNumber::from_normal_idx(index)
+        }
+    }
+}
+
+/// A derivation path from the master key, like `m/84'/0'/0'/0/5`.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub struct DerivationPath(Vec<ChildNumber>);
+
+impl DerivationPath {
+    pub fn master() -> DerivationPath {
+        DerivationPath(Vec::new())
+    }
+
+    /// Returns the path extended with `child`.
+    pub fn child(&self, child: ChildNumber) -> DerivationPath {
+        let mut path = self.0.clone();
+        path.push(child);
+        DerivationPath(path)
+    }
+}
+
+impl AsRef<[ChildNumber]> for DerivationPath {
+    fn as_ref(&self) -> &[ChildNumber] {
+        &self.0
+    }
+}
+
+impl From<Vec<ChildNumber>> for DerivationPath {
+    fn from(path: Vec<ChildNumber>) -> Self {
+        DerivationPath(path)
+    }
+}
+
+impl fmt::Display for DerivationPath {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "m")?;
+        for child in &self.0 {
+            write!(f, "/{}", child)?;
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for DerivationPath {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut parts = s.split('/');
+        if parts.next() != Some("m") {
+            return Err(Error::InvalidDerivationPath);
+        }
+        parts.map(ChildNumber::from_str).collect::<Result<_, _>>().map(DerivationPath)
+    }
+}
+
+pub struct ExtendedPrivKey {
+    pub network: Network,
+    pub depth: u8,
+    pub parent_fingerprint: [u8; 4],
+    pub child_number: ChildNumber,
+    pub chain_code: [u8; 32],
+    pub private_key: PrivateKey,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct ExtendedPubKey {
+    pub network: Network,
+    pub depth: u8,
+    pub parent_fingerprint: [u8; 4],
+    pub child_number: ChildNumber,
+    pub chain_code: [u8; 32],
+    pub public_key: PublicKey,
+}
+
+impl ExtendedPrivKey {
+    /// The master key is `HMAC-SHA512("Bitcoin seed", seed)`, split into the private key and the chain code.
+    pub fn new_master(network: Network, seed: &[u8]) -> Result<ExtendedPrivKey, Error> {
+        if seed.len() < 16 || seed.len() > 64 {
+            return Err(Error::InvalidSeedLength);
+        }
+        let i = HmacSha512::quick(b"Bitcoin seed", seed);
+        let private_key = parse_private_key(&i[..32]).ok_or(Error::InvalidDerivation)?;
+        Ok(ExtendedPrivKey {
+            network,
+            depth: 0,
+            parent_fingerprint: [0u8; 4],
+            child_number: ChildNumber::Normal(0),
+            chain_code: chain_code(&i),
+            private_key,
+        })
+    }
+
+    /// `CKDpriv`, derives the private child key.
+    pub fn ckd_priv(&self, child: ChildNumber) -> Result<ExtendedPrivKey, Error> {
+        let mut hmac = HmacSha512::new(&self.chain_code);
+        match child {
+            ChildNumber::Normal(_) => hmac.input(&self.private_key.generate_pubkey().compressed()),
+            ChildNumber::Hardened(_) => {
+                hmac.input(&[0]);
+                hmac.input(&self.private_key.serialize());
+            }
+        }
+        hmac.input(&u32::from(child).to_be_bytes());
+        let i = hmac.finalize();
+
+        let order = &get_context().order;
+        let tweak = parse_private_key(&i[..32]).ok_or(Error::InvalidDerivation)?;
+        let scalar = (tweak.scalar + &self.private_key.scalar) % order;
+        if scalar == BigInt::from(0) {
+            return Err(Error::InvalidDerivation);
+        }
+        Ok(ExtendedPrivKey {
+            network: self.network,
+            depth: self.depth.checked_add(1).ok_or(Error::MaxDepthExceeded)?,
+            parent_fingerprint: self.fingerprint(),
+            child_number: child,
+            chain_code: chain_code(&i),
+            private_key: PrivateKey::new(scalar),
+        })
+    }
+
+    pub fn derive_priv(&self, path: &DerivationPath) -> Result<ExtendedPrivKey, Error> {
+        path.0.iter().try_fold(self.clone(), |key, child| key.ckd_priv(*child))
+    }
+
+    pub fn to_extended_pubkey(&self) -> ExtendedPubKey {
+        ExtendedPubKey {
+            network: self.network,
+            depth: self.depth,
+            parent_fingerprint: self.parent_fingerprint,
+            child_number: self.child_number,
+            chain_code: self.chain_code,
+            public_key: self.private_key.generate_pubkey(),
+        }
+    }
+
+    /// The HASH160 of the public key.
+    pub fn identifier(&self) -> [u8; 20] {
+        self.private_key.generate_pubkey().hash160()
+    }
+
+    /// The first 4 bytes of the identifier.
+    pub fn fingerprint(&self) -> [u8; 4] {
+        fingerprint(&self.identifier())
+    }
+
+    pub fn encode(&self) -> [u8; ENCODED_SIZE] {
+        let version = match self.network {
+            Network::Mainnet => XPRV,
+            Network::Testnet => TPRV,
+        };
+        let mut key = [0u8; 33];
+        key[1..].copy_from_slice(&self.private_key.serialize());
+        encode(version, self.depth, self.parent_fingerprint, self.child_number, &self.chain_code, &key)
+    }
+
+    pub fn decode(data: &[u8]) -> Result<ExtendedPrivKey, Error> {
+        let (version, depth, parent_fingerprint, child_number, chain_code, key) = decode(data)?;
+        let network = match version {
+            XPRV => Network::Mainnet,
+            TPRV => Network::Testnet,
+            _ => return Err(Error::UnknownVersion),
+        };
+        if key[0] != 0 {
+            return Err(Error::InvalidPrivateKey);
+        }
+        let private_key = parse_private_key(&key[1..]).ok_or(Error::InvalidPrivateKey)?;
+        Ok(ExtendedPrivKey { network, depth, parent_fingerprint, child_number, chain_code, private_key })
+    }
+}
+
+impl ExtendedPubKey {
+    /// `CKDpub`, derives the public child key, only possible for non-hardened children.
+    #[allow(non_snake_case)]
+    pub fn ckd_pub(&self, child: ChildNumber) -> Result<ExtendedPubKey, Error> {
+        if child.is_hardened() {
+            return Err(Error::CannotDeriveHardened);
+        }
+        let mut hmac = HmacSha512::new(&self.chain_code);
+        hmac.input(&self.public_key.clone().compressed());
+        hmac.input(&u32::from(child).to_be_bytes());
+        let i = hmac.finalize();
+
+        let tweak = parse_private_key(&i[..32]).ok_or(Error::InvalidDerivation)?;
+        let K = (&tweak.scalar * get_context().generator()) + self.public_key.point.clone();
+        if K.is_on_infinity() {
+            return Err(Error::InvalidDerivation);
+        }
+        Ok(ExtendedPubKey {
+            network: self.network,
+            depth: self.depth.checked_add(1).ok_or(Error::MaxDepthExceeded)?,
+            parent_fingerprint: self.fingerprint(),
+            child_number: child,
+            chain_code: chain_code(&i),
+            public_key: PublicKey::from(K),
+        })
+    }
+
+    pub fn derive_pub(&self, path: &DerivationPath) -> Result<ExtendedPubKey, Error> {
+        path.0.iter().try_fold(self.clone(), |key, child| key.ckd_pub(*child))
+    }
+
+    /// The HASH160 of the public key.
+    pub fn identifier(&self) -> [u8; 20] {
+        self.public_key.hash160()
+    }
+
+    /// The first 4 bytes of the identifier.
+    pub fn fingerprint(&self) -> [u8; 4] {
+        fingerprint(&self.identifier())
+    }
+
+    pub fn encode(&self) -> [u8; ENCODED_SIZE] {
+        let version = match self.network {
+            Network::Mainnet => XPUB,
+            Network::Testnet => TPUB,
+        };
+        let key = self.public_key.clone().compressed();
+        encode(version, self.depth, self.parent_fingerprint, self.child_number, &self.chain_code, &key)
+    }
+
+    pub fn decode(data: &[u8]) -> Result<ExtendedPubKey, Error> {
+        let (version, depth, parent_fingerprint, child_number, chain_code, key) = decode(data)?;
+        let network = match version {
+            XPUB => Network::Mainnet,
+            TPUB => Network::Testnet,
+            _ => return Err(Error::UnknownVersion),
+        };
+        if key[0] != 0x02 && key[0] != 0x03 {
+            return Err(Error::InvalidPublicKey);
+        }
+        let public_key = PublicKey::from_compressed(&key).map_err(|_| Error::InvalidPublicKey)?;
+        Ok(ExtendedPubKey { network, depth, parent_fingerprint, child_number, chain_code, public_key })
+    }
+}
+
+impl Clone for ExtendedPrivKey {
+    fn clone(&self) -> Self {
+        ExtendedPrivKey {
+            network: self.network,
+            depth: self.depth,
+            parent_fingerprint: self.parent_fingerprint,
+            child_number: self.child_number,
+            chain_code: self.chain_code,
+            private_key: PrivateKey::new(self.private_key.scalar.clone()),
+        }
+    }
+}
+
+impl fmt::Display for ExtendedPrivKey {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", base58::encode_check(&self.encode()))
+    }
+}
+
+impl fmt::Display for ExtendedPubKey {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", base58::encode_check(&self.encode()))
+    }
+}
+
+impl FromStr for ExtendedPrivKey {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        ExtendedPrivKey::decode(&base58::decode_check(s)?)
+    }
+}
+
+impl FromStr for ExtendedPubKey {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        ExtendedPubKey::decode(&base58::decode_check(s)?)
+    }
+}
+
+// Returns `None` if the scalar is zero or not lower than the order.
+fn parse_private_key(ser: &[u8]) -> Option<PrivateKey> {
+    let scalar = BigInt::from_bytes_be(Sign::Plus, ser);
+    if scalar == BigInt::from(0) || scalar >= get_context().order {
+        return None;
+    }
+    Some(PrivateKey::new(scalar))
+}
+
+fn chain_code(i: &[u8; 64]) -> [u8; 32] {
+    let mut chain_code = [0u8; 32];
+    chain_code.copy_from_slice(&i[32..]);
+    chain_code
+}
+
+fn fingerprint(identifier: &[u8; 20]) -> [u8; 4] {
+    let mut fingerprint = [0u8; 4];
+    fingerprint.copy_from_slice(&identifier[..4]);
+    fingerprint
+}
+
+// version (4) || depth (1) || parent fingerprint (4) || child number (4) || chain code (32) || key (33)
+fn encode(
+    version: [u8; 4],
+    depth: u8,
+    parent: [u8; 4],
+    child: ChildNumber,
+    chain_code: &[u8; 32],
+    key: &[u8; 33],
+) -> [u8; ENCODED_SIZE] {
+    let mut res = [0u8; ENCODED_SIZE];
+    res[..4].copy_from_slice(&version);
+    res[4] = depth;
+    res[5..9].copy_from_slice(&parent);
+    res[9..13].copy_from_slice(&u32::from(child).to_be_bytes());
+    res[13..45].copy_from_slice(chain_code);
+    res[45..].copy_from_slice(key);
+    res
+}
+
+type Decoded = ([u8; 4], u8, [u8; 4], ChildNumber, [u8; 32], [u8; 33]);
+
+fn decode(data: &[u8]) -> Result<Decoded, Error> {
+    if data.len() != ENCODED_SIZE {
+        return Err(Error::InvalidLength);
+    }
+    let mut version = [0u8; 4];
+    version.copy_from_slice(&data[..4]);
+    let depth = data[4];
+    let mut parent = [0u8; 4];
+    parent.copy_from_slice(&data[5..9]);
+    let mut child = [0u8; 4];
+    child.copy_from_slice(&data[9..13]);
+    let child = u32::from_be_bytes(child);
+    if depth == 0 && (parent != [0u8; 4] || child != 0) {
+        return Err(Error::InvalidMasterKey);
+    }
+    let mut chain_code = [0u8; 32];
+    chain_code.copy_from_slice(&data[13..45]);
+    let mut key = [0u8; 33];
+    key.copy_from_slice(&data[45..]);
+    Ok((version, depth, parent, ChildNumber::from(child), chain_code, key))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rustc_hex::FromHex;
+
+    // Derives each step from the previous one and checks the serialized keys,
+    // and that public derivation agrees for non-hardened children.
+    fn test_chain(seed: &str, steps: &[(&str, &str, &str)]) {
+        let seed: Vec<u8> = seed.from_hex().unwrap();
+        let mut key = ExtendedPrivKey::new_master(Network::Mainnet, &seed).unwrap();
+        let mut path = DerivationPath::master();
+        for (i, (child, xprv, xpub)) in steps.iter().enumerate() {
+            if i > 0 {
+                let child = ChildNumber::from_str(child).unwrap();
+                let parent = key.to_extended_pubkey();
+                key = key.ckd_priv(child).unwrap();
+                path = path.child(child);
+                if !child.is_hardened() {
+                    assert_eq!(parent.ckd_pub(child).unwrap(), key.to_extended_pubkey());
+                } else {
+                    assert_eq!(parent.ckd_pub(child), Err(Error::CannotDeriveHardened));
+                }
+            }
+            assert_eq!(key.to_string(), *xprv, "{}", path);
+            assert_eq!(key.to_extended_pubkey().to_string(), *xpub, "{}", path);
+            assert_eq!(ExtendedPrivKey::from_str(xprv).unwrap().to_string(), *xprv);
+            assert_eq!(ExtendedPubKey::from_str(xpub).unwrap(), key.to_extended_pubkey());
+        }
+    }
+
+    #[test]
+    fn test_vector_1() {
+        test_chain(
+            "000102030405060708090a0b0c0d0e0f",
+            &[
+                (
+                    "m",
+                    "xprv9s21ZrQH143K3QTDL4LXw2F7HEK3wJUD2nW2nRk4stbPy6cq3jPPqjiChkVvvNKmPGJxWUtg6LnF5kejMRNNU3TGtRBeJgk33yuGBxrMPHi",
+                    "xpub661MyMwAqRbcFtXgS5sYJABqqG9YLmC4Q1Rdap9gSE8NqtwybGhePY2gZ29ESFjqJoCu1Rupje8YtGqsefD265TMg7usUDFdp6W1EGMcet8",
+                ),
+                (
+                    "0'",
+                    "xprv9uHRZZhk6KAJC1avXpDAp4MDc3sQKNxDiPvvkX8Br5ngLNv1TxvUxt4cV1rGL5hj6KCesnDYUhd7oWgT11eZG7XnxHrnYeSvkzY7d2bhkJ7",
+                    "xpub68Gmy5EdvgibQVfPdqkBBCHxA5htiqg55crXYuXoQRKfDBFA1WEjWgP6LHhwBZeNK1VTsfTFUHCdrfp1bgwQ9xv5ski8PX9rL2dZXvgGDnw",
+                ),
+                (
+                    "1",
+                    "xprv9wTYmMFdV23N2TdNG573QoEsfRrWKQgWeibmLntzniatZvR9BmLnvSxqu53Kw1UmYPxLgboyZQaXwTCg8MSY3H2EU4pWcQDnRnrVA1xe8fs",
+                    "xpub6ASuArnXKPbfEwhqN6e3mwBcDTgzisQN1wXN9BJcM47sSikHjJf3UFHKkNAWbWMiGj7Wf5uMash7SyYq527Hqck2AxYysAA7xmALppuCkwQ",
+                ),
+                (
+                    "2'",
+                    "xprv9z4pot5VBttmtdRTWfWQmoH1taj2axGVzFqSb8C9xaxKymcFzXBDptWmT7FwuEzG3ryjH4ktypQSAewRiNMjANTtpgP4mLTj34bhnZX7UiM",
+                    "xpub6D4BDPcP2GT577Vvch3R8wDkScZWzQzMMUm3PWbmWvVJrZwQY4VUNgqFJPMM3No2dFDFGTsxxpG5uJh7n7epu4trkrX7x7DogT5Uv6fcLW5",
+                ),
+                (
+                    "2",
+                    "xprvA2JDeKCSNNZky6uBCviVfJSKyQ1mDYahRjijr5idH2WwLsEd4Hsb2Tyh8RfQMuPh7f7RtyzTtdrbdqqsunu5Mm3wDvUAKRHSC34sJ7in334",
+                    "xpub6FHa3pjLCk84BayeJxFW2SP4XRrFd1JYnxeLeU8EqN3vDfZmbqBqaGJAyiLjTAwm6ZLRQUMv1ZACTj37sR62cfN7fe5JnJ7dh8zL4fiyLHV",
+                ),
+                (
+                    "1000000000",
+                    "xprvA41z7zogVVwxVSgdKUHDy1SKmdb533PjDz7J6N6mV6uS3ze1ai8FHa8kmHScGpWmj4WggLyQjgPie1rFSruoUihUZREPSL39UNdE3BBDu76",
+                    "xpub6H1LXWLaKsWFhvm6RVpEL9P4KfRZSW7abD2ttkWP3SSQvnyA8FSVqNTEcYFgJS2UaFcxupHiYkro49S8yGasTvXEYBVPamhGW6cFJodrTHy",
+                ),
+            ],
+        );
+    }
+
+    #[test]
+    fn test_vector_2() {
+        test_chain(
+            "fffcf9f6f3f0edeae7e4e1dedbd8d5d2cfccc9c6c3c0bdbab7b4b1aeaba8a5a29f9c999693908d8a8784817e7b7875726f6c696663605d5a5754514e4b484542",
+            &[
+                (
+                    "m",
+                    "xprv9s21ZrQH143K31xYSDQpPDxsXRTUcvj2iNHm5NUtrGiGG5e2DtALGdso3pGz6ssrdK4PFmM8NSpSBHNqPqm55Qn3LqFtT2emdEXVYsCzC2U",
+                    "xpub661MyMwAqRbcFW31YEwpkMuc5THy2PSt5bDMsktWQcFF8syAmRUapSCGu8ED9W6oDMSgv6Zz8idoc4a6mr8BDzTJY47LJhkJ8UB7WEGuduB",
+                ),
+                (
+                    "0",
+                    "xprv9vHkqa6EV4sPZHYqZznhT2NPtPCjKuDKGY38FBWLvgaDx45zo9WQRUT3dKYnjwih2yJD9mkrocEZXo1ex8G81dwSM1fwqWpWkeS3v86pgKt",
+                    "xpub69H7F5d8KSRgmmdJg2KhpAK8SR3DjMwAdkxj3ZuxV27CprR9LgpeyGmXUbC6wb7ERfvrnKZjXoUmmDznezpbZb7ap6r1D3tgFxHmwMkQTPH",
+                ),
+                (
+                    "2147483647'",
+                    "xprv9wSp6B7kry3Vj9m1zSnLvN3xH8RdsPP1Mh7fAaR7aRLcQMKTR2vidYEeEg2mUCTAwCd6vnxVrcjfy2kRgVsFawNzmjuHc2YmYRmagcEPdU9",
+                    "xpub6ASAVgeehLbnwdqV6UKMHVzgqAG8Gr6riv3Fxxpj8ksbH9ebxaEyBLZ85ySDhKiLDBrQSARLq1uNRts8RuJiHjaDMBU4Zn9h8LZNnBC5y4a",
+                ),
+                (
+                    "1",
+                    "xprv9zFnWC6h2cLgpmSA46vutJzBcfJ8yaJGg8cX1e5StJh45BBciYTRXSd25UEPVuesF9yog62tGAQtHjXajPPdbRCHuWS6T8XA2ECKADdw4Ef",
+                    "xpub6DF8uhdarytz3FWdA8TvFSvvAh8dP3283MY7p2V4SeE2wyWmG5mg5EwVvmdMVCQcoNJxGoWaU9DCWh89LojfZ537wTfunKau47EL2dhHKon",
+                ),
+                (
+                    "2147483646'",
+                    "xprvA1RpRA33e1JQ7ifknakTFpgNXPmW2YvmhqLQYMmrj4xJXXWYpDPS3xz7iAxn8L39njGVyuoseXzU6rcxFLJ8HFsTjSyQbLYnMpCqE2VbFWc",
+                    "xpub6ERApfZwUNrhLCkDtcHTcxd75RbzS1ed54G1LkBUHQVHQKqhMkhgbmJbZRkrgZw4koxb5JaHWkY4ALHY2grBGRjaDMzQLcgJvLJuZZvRcEL",
+                ),
+                (
+                    "2",
+                    "xprvA2nrNbFZABcdryreWet9Ea4LvTJcGsqrMzxHx98MMrotbir7yrKCEXw7nadnHM8Dq38EGfSh6dqA9QWTyefMLEcBYJUuekgW4BYPJcr9E7j",
+                    "xpub6FnCn6nSzZAw5Tw7cgR9bi15UV96gLZhjDstkXXxvCLsUXBGXPdSnLFbdpq8p9HmGsApME5hQTZ3emM2rnY5agb9rXpVGyy3bdW6EEgAtqt",
+                ),
+            ],
+        );
+    }
+
+    // Retention of leading zeros.
+    #[test]
+    fn test_vector_3() {
+        test_chain(
+            "4b381541583be4423346c643850da4b320e46a87ae3d2a4e6da11eba819cd4acba45d239319ac14f863b8d5ab5a0d0c64d2e8a1e7d1457df2e5a3c51c73235be",
+            &[
+                (
+                    "m",
+                    "xprv9s21ZrQH143K25QhxbucbDDuQ4naNntJRi4KUfWT7xo4EKsHt2QJDu7KXp1A3u7Bi1j8ph3EGsZ9Xvz9dGuVrtHHs7pXeTzjuxBrCmmhgC6",
+                    "xpub661MyMwAqRbcEZVB4dScxMAdx6d4nFc9nvyvH3v4gJL378CSRZiYmhRoP7mBy6gSPSCYk6SzXPTf3ND1cZAceL7SfJ1Z3GC8vBgp2epUt13",
+                ),
+                (
+                    "0'",
+                    "xprv9uPDJpEQgRQfDcW7BkF7eTya6RPxXeJCqCJGHuCJ4GiRVLzkTXBAJMu2qaMWPrS7AANYqdq6vcBcBUdJCVVFceUvJFjaPdGZ2y9WACViL4L",
+                    "xpub68NZiKmJWnxxS6aaHmn81bvJeTESw724CRDs6HbuccFQN9Ku14VQrADWgqbhhTHBaohPX4CjNLf9fq9MYo6oDaPPLPxSb7gwQN3ih19Zm4Y",
+                ),
+            ],
+        );
+    }
+
+    // Retention of leading zeros in hardened derivation.
+    #[test]
+    fn test_vector_4() {
+        test_chain(
+            "3ddd5602285899a946114506157c7997e5444528f3003f6134712147db19b678",
+            &[
+                (
+                    "m",
+                    "xprv9s21ZrQH143K48vGoLGRPxgo2JNkJ3J3fqkirQC2zVdk5Dgd5w14S7fRDyHH4dWNHUgkvsvNDCkvAwcSHNAQwhwgNMgZhLtQC63zxwhQmRv",
+                    "xpub661MyMwAqRbcGczjuMoRm6dXaLDEhW1u34gKenbeYqAix21mdUKJyuyu5F1rzYGVxyL6tmgBUAEPrEz92mBXjByMRiJdba9wpnN37RLLAXa",
+                ),
+                (
+                    "0'",
+                    "xprv9vB7xEWwNp9kh1wQRfCCQMnZUEG21LpbR9NPCNN1dwhiZkjjeGRnaALmPXCX7SgjFTiCTT6bXes17boXtjq3xLpcDjzEuGLQBM5ohqkao9G",
+                    "xpub69AUMk3qDBi3uW1sXgjCmVjJ2G6WQoYSnNHyzkmdCHEhSZ4tBok37xfFEqHd2AddP56Tqp4o56AePAgCjYdvpW2PU2jbUPFKsav5ut6Ch1m",
+                ),
+                (
+                    "1'",
+                    "xprv9xJocDuwtYCMNAo3Zw76WENQeAS6WGXQ55RCy7tDJ8oALr4FWkuVoHJeHVAcAqiZLE7Je3vZJHxspZdFHfnBEjHqU5hG1Jaj32dVoS6XLT1",
+                    "xpub6BJA1jSqiukeaesWfxe6sNK9CCGaujFFSJLomWHprUL9DePQ4JDkM5d88n49sMGJxrhpjazuXYWdMf17C9T5XnxkopaeS7jGk1GyyVziaMt",
+                ),
+            ],
+        );
+    }
+
+    // Invalid extended keys.
+    #[test]
+    fn test_vector_5() {
+        let pubkeys = [
+            // pubkey version / prvkey mismatch
+            (
+                "xpub661MyMwAqRbcEYS8w7XLSVeEsBXy79zSzH1J8vCdxAZningWLdN3zgtU6LBpB85b3D2yc8sfvZU521AAwdZafEz7mnzBBsz4wKY5fTtTQBm",
+                Error::InvalidPublicKey,
+            ),
+            // invalid pubkey prefix 04
+            (
+                "xpub661MyMwAqRbcEYS8w7XLSVeEsBXy79zSzH1J8vCdxAZningWLdN3zgtU6Txnt3siSujt9RCVYsx4qHZGc62TG4McvMGcAUjeuwZdduYEvFn",
+                Error::InvalidPublicKey,
+            ),
+            // invalid pubkey prefix 01
+            (
+                "xpub661MyMwAqRbcEYS8w7XLSVeEsBXy79zSzH1J8vCdxAZningWLdN3zgtU6N8ZMMXctdiCjxTNq964yKkwrkBJJwpzZS4HS2fxvyYUA4q2Xe4",
+                Error::InvalidPublicKey,
+            ),
+            // zero depth with non-zero parent fingerprint
+            (
+                "xpub661no6RGEX3uJkY4bNnPcw4URcQTrSibUZ4NqJEw5eBkv7ovTwgiT91XX27VbEXGENhYRCf7hyEbWrR3FewATdCEebj6znwMfQkhRYHRLpJ",
+                Error::InvalidMasterKey,
+            ),
+            // zero depth with non-zero index
+            (
+                "xpub661MyMwAuDcm6CRQ5N4qiHKrJ39Xe1R1NyfouMKTTWcguwVcfrZJaNvhpebzGerh7gucBvzEQWRugZDuDXjNDRmXzSZe4c7mnTK97pTvGS8",
+                Error::InvalidMasterKey,
+            ),
+            // unknown extended key version
+            (
+                "DMwo58pR1QLEFihHiXPVykYB6fJmsTeHvyTp7hRThAtCX8CvYzgPcn8XnmdfHGMQzT7ayAmfo4z3gY5KfbrZWZ6St24UVf2Qgo6oujFktLHdHY4",
+                Error::UnknownVersion,
+            ),
+            // invalid pubkey 020000000000000000000000000000000000000000000000000000000000000007
+            (
+                "xpub661MyMwAqRbcEYS8w7XLSVeEsBXy79zSzH1J8vCdxAZningWLdN3zgtU6Q5JXayek4PRsn35jii4veMimro1xefsM58PgBMrvdYre8QyULY",
+                Error::InvalidPublicKey,
+            ),
+        ];
+        for (xpub, err) in pubkeys.iter() {
+            assert_eq!(ExtendedPubKey::from_str(xpub), Err(*err), "{}", xpub);
+        }
+
+        let privkeys = [
+            // prvkey version / pubkey mismatch
+            (
+                "xprv9s21ZrQH143K24Mfq5zL5MhWK9hUhhGbd45hLXo2Pq2oqzMMo63oStZzFGTQQD3dC4H2D5GBj7vWvSQaaBv5cxi9gafk7NF3pnBju6dwKvH",
+                Error::InvalidPrivateKey,
+            ),
+            // invalid prvkey prefix 04
+            (
+                "xprv9s21ZrQH143K24Mfq5zL5MhWK9hUhhGbd45hLXo2Pq2oqzMMo63oStZzFGpWnsj83BHtEy5Zt8CcDr1UiRXuWCmTQLxEK9vbz5gPstX92JQ",
+                Error::InvalidPrivateKey,
+            ),
+            // invalid prvkey prefix 01
+            (
+                "xprv9s21ZrQH143K24Mfq5zL5MhWK9hUhhGbd45hLXo2Pq2oqzMMo63oStZzFAzHGBP2UuGCqWLTAPLcMtD9y5gkZ6Eq3Rjuahrv17fEQ3Qen6J",
+                Error::InvalidPrivateKey,
+            ),
+            // zero depth with non-zero parent fingerprint
+            (
+                "xprv9s2SPatNQ9Vc6GTbVMFPFo7jsaZySyzk7L8n2uqKXJen3KUmvQNTuLh3fhZMBoG3G4ZW1N2kZuHEPY53qmbZzCHshoQnNf4GvELZfqTUrcv",
+                Error::InvalidMasterKey,
+            ),
+            // zero depth with non-zero index
+            (
+                "xprv9s21ZrQH4r4TsiLvyLXqM9P7k1K3EYhA1kkD6xuquB5i39AU8KF42acDyL3qsDbU9NmZn6MsGSUYZEsuoePmjzsB3eFKSUEh3Gu1N3cqVUN",
+                Error::InvalidMasterKey,
+            ),
+            // unknown extended key version
+            (
+                "DMwo58pR1QLEFihHiXPVykYB6fJmsTeHvyTp7hRThAtCX8CvYzgPcn8XnmdfHPmHJiEDXkTiJTVV9rHEBUem2mwVbbNfvT2MTcAqj3nesx8uBf9",
+                Error::UnknownVersion,
+            ),
+            // private key 0 not in 1..n-1
+            (
+                "xprv9s21ZrQH143K24Mfq5zL5MhWK9hUhhGbd45hLXo2Pq2oqzMMo63oStZzF93Y5wvzdUayhgkkFoicQZcP3y52uPPxFnfoLZB21Teqt1VvEHx",
+                Error::InvalidPrivateKey,
+            ),
+            // private key n not in 1..n-1
+            (
+                "xprv9s21ZrQH143K24Mfq5zL5MhWK9hUhhGbd45hLXo2Pq2oqzMMo63oStZzFAzHGBP2UuGCqWLTAPLcMtD9y5gkZ6Eq3Rjuahrv17fENZ3QzxW",
+                Error::InvalidPrivateKey,
+            ),
+            // invalid checksum
+            (
+                "xprv9s21ZrQH143K3QTDL4LXw2F7HEK3wJUD2nW2nRk4stbPy6cq3jPPqjiChkVvvNKmPGJxWUtg6LnF5kejMRNNU3TGtRBeJgk33yuGBxrMPHL",
+                Error::Base58(base58::Error::BadChecksum),
+            ),
+        ];
+        for (xprv, err) in privkeys.iter() {
+            assert_eq!(ExtendedPrivKey::from_str(xprv).err(), Some(*err), "{}", xprv);
+        }
+    }
+
+    #[test]
+    fn test_derivation_path() {
+        let path = DerivationPath::from_str("m/84'/0'/0h/0/5").unwrap();
+        assert_eq!(
+            path.as_ref(),
+            &[
+                ChildNumber::Hardened(84),
+                ChildNumber::Hardened(0),
+                ChildNumber::Hardened(0),
+                ChildNumber::Normal(0),
+                ChildNumber::Normal(5)
+            ]
+        );
+        assert_eq!(path.to_string(), "m/84'/0'/0'/0/5");
+        assert_eq!(DerivationPath::from_str("m").unwrap(), DerivationPath::master());
+        assert_eq!(u32::from(ChildNumber::from_str("2147483647H").unwrap()), u32::MAX);
+        for invalid in &["", "84'/0'", "m/", "m/0/", "m//1", "m/a", "m/-1", "m/1''", "m/2147483648", "m/2147483648'"] {
+            assert!(DerivationPath::from_str(invalid).is_err(), "{}", invalid);
+        }
+
+        let seed: Vec<u8> = "000102030405060708090a0b0c0d0e0f".from_hex().unwrap();
+        let master = ExtendedPrivKey::new_master(Network::Mainnet, &seed).unwrap();
+        let key = master.derive_priv(&"m/0'/1/2'".parse().unwrap()).unwrap();
+        assert_eq!(
+            key.to_string(),
+            "xprv9z4pot5VBttmtdRTWfWQmoH1taj2axGVzFqSb8C9xaxKymcFzXBDptWmT7FwuEzG3ryjH4ktypQSAewRiNMjANTtpgP4mLTj34bhnZX7UiM"
+        );
+        let xpub = key.to_extended_pubkey().derive_pub(&"m/2/1000000000".parse().unwrap()).unwrap();
+        assert_eq!(
+            xpub.to_string(),
+            "xpub6H1LXWLaKsWFhvm6RVpEL9P4KfRZSW7abD2ttkWP3SSQvnyA8FSVqNTEcYFgJS2UaFcxupHiYkro49S8yGasTvXEYBVPamhGW6cFJodrTHy"
+        );
+        assert_eq!(master.to_extended_pubkey().derive_pub(&"m/0'".parse().unwrap()), Err(Error::CannotDeriveHardened));
+
+        let testnet = ExtendedPrivKey::new_master(Network::Testnet, &seed).unwrap();
+        assert!(testnet.to_string().starts_with("tprv"));
+        assert!(testnet.to_extended_pubkey().to_string().starts_with("tpub"));
+        assert_eq!(ExtendedPubKey::from_str(&testnet.to_extended_pubkey().to_string()).unwrap().network, Network::Testnet);
+        assert_eq!(ExtendedPrivKey::new_master(Network::Mainnet, &seed[..15]).err(), Some(Error::InvalidSeedLength));
+    }
+}
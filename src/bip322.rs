@@ -0,0 +1,547 @@
+//! [BIP322](https://github.com/bitcoin/bips/blob/master/bip-0322.mediawiki) generic signed messages.
+//!
+//! The message is committed to in a virtual `to_spend` transaction paying to the address,
+//! and the signature is the witness of a virtual `to_sign` transaction spending it.
+//! A "simple" signature is just the witness stack, a "full" one is the whole `to_sign` transaction.
+//! Only P2WPKH and P2TR key path addresses are supported, and full signatures can't have proof of funds inputs.
+
+use crate::address::{self, Address, Payload};
+use crate::base64;
+use crate::hash::{sha256d, HashTrait, TaggedHash};
+use crate::message::compact_size;
+use crate::network::Network;
+use crate::secp256k1::{PrivateKey, PublicKey, SchnorrSignature, Signature};
+use std::fmt;
+
+const MESSAGE_TAG: &[u8] = b"BIP0322-signed-message";
+const SIGHASH_DEFAULT: u8 = 0x00;
+const SIGHASH_ALL: u8 = 0x01;
+const OP_RETURN: u8 = 0x6A;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Error {
+    InvalidBase64,
+    /// The signature isn't a valid witness stack or transaction.
+    InvalidEncoding,
+    Address(address::Error),
+    /// Only P2WPKH and P2TR addresses are supported.
+    UnsupportedAddress,
+    /// The private key doesn't control the address.
+    WrongKey,
+    /// Only `SIGHASH_ALL` and Taproot's `SIGHASH_DEFAULT` are supported.
+    UnsupportedSighashType(u8),
+    /// The BIP340 nonce derived for the signature is zero.
+    ZeroNonce,
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Error::InvalidBase64 => write!(f, "Invalid Base64 signature"),
+            Error::InvalidEncoding => write!(f, "Invalid witness or transaction encoding"),
+            Error::Address(e) => write!(f, "{}", e),
+            Error::UnsupportedAddress => write!(f, "Unsupported address type"),
+            Error::WrongKey => write!(f, "The private key doesn't match the address"),
+            Error::UnsupportedSighashType(sighash_type) => write!(f, "Unsupported sighash type {}", sighash_type),
+            Error::ZeroNonce => write!(f, "The signature nonce is zero"),
+        }
+    }
+}
+
+impl From<address::Error> for Error {
+    fn from(e: address::Error) -> Self {
+        Error::Address(e)
+    }
+}
+
+/// A minimal Bitcoin transaction, just enough for the virtual transactions.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Transaction {
+    pub version: i32,
+    pub inputs: Vec<TxIn>,
+    pub outputs: Vec<TxOut>,
+    pub lock_time: u32,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TxIn {
+    /// In internal byte order, the reverse of how txids are usually displayed.
+    pub prev_txid: [u8; 32],
+    pub prev_vout: u32,
+    pub script_sig: Vec<u8>,
+    pub sequence: u32,
+    pub witness: Vec<Vec<u8>>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TxOut {
+    pub value: u64,
+    pub script_pubkey: Vec<u8>,
+}
+
+impl Transaction {
+    /// The network serialization, with the BIP144 witness if any input has one.
+    pub fn serialize(&self) -> Vec<u8> {
+        self.serialize_with(self.inputs.iter().any(|input| !input.witness.is_empty()))
+    }
+
+    /// `SHA256d` of the serialization without witnesses, in internal byte order.
+    pub fn txid(&self) -> [u8; 32] {
+        sha256d(&self.serialize_with(false))
+    }
+
+    pub fn parse(mut data: &[u8]) -> Result<Transaction, Error> {
+        let data = &mut data;
+        let version = i32::from_le_bytes(read_array(data)?);
+        let is_segwit = data.starts_with(&[0x00, 0x01]);
+        if is_segwit {
+            *data = &data[2..];
+        }
+        let mut inputs = Vec::new();
+        for _ in 0..read_compact_size(data)? {
+            let prev_txid = read_array(data)?;
+            let prev_vout = u32::from_le_bytes(read_array(data)?);
+            let script_sig = read_var_bytes(data)?.to_vec();
+            let sequence = u32::from_le_bytes(read_array(data)?);
+            inputs.push(TxIn { prev_txid, prev_vout, script_sig, sequence, witness: Vec::new() });
+        }
+        let mut outputs = Vec::new();
+        for _ in 0..read_compact_size(data)? {
+            let value = u64::from_le_bytes(read_array(data)?);
+            outputs.push(TxOut { value, script_pubkey: read_var_bytes(data)?.to_vec() });
+        }
+        if is_segwit {
+            for input in &mut inputs {
+                input.witness = parse_witness(data)?;
+            }
+        }
+        let lock_time = u32::from_le_bytes(read_array(data)?);
+        if !data.is_empty() {
+            return Err(Error::InvalidEncoding);
+        }
+        Ok(Transaction { version, inputs, outputs, lock_time })
+    }
+
+    fn serialize_with(&self, witness: bool) -> Vec<u8> {
+        let mut res = self.version.to_le_bytes().to_vec();
+        if witness {
+            res.extend_from_slice(&[0x00, 0x01]);
+        }
+        res.extend(compact_size(self.inputs.len() as u64));
+        for input in &self.inputs {
+            res.extend(input.serialize_outpoint());
+            res.extend(var_bytes(&input.script_sig));
+            res.extend_from_slice(&input.sequence.to_le_bytes());
+        }
+        res.extend(compact_size(self.outputs.len() as u64));
+        for output in &self.outputs {
+            res.extend(output.serialize());
+        }
+        if witness {
+            for input in &self.inputs {
+                res.extend(serialize_witness(&input.witness));
+            }
+        }
+        res.extend_from_slice(&self.lock_time.to_le_bytes());
+        res
+    }
+
+    // BIP143, the spent output is `script_code` with a zero value.
+    fn segwit_v0_sighash(&self, index: usize, script_code: &[u8]) -> [u8; 32] {
+        let prevouts: Vec<u8> = self.inputs.iter().flat_map(TxIn::serialize_outpoint).collect();
+        let sequences: Vec<u8> = self.inputs.iter().flat_map(|input| input.sequence.to_le_bytes()).collect();
+        let outputs: Vec<u8> = self.outputs.iter().flat_map(TxOut::serialize).collect();
+
+        let mut preimage = self.version.to_le_bytes().to_vec();
+        preimage.extend_from_slice(&sha256d(&prevouts));
+        preimage.extend_from_slice(&sha256d(&sequences));
+        preimage.extend(self.inputs[index].serialize_outpoint());
+        preimage.extend(var_bytes(script_code));
+        preimage.extend_from_slice(&0u64.to_le_bytes());
+        preimage.extend_from_slice(&self.inputs[index].sequence.to_le_bytes());
+        preimage.extend_from_slice(&sha256d(&outputs));
+        preimage.extend_from_slice(&self.lock_time.to_le_bytes());
+        preimage.extend_from_slice(&u32::from(SIGHASH_ALL).to_le_bytes());
+        sha256d(&preimage)
+    }
+
+    // BIP341 key path spend, every input spends a zero value `spent_script`.
+    fn taproot_sighash(&self, index: usize, spent_script: &[u8], sighash_type: u8) -> [u8; 32] {
+        let prevouts: Vec<u8> = self.inputs.iter().flat_map(TxIn::serialize_outpoint).collect();
+        let amounts: Vec<u8> = self.inputs.iter().flat_map(|_| 0u64.to_le_bytes()).collect();
+        let scripts: Vec<u8> = self.inputs.iter().flat_map(|_| var_bytes(spent_script)).collect();
+        let sequences: Vec<u8> = self.inputs.iter().flat_map(|input| input.sequence.to_le_bytes()).collect();
+        let outputs: Vec<u8> = self.outputs.iter().flat_map(TxOut::serialize).collect();
+
+        let mut hash = TaggedHash::new(b"TapSighash");
+        hash.input(&[0x00, sighash_type]);
+        hash.input(&self.version.to_le_bytes());
+        hash.input(&self.lock_time.to_le_bytes());
+        for data in [&prevouts, &amounts, &scripts, &sequences, &outputs].iter() {
+            hash.input(&sha256(data));
+        }
+        // No annex and a key path spend.
+        hash.input(&[0x00]);
+        hash.input(&(index as u32).to_le_bytes());
+        hash.finalize()
+    }
+}
+
+impl TxIn {
+    fn serialize_outpoint(&self) -> Vec<u8> {
+        [&self.prev_txid[..], &self.prev_vout.to_le_bytes()].concat()
+    }
+}
+
+impl TxOut {
+    fn serialize(&self) -> Vec<u8> {
+        [&self.value.to_le_bytes()[..], &var_bytes(&self.script_pubkey)].concat()
+    }
+}
+
+/// `TaggedHash("BIP0322-signed-message", msg)`
+pub fn message_hash(msg: &str) -> [u8; 32] {
+    let mut hash = TaggedHash::new(MESSAGE_TAG);
+    hash.input(msg.as_bytes());
+    hash.finalize()
+}
+
+/// The virtual transaction committing to the message, with a single zero value output to `address`.
+pub fn to_spend(address: &Address, msg: &str) -> Transaction {
+    let mut script_sig = vec![0x00, 0x20];
+    script_sig.extend_from_slice(&message_hash(msg));
+    Transaction {
+        version: 0,
+        inputs: vec![TxIn { prev_txid: [0u8; 32], prev_vout: 0xFFFF_FFFF, script_sig, sequence: 0, witness: Vec::new() }],
+        outputs: vec![TxOut { value: 0, script_pubkey: address.script_pubkey() }],
+        lock_time: 0,
+    }
+}
+
+/// The virtual transaction spending [`to_spend`] into an `OP_RETURN`, whose input witness is the signature.
+pub fn to_sign(to_spend: &Transaction, witness: Vec<Vec<u8>>) -> Transaction {
+    Transaction {
+        version: 0,
+        inputs: vec![TxIn { prev_txid: to_spend.txid(), prev_vout: 0, script_sig: Vec::new(), sequence: 0, witness }],
+        outputs: vec![TxOut { value: 0, script_pubkey: vec![OP_RETURN] }],
+        lock_time: 0,
+    }
+}
+
+/// Signs `msg` for `address`, returning the Base64 of the witness stack.
+pub fn sign_simple(key: &PrivateKey, address: &Address, msg: &str) -> Result<String, Error> {
+    let to_sign = sign_to_sign(key, address, msg)?;
+    Ok(base64::encode(&serialize_witness(&to_sign.inputs[0].witness)))
+}
+
+/// Signs `msg` for `address`, returning the Base64 of the whole `to_sign` transaction.
+pub fn sign_full(key: &PrivateKey, address: &Address, msg: &str) -> Result<String, Error> {
+    Ok(base64::encode(&sign_to_sign(key, address, msg)?.serialize()))
+}
+
+/// Verifies a simple signature, which is the witness of the default `to_sign` transaction.
+pub fn verify_simple(address: &str, msg: &str, sig: &str) -> Result<bool, Error> {
+    let address: Address = address.parse()?;
+    let witness = parse_witness(&mut &decode_base64(sig)?[..])?;
+    let to_spend = to_spend(&address, msg);
+    let to_sign = to_sign(&to_spend, witness);
+    verify_to_sign(&address, &to_spend, &to_sign)
+}
+
+/// Verifies a full signature, the `to_sign` transaction can have any version, lock time and sequence.
+pub fn verify_full(address: &str, msg: &str, sig: &str) -> Result<bool, Error> {
+    let address: Address = address.parse()?;
+    let to_sign = Transaction::parse(&decode_base64(sig)?)?;
+    let to_spend = to_spend(&address, msg);
+    verify_to_sign(&address, &to_spend, &to_sign)
+}
+
+fn sign_to_sign(key: &PrivateKey, address: &Address, msg: &str) -> Result<Transaction, Error> {
+    let pubkey = key.generate_pubkey();
+    let to_spend = to_spend(address, msg);
+    let mut to_sign = to_sign(&to_spend, Vec::new());
+    let witness = match &address.payload {
+        Payload::WitnessProgram { version: 0, program } if program.len() == 20 => {
+            if *address != Address::p2wpkh(&pubkey, address.network) {
+                return Err(Error::WrongKey);
+            }
+            let sighash = to_sign.segwit_v0_sighash(0, &p2wpkh_script_code(program));
+            let mut sig = key.sign(&sighash, false).serialize_der();
+            sig.push(SIGHASH_ALL);
+            vec![sig, pubkey.compressed().to_vec()]
+        }
+        Payload::WitnessProgram { version: 1, program } if program.len() == 32 => {
+            if *address != Address::p2tr(&pubkey, None, address.network)? {
+                return Err(Error::WrongKey);
+            }
+            let sighash = to_sign.taproot_sighash(0, &address.script_pubkey(), SIGHASH_DEFAULT);
+            // Zero auxiliary randomness keeps the signatures deterministic, like the rest of the crate.
+            let tweaked = key.taproot_tweaked(None).map_err(|_| address::Error::InvalidTaprootTweak)?;
+            let sig = tweaked.sign_bip340(&sighash, &[0u8; 32]).map_err(|_| Error::ZeroNonce)?;
+            vec![sig.serialize().to_vec()]
+        }
+        _ => return Err(Error::UnsupportedAddress),
+    };
+    to_sign.inputs[0].witness = witness;
+    Ok(to_sign)
+}
+
+fn verify_to_sign(address: &Address, to_spend: &Transaction, to_sign: &Transaction) -> Result<bool, Error> {
+    // The txid commits to both the message and the address.
+    let spends_to_spend = to_sign.inputs.len() == 1
+        && to_sign.inputs[0].prev_txid == to_spend.txid()
+        && to_sign.inputs[0].prev_vout == 0
+        && to_sign.outputs == [TxOut { value: 0, script_pubkey: vec![OP_RETURN] }];
+    if !spends_to_spend {
+        return Ok(false);
+    }
+    let witness = &to_sign.inputs[0].witness;
+
+    match &address.payload {
+        Payload::WitnessProgram { version: 0, program } if program.len() == 20 => {
+            if witness.len() != 2 || witness[1].len() != 33 {
+                return Ok(false);
+            }
+            let pubkey = match PublicKey::from_compressed(&witness[1]) {
+                Ok(pubkey) if pubkey.hash160()[..] == program[..] => pubkey,
+                _ => return Ok(false),
+            };
+            let (sighash_type, sig) = match witness[0].split_last() {
+                Some((sighash_type, der)) => match Signature::parse_der(der) {
+                    Ok(sig) => (*sighash_type, sig),
+                    Err(_) => return Ok(false),
+                },
+                None => return Ok(false),
+            };
+            if sighash_type != SIGHASH_ALL {
+                return Err(Error::UnsupportedSighashType(sighash_type));
+            }
+            let sighash = to_sign.segwit_v0_sighash(0, &p2wpkh_script_code(program));
+            Ok(pubkey.verify(&sighash, sig, false))
+        }
+        Payload::WitnessProgram { version: 1, program } if program.len() == 32 => {
+            let sighash_type = match witness.as_slice() {
+                [sig] if sig.len() == 64 => SIGHASH_DEFAULT,
+                // An explicit `SIGHASH_DEFAULT` byte is invalid, it must be a 64 bytes signature.
+                [sig] if sig.len() == 65 && sig[64] != SIGHASH_DEFAULT => sig[64],
+                _ => return Ok(false),
+            };
+            if sighash_type != SIGHASH_DEFAULT && sighash_type != SIGHASH_ALL {
+                return Err(Error::UnsupportedSighashType(sighash_type));
+            }
+            let pubkey = match PublicKey::from_x_only(program) {
+                Ok(pubkey) => pubkey,
+                Err(_) => return Ok(false),
+            };
+            let sighash = to_sign.taproot_sighash(0, &address.script_pubkey(), sighash_type);
+            Ok(pubkey.verify_bip340(&sighash, SchnorrSignature::parse_slice(&witness[0][..64])))
+        }
+        _ => Err(Error::UnsupportedAddress),
+    }
+}
+
+// `OP_DUP OP_HASH160 <hash> OP_EQUALVERIFY OP_CHECKSIG`, which is the P2PKH script.
+fn p2wpkh_script_code(hash: &[u8]) -> Vec<u8> {
+    let mut hash160 = [0u8; 20];
+    hash160.copy_from_slice(hash);
+    Address { network: Network::Mainnet, payload: Payload::PubkeyHash(hash160) }.script_pubkey()
+}
+
+fn sha256(data: &[u8]) -> [u8; 32] {
+    data.hash_digest()
+}
+
+fn decode_base64(sig: &str) -> Result<Vec<u8>, Error> {
+    base64::decode(sig).map_err(|_| Error::InvalidBase64)
+}
+
+fn var_bytes(data: &[u8]) -> Vec<u8> {
+    [compact_size(data.len() as u64), data.to_vec()].concat()
+}
+
+fn serialize_witness(witness: &[Vec<u8>]) -> Vec<u8> {
+    let mut res = compact_size(witness.len() as u64);
+    witness.iter().for_each(|item| res.extend(var_bytes(item)));
+    res
+}
+
+fn parse_witness(data: &mut &[u8]) -> Result<Vec<Vec<u8>>, Error> {
+    (0..read_compact_size(data)?).map(|_| read_var_bytes(data).map(<[u8]>::to_vec)).collect()
+}
+
+fn read_bytes<'a>(data: &mut &'a [u8], len: usize) -> Result<&'a [u8], Error> {
+    if data.len() < len {
+        return Err(Error::InvalidEncoding);
+    }
+    let (res, rest) = data.split_at(len);
+    *data = rest;
+    Ok(res)
+}
+
+fn read_array<const N: usize>(data: &mut &[u8]) -> Result<[u8; N], Error> {
+    let mut res = [0u8; N];
+    res.copy_from_slice(read_bytes(data, N)?);
+    Ok(res)
+}
+
+fn read_var_bytes<'a>(data: &mut &'a [u8]) -> Result<&'a [u8], Error> {
+    let len = read_compact_size(data)?;
+    // Also stops huge lengths from being truncated on 32 bit targets.
+    if len > data.len() as u64 {
+        return Err(Error::InvalidEncoding);
+    }
+    read_bytes(data, len as usize)
+}
+
+// Only accepts the minimal encoding, so every signature has a single serialization.
+fn read_compact_size(data: &mut &[u8]) -> Result<u64, Error> {
+    let (n, min) = match read_array::<1>(data)?[0] {
+        0xfd => (u64::from(u16::from_le_bytes(read_array(data)?)), 0xfd),
+        0xfe => (u64::from(u32::from_le_bytes(read_array(data)?)), 0x1_0000),
+        0xff => (u64::from_le_bytes(read_array(data)?), 0x1_0000_0000),
+        n => return Ok(u64::from(n)),
+    };
+    if n < min {
+        return Err(Error::InvalidEncoding);
+    }
+    Ok(n)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rustc_hex::ToHex;
+
+    const WIF: &str = "L3VFeEujGtevx9w18HD1fhRbCH67Az2dpCymeRE1SoPK6XQtaN2k";
+    const P2WPKH: &str = "bc1q9vza2e8x573nczrlzms0wvx3gsqjx7vavgkx0l";
+    const P2TR: &str = "bc1ppv609nr0vr25u07u95waq5lucwfm6tde4nydujnu8npg4q75mr5sxq8lt3";
+
+    fn txid_hex(tx: &Transaction) -> String {
+        let mut txid = tx.txid();
+        txid.reverse();
+        txid.to_hex()
+    }
+
+    #[test]
+    fn test_message_hash() {
+        assert_eq!(message_hash("").to_hex::<String>(), "c90c269c4f8fcbe6880f72a721ddfbf1914268a794cbb21cfafee13770ae19f1");
+        assert_eq!(message_hash("Hello World").to_hex::<String>(), "f0eb03b1a75ac6d9847f55c624a99169b5dccba2a31f5b23bea77ba270de0a7a");
+    }
+
+    #[test]
+    fn test_transactions() {
+        let address: Address = P2WPKH.parse().unwrap();
+        let vectors = [
+            (
+                "",
+                "c5680aa69bb8d860bf82d4e9cd3504b55dde018de765a91bb566283c545a99a7",
+                "1e9654e951a5ba44c8604c4de6c67fd78a27e81dcadcfe1edf638ba3aaebaed6",
+            ),
+            (
+                "Hello World",
+                "b79d196740ad5217771c1098fc4a4b51e0535c32236c71f1ea4d61a2d603352b",
+                "88737ae86f2077145f93cc4b153ae9a1cb8d56afa511988c149c5c8c9d93bddf",
+            ),
+        ];
+        for (msg, to_spend_txid, to_sign_txid) in vectors.iter() {
+            let to_spend = to_spend(&address, msg);
+            let to_sign = to_sign(&to_spend, Vec::new());
+            assert_eq!(txid_hex(&to_spend), *to_spend_txid);
+            assert_eq!(txid_hex(&to_sign), *to_sign_txid);
+            assert_eq!(Transaction::parse(&to_spend.serialize()), Ok(to_spend));
+        }
+    }
+
+    #[test]
+    fn test_p2wpkh() {
+        let (key, _, _) = PrivateKey::from_wif(WIF).unwrap();
+        let address = Address::p2wpkh(&key.generate_pubkey(), Network::Mainnet);
+        assert_eq!(address.to_string(), P2WPKH);
+        let vectors = [
+            ("", "AkcwRAIgM2gBAQqvZX15ZiysmKmQpDrG83avLIT492QBzLnQIxYCIBaTpOaD20qRlEylyxFSeEA2ba9YOixpX8z46TSDtS40ASECx/EgAxlkQpQ9hYjgGu6EBCPMVPwVIVJqO4XCsMvViHI="),
+            ("Hello World", "AkcwRAIgZRfIY3p7/DoVTty6YZbWS71bc5Vct9p9Fia83eRmw2QCICK/ENGfwLtptFluMGs2KsqoNSk89pO7F29zJLUx9a/sASECx/EgAxlkQpQ9hYjgGu6EBCPMVPwVIVJqO4XCsMvViHI="),
+        ];
+        // Bitcoin Core grinds the nonce for a low R, so our signatures are different but still valid.
+        for (msg, sig) in vectors.iter() {
+            assert_eq!(verify_simple(P2WPKH, msg, sig), Ok(true));
+            let simple = sign_simple(&key, &address, msg).unwrap();
+            assert_eq!(verify_simple(P2WPKH, msg, &simple), Ok(true));
+        }
+        assert_eq!(verify_simple(P2WPKH, "Hello World", vectors[0].1), Ok(false));
+        assert_eq!(verify_simple(P2WPKH, "", vectors[1].1), Ok(false));
+
+        let full = sign_full(&key, &address, "Hello World").unwrap();
+        assert_eq!(verify_full(P2WPKH, "Hello World", &full), Ok(true));
+        assert_eq!(verify_full(P2WPKH, "Hello World!", &full), Ok(false));
+    }
+
+    #[test]
+    fn test_p2tr() {
+        let (key, _, _) = PrivateKey::from_wif(WIF).unwrap();
+        let address = Address::p2tr(&key.generate_pubkey(), None, Network::Mainnet).unwrap();
+        assert_eq!(address.to_string(), P2TR);
+        // Signed with random auxiliary data, so it can only be verified.
+        let sig = "AUHd69PrJQEv+oKTfZ8l+WROBHuy9HKrbFCJu7U1iK2iiEy1vMU5EfMtjc+VSHM7aU0SDbak5IUZRVno2P5mjSafAQ==";
+        assert_eq!(verify_simple(P2TR, "Hello World", sig), Ok(true));
+        assert_eq!(verify_simple(P2TR, "Hello World!", sig), Ok(false));
+        // The same signature with an explicit `SIGHASH_DEFAULT` byte.
+        let mut witness = parse_witness(&mut &base64::decode(sig).unwrap()[..]).unwrap();
+        witness[0][64] = SIGHASH_DEFAULT;
+        assert_eq!(verify_simple(P2TR, "Hello World", &base64::encode(&serialize_witness(&witness))), Ok(false));
+
+        for msg in ["", "Hello World"].iter() {
+            let simple = sign_simple(&key, &address, msg).unwrap();
+            assert_eq!(verify_simple(P2TR, msg, &simple), Ok(true));
+            let full = sign_full(&key, &address, msg).unwrap();
+            assert_eq!(verify_full(P2TR, msg, &full), Ok(true));
+            assert_eq!(verify_full(P2WPKH, msg, &full), Ok(false));
+        }
+    }
+
+    #[test]
+    fn test_errors() {
+        let (key, _, _) = PrivateKey::from_wif(WIF).unwrap();
+        let other = Address::p2wpkh(&PrivateKey::new(1u8).generate_pubkey(), Network::Mainnet);
+        assert_eq!(sign_simple(&key, &other, ""), Err(Error::WrongKey));
+        let p2pkh = Address::p2pkh(&key.generate_pubkey(), Network::Mainnet);
+        assert_eq!(sign_simple(&key, &p2pkh, ""), Err(Error::UnsupportedAddress));
+        let sig = sign_simple(&key, &Address::p2wpkh(&key.generate_pubkey(), Network::Mainnet), "").unwrap();
+        assert_eq!(verify_simple(&p2pkh.to_string(), "", &sig), Err(Error::UnsupportedAddress));
+
+        assert_eq!(verify_simple(P2WPKH, "", "AkcwRAIg*"), Err(Error::InvalidBase64));
+        assert_eq!(verify_simple(P2WPKH, "", "AkcwRAIg"), Err(Error::InvalidEncoding));
+        assert_eq!(verify_full(P2WPKH, "", &sig), Err(Error::InvalidEncoding));
+        assert!(matches!(verify_simple("bc1q9vza2e8x573nczrlzms0wvx3gsqjx7vavgkx0m", "", &sig), Err(Error::Address(_))));
+
+        // A non minimal CompactSize.
+        assert_eq!(read_compact_size(&mut &[0xfd, 0xfc, 0x00][..]), Err(Error::InvalidEncoding));
+        assert_eq!(read_compact_size(&mut &[0xfd, 0xfd, 0x00][..]), Ok(0xfd));
+        // An ECDSA signature with another sighash type.
+        let mut witness = parse_witness(&mut &base64::decode(&sig).unwrap()[..]).unwrap();
+        *witness[0].last_mut().unwrap() = 0x02;
+        let sig = base64::encode(&serialize_witness(&witness));
+        assert_eq!(verify_simple(P2WPKH, "", &sig), Err(Error::UnsupportedSighashType(0x02)));
+    }
+
+    #[test]
+    fn test_non_strict_der() {
+        let (key, _, _) = PrivateKey::from_wif(WIF).unwrap();
+        let sig = sign_simple(&key, &P2WPKH.parse::<Address>().unwrap(), "").unwrap();
+        let mut witness = parse_witness(&mut &base64::decode(&sig).unwrap()[..]).unwrap();
+        let (sighash_type, der) = witness[0].split_last().map(|(t, der)| (*t, der.to_vec())).unwrap();
+
+        // The valid signature with a zero padded `r`.
+        let mut padded = der;
+        padded[1] += 1;
+        padded[3] += 1;
+        padded.insert(4, 0x00);
+        // A minimally encoded 34 bytes `r`, which used to panic the verifier.
+        let mut long_r = vec![0x30, 0x27, 0x02, 0x22, 0x01];
+        long_r.extend_from_slice(&[0x11; 33]);
+        long_r.extend_from_slice(&[0x02, 0x01, 0x01]);
+        for der in [padded, long_r].iter() {
+            witness[0] = [&der[..], &[sighash_type]].concat();
+            assert_eq!(verify_simple(P2WPKH, "", &base64::encode(&serialize_witness(&witness))), Ok(false));
+        }
+    }
+}
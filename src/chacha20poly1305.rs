@@ -0,0 +1,226 @@
+//! The ChaCha20-Poly1305 AEAD ([RFC 8439](https://www.rfc-editor.org/rfc/rfc8439)) and its XChaCha20 variant with 24 bytes nonces.
+//! Poly1305 is computed over BigInt, this is neither constant time nor fast.
+
+use num_bigint::{BigInt, Sign};
+use num_traits::One;
+
+const CONSTANTS: [u32; 4] = [0x6170_7865, 0x3320_646e, 0x7962_2d32, 0x6b20_6574];
+pub(crate) const TAG_SIZE: usize = 16;
+
+pub(crate) struct ChaCha20Poly1305 {
+    key: [u8; 32],
+}
+
+pub(crate) struct XChaCha20Poly1305 {
+    key: [u8; 32],
+}
+
+impl ChaCha20Poly1305 {
+    pub fn new(key: &[u8; 32]) -> Self {
+        Self { key: *key }
+    }
+
+    /// Returns the ciphertext and the authentication tag.
+    pub fn encrypt(&self, nonce: &[u8; 12], aad: &[u8], plaintext: &[u8]) -> (Vec<u8>, [u8; TAG_SIZE]) {
+        let mut ciphertext = plaintext.to_vec();
+        chacha20_xor(&self.key, nonce, 1, &mut ciphertext);
+        let tag = self.tag(nonce, aad, &ciphertext);
+        (ciphertext, tag)
+    }
+
+    pub fn decrypt(&self, nonce: &[u8; 12], aad: &[u8], ciphertext: &[u8], tag: &[u8; TAG_SIZE]) -> Result<Vec<u8>, &'static str> {
+        let expected = self.tag(nonce, aad, ciphertext);
+        // Compare without short circuiting.
+        if expected.iter().zip(tag.iter()).fold(0, |acc, (a, b)| acc | (a ^ b)) != 0 {
+            return Err("Invalid authentication tag");
+        }
+        let mut plaintext = ciphertext.to_vec();
+        chacha20_xor(&self.key, nonce, 1, &mut plaintext);
+        Ok(plaintext)
+    }
+
+    fn tag(&self, nonce: &[u8; 12], aad: &[u8], ciphertext: &[u8]) -> [u8; TAG_SIZE] {
+        let mut poly_key = [0u8; 32];
+        poly_key.copy_from_slice(&chacha20_block(&self.key, nonce, 0)[..32]);
+        let mut mac_data = Vec::with_capacity(aad.len() + ciphertext.len() + 32);
+        for data in &[aad, ciphertext] {
+            mac_data.extend_from_slice(data);
+            mac_data.resize((mac_data.len() + 15) / 16 * 16, 0);
+        }
+        mac_data.extend_from_slice(&(aad.len() as u64).to_le_bytes());
+        mac_data.extend_from_slice(&(ciphertext.len() as u64).to_le_bytes());
+        poly1305(&poly_key, &mac_data)
+    }
+}
+
+impl XChaCha20Poly1305 {
+    pub fn new(key: &[u8; 32]) -> Self {
+        Self { key: *key }
+    }
+
+    /// Returns the ciphertext and the authentication tag.
+    pub fn encrypt(&self, nonce: &[u8; 24], aad: &[u8], plaintext: &[u8]) -> (Vec<u8>, [u8; TAG_SIZE]) {
+        let (cipher, nonce) = self.derive(nonce);
+        cipher.encrypt(&nonce, aad, plaintext)
+    }
+
+    pub fn decrypt(&self, nonce: &[u8; 24], aad: &[u8], ciphertext: &[u8], tag: &[u8; TAG_SIZE]) -> Result<Vec<u8>, &'static str> {
+        let (cipher, nonce) = self.derive(nonce);
+        cipher.decrypt(&nonce, aad, ciphertext, tag)
+    }
+
+    // The subkey is HChaCha20 of the first 16 bytes of the nonce, the rest is the ChaCha20 nonce.
+    fn derive(&self, nonce: &[u8; 24]) -> (ChaCha20Poly1305, [u8; 12]) {
+        let mut state = init_state(&self.key, &nonce[..16]);
+        rounds(&mut state);
+        let mut subkey = [0u8; 32];
+        for (i, word) in state[..4].iter().chain(state[12..].iter()).enumerate() {
+            subkey[i * 4..i * 4 + 4].copy_from_slice(&word.to_le_bytes());
+        }
+        let mut chacha_nonce = [0u8; 12];
+        chacha_nonce[4..].copy_from_slice(&nonce[16..]);
+        (ChaCha20Poly1305::new(&subkey), chacha_nonce)
+    }
+}
+
+// `input` is the counter and nonce (16 bytes).
+fn init_state(key: &[u8; 32], input: &[u8]) -> [u32; 16] {
+    let mut state = [0u32; 16];
+    state[..4].copy_from_slice(&CONSTANTS);
+    for (word, bytes) in state[4..].iter_mut().zip(key.chunks(4).chain(input.chunks(4))) {
+        *word = u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]);
+    }
+    state
+}
+
+fn quarter_round(state: &mut [u32; 16], a: usize, b: usize, c: usize, d: usize) {
+    state[a] = state[a].wrapping_add(state[b]);
+    state[d] = (state[d] ^ state[a]).rotate_left(16);
+    state[c] = state[c].wrapping_add(state[d]);
+    state[b] = (state[b] ^ state[c]).rotate_left(12);
+    state[a] = state[a].wrapping_add(state[b]);
+    state[d] = (state[d] ^ state[a]).rotate_left(8);
+    state[c] = state[c].wrapping_add(state[d]);
+    state[b] = (state[b] ^ state[c]).rotate_left(7);
+}
+
+fn rounds(state: &mut [u32; 16]) {
+    for _ in 0..10 {
+        quarter_round(state, 0, 4, 8, 12);
+        quarter_round(state, 1, 5, 9, 13);
+        quarter_round(state, 2, 6, 10, 14);
+        quarter_round(state, 3, 7, 11, 15);
+        quarter_round(state, 0, 5, 10, 15);
+        quarter_round(state, 1, 6, 11, 12);
+        quarter_round(state, 2, 7, 8, 13);
+        quarter_round(state, 3, 4, 9, 14);
+    }
+}
+
+fn chacha20_block(key: &[u8; 32], nonce: &[u8; 12], counter: u32) -> [u8; 64] {
+    let mut input = [0u8; 16];
+    input[..4].copy_from_slice(&counter.to_le_bytes());
+    input[4..].copy_from_slice(nonce);
+    let initial = init_state(key, &input);
+    let mut state = initial;
+    rounds(&mut state);
+    let mut res = [0u8; 64];
+    for (i, (word, init)) in state.iter().zip(initial.iter()).enumerate() {
+        res[i * 4..i * 4 + 4].copy_from_slice(&word.wrapping_add(*init).to_le_bytes());
+    }
+    res
+}
+
+fn chacha20_xor(key: &[u8; 32], nonce: &[u8; 12], counter: u32, data: &mut [u8]) {
+    for (i, chunk) in data.chunks_mut(64).enumerate() {
+        let keystream = chacha20_block(key, nonce, counter + i as u32);
+        chunk.iter_mut().zip(keystream.iter()).for_each(|(b, k)| *b ^= k);
+    }
+}
+
+fn poly1305(key: &[u8; 32], msg: &[u8]) -> [u8; TAG_SIZE] {
+    let p: BigInt = (BigInt::one() << 130) - 5;
+    let mut r = key[..16].to_vec();
+    for i in &[3, 7, 11, 15] {
+        r[*i] &= 0x0f;
+    }
+    for i in &[4, 8, 12] {
+        r[*i] &= 0xfc;
+    }
+    let r = BigInt::from_bytes_le(Sign::Plus, &r);
+    let s = BigInt::from_bytes_le(Sign::Plus, &key[16..]);
+
+    let mut acc = BigInt::from(0);
+    for chunk in msg.chunks(16) {
+        let mut block = chunk.to_vec();
+        block.push(1);
+        acc = ((acc + BigInt::from_bytes_le(Sign::Plus, &block)) * &r) % &p;
+    }
+    let (_, bytes) = (acc + s).to_bytes_le();
+    let mut tag = [0u8; TAG_SIZE];
+    let len = bytes.len().min(TAG_SIZE);
+    tag[..len].copy_from_slice(&bytes[..len]);
+    tag
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rustc_hex::{FromHex, ToHex};
+
+    const KEY: &str = "808182838485868788898a8b8c8d8e8f909192939495969798999a9b9c9d9e9f";
+    const AAD: &str = "50515253c0c1c2c3c4c5c6c7";
+    const PLAINTEXT: &[u8] =
+        b"Ladies and Gentlemen of the class of '99: If I could offer you only one tip for the future, sunscreen would be it.";
+
+    fn key() -> [u8; 32] {
+        let mut key = [0u8; 32];
+        key.copy_from_slice(&KEY.from_hex::<Vec<u8>>().unwrap());
+        key
+    }
+
+    // RFC 8439 section 2.5.2
+    #[test]
+    fn test_poly1305() {
+        let mut key = [0u8; 32];
+        key.copy_from_slice(&"85d6be7857556d337f4452fe42d506a80103808afb0db2fd4abff6af4149f51b".from_hex::<Vec<u8>>().unwrap());
+        let tag = poly1305(&key, b"Cryptographic Forum Research Group");
+        assert_eq!(tag.to_hex::<String>(), "a8061dc1305136c6c22b8baf0c0127a9");
+    }
+
+    // RFC 8439 section 2.8.2
+    #[test]
+    fn test_chacha20poly1305() {
+        let cipher = ChaCha20Poly1305::new(&key());
+        let nonce = [0x07, 0x00, 0x00, 0x00, 0x40, 0x41, 0x42, 0x43, 0x44, 0x45, 0x46, 0x47];
+        let aad = AAD.from_hex::<Vec<u8>>().unwrap();
+        let (ciphertext, tag) = cipher.encrypt(&nonce, &aad, PLAINTEXT);
+        assert_eq!(
+            ciphertext.to_hex::<String>(),
+            "d31a8d34648e60db7b86afbc53ef7ec2a4aded51296e08fea9e2b5a736ee62d63dbea45e8ca9671282fafb69da92728b1a71de0a9e060b2905d6a5b67ecd3b3692ddbd7f2d778b8c9803aee328091b58fab324e4fad675945585808b4831d7bc3ff4def08e4b7a9de576d26586cec64b6116"
+        );
+        assert_eq!(tag.to_hex::<String>(), "1ae10b594f09e26a7e902ecbd0600691");
+        assert_eq!(cipher.decrypt(&nonce, &aad, &ciphertext, &tag).unwrap(), PLAINTEXT);
+        assert!(cipher.decrypt(&nonce, &aad[1..], &ciphertext, &tag).is_err());
+        assert!(cipher.decrypt(&nonce, &aad, &ciphertext[1..], &tag).is_err());
+    }
+
+    // draft-irtf-cfrg-xchacha-03 appendix A.3.1
+    #[test]
+    fn test_xchacha20poly1305() {
+        let cipher = XChaCha20Poly1305::new(&key());
+        let mut nonce = [0u8; 24];
+        nonce.iter_mut().enumerate().for_each(|(i, b)| *b = 0x40 + i as u8);
+        let aad = AAD.from_hex::<Vec<u8>>().unwrap();
+        let (ciphertext, tag) = cipher.encrypt(&nonce, &aad, PLAINTEXT);
+        assert_eq!(
+            ciphertext.to_hex::<String>(),
+            "bd6d179d3e83d43b9576579493c0e939572a1700252bfaccbed2902c21396cbb731c7f1b0b4aa6440bf3a82f4eda7e39ae64c6708c54c216cb96b72e1213b4522f8c9ba40db5d945b11b69b982c1bb9e3f3fac2bc369488f76b2383565d3fff921f9664c97637da9768812f615c68b13b52e"
+        );
+        assert_eq!(tag.to_hex::<String>(), "c0875924c1c7987947deafd8780acf49");
+        assert_eq!(cipher.decrypt(&nonce, &aad, &ciphertext, &tag).unwrap(), PLAINTEXT);
+        let mut bad_tag = tag;
+        bad_tag[0] ^= 1;
+        assert!(cipher.decrypt(&nonce, &aad, &ciphertext, &bad_tag).is_err());
+    }
+}
@@ -0,0 +1,332 @@
+//! Chaum-Pedersen proofs of discrete log equality (DLEQ).
+//!
+//! Proves that `P1 = x*G` and `P2 = x*Y` for the same secret `x`, without revealing it.
+//! The challenge is a "DLEQ" tagged hash and the nonce derivation is the one from secp256k1-zkp,
+//! so proofs are compatible with the ones in its `ecdsa_adaptor` module and the DLC specification.
+//!
+//! That `e || s` form only gives the nonce points through the challenge hash, so it can't be batch verified.
+//! [`BatchableDleqProof`] carries the nonce points instead, and converts to the compact form with the same challenge.
+
+use crate::field::FieldElement;
+use crate::hash::HashDigest;
+use crate::point::Point;
+use crate::secp256k1::{get_context, negate_scalar, scalar_mod_order, PrivateKey, PublicKey};
+
+/// A non interactive DLEQ proof, serialized as `e || s`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DleqProof {
+    e: FieldElement,
+    s: FieldElement,
+}
+
+impl DleqProof {
+    /// Proves that `x*G` and `x*base` share the same discrete log, failing only if the derived nonce is zero.
+    pub fn prove(x: &PrivateKey, base: &PublicKey) -> Result<DleqProof, &'static str> {
+        let x = FieldElement::new(x.scalar.clone(), get_context().order.clone());
+        let p1 = &x.num * get_context().generator();
+        let p2 = &x.num * base.point.clone();
+        Self::prove_raw(&x, &base.point, &p1, &p2)
+    }
+
+    /// Checks that `p1 = x*G` and `p2 = x*base` for some `x`.
+    pub fn verify(&self, p1: &PublicKey, base: &PublicKey, p2: &PublicKey) -> bool {
+        self.verify_raw(&p1.point, &base.point, &p2.point)
+    }
+
+    pub(crate) fn prove_raw(x: &FieldElement, base: &Point, p1: &Point, p2: &Point) -> Result<DleqProof, &'static str> {
+        let (_, _, e, s) = prove_parts(x, base, p1, p2)?;
+        Ok(DleqProof { e, s })
+    }
+
+    #[allow(non_snake_case)]
+    pub(crate) fn verify_raw(&self, p1: &Point, base: &Point, p2: &Point) -> bool {
+        let G = get_context().generator();
+        let neg_e = &get_context().order - self.e.clone();
+        // R1 = s*G - e*P1, R2 = s*Y - e*P2
+        let r1 = (&self.s.num * G) + (&neg_e.num * p1);
+        let r2 = (&self.s.num * base) + (&neg_e.num * p2);
+        challenge(base, &r1, &r2, p1, p2) == self.e
+    }
+
+    pub fn serialize(&self) -> [u8; 64] {
+        let mut res = [0u8; 64];
+        res[..32].copy_from_slice(&self.e.clone().serialize_num());
+        res[32..].copy_from_slice(&self.s.clone().serialize_num());
+        res
+    }
+
+    /// Parses a proof, `e` is reduced modulo the order like in secp256k1-zkp but `s` must be a valid scalar.
+    pub fn parse(ser: &[u8]) -> Result<DleqProof, &'static str> {
+        let order = &get_context().order;
+        if ser.len() != 64 {
+            return Err("A DLEQ proof must be 64 bytes");
+        }
+        let e = scalar_mod_order(&ser[..32]);
+        let s = FieldElement::from_serialize(&ser[32..], order.clone());
+        if &s.num >= order {
+            return Err("s is not a scalar");
+        }
+        Ok(DleqProof { e, s })
+    }
+}
+
+/// A DLEQ proof with the nonce points instead of the challenge, serialized as `R1 || R2 || s` (33 + 33 + 32 bytes).
+#[derive(Debug, Clone, PartialEq)]
+pub struct BatchableDleqProof {
+    r1: Point,
+    r2: Point,
+    s: FieldElement,
+}
+
+impl BatchableDleqProof {
+    /// Proves that `x*G` and `x*base` share the same discrete log, with the same nonce as [`DleqProof::prove`].
+    pub fn prove(x: &PrivateKey, base: &PublicKey) -> Result<BatchableDleqProof, &'static str> {
+        let x = FieldElement::new(x.scalar.clone(), get_context().order.clone());
+        let p1 = &x.num * get_context().generator();
+        let p2 = &x.num * base.point.clone();
+        let (r1, r2, _, s) = prove_parts(&x, &base.point, &p1, &p2)?;
+        Ok(BatchableDleqProof { r1, r2, s })
+    }
+
+    /// Checks that `p1 = x*G` and `p2 = x*base` for some `x`.
+    #[allow(non_snake_case)]
+    pub fn verify(&self, p1: &PublicKey, base: &PublicKey, p2: &PublicKey) -> bool {
+        let G = get_context().generator();
+        let e = challenge(&base.point, &self.r1, &self.r2, &p1.point, &p2.point);
+        // s*G = R1 + e*P1, s*Y = R2 + e*P2
+        &self.s.num * G == self.r1.clone() + (&e.num * &p1.point)
+            && &self.s.num * &base.point == self.r2.clone() + (&e.num * &p2.point)
+    }
+
+    /// The secp256k1-zkp compatible `e || s` form of the proof for this statement.
+    pub fn to_proof(&self, p1: &PublicKey, base: &PublicKey, p2: &PublicKey) -> DleqProof {
+        let e = challenge(&base.point, &self.r1, &self.r2, &p1.point, &p2.point);
+        DleqProof { e, s: self.s.clone() }
+    }
+
+    pub fn serialize(&self) -> [u8; 98] {
+        let mut res = [0u8; 98];
+        res[..33].copy_from_slice(&compressed(&self.r1));
+        res[33..66].copy_from_slice(&compressed(&self.r2));
+        res[66..].copy_from_slice(&self.s.clone().serialize_num());
+        res
+    }
+
+    pub fn parse(ser: &[u8]) -> Result<BatchableDleqProof, &'static str> {
+        let order = &get_context().order;
+        if ser.len() != 98 {
+            return Err("A batchable DLEQ proof must be 98 bytes");
+        }
+        let r1 = PublicKey::from_compressed(&ser[..33])?.point;
+        let r2 = PublicKey::from_compressed(&ser[33..66])?.point;
+        let s = FieldElement::from_serialize(&ser[66..], order.clone());
+        if &s.num >= order {
+            return Err("s is not a scalar");
+        }
+        Ok(BatchableDleqProof { r1, r2, s })
+    }
+}
+
+/// Batch verifies every `(proof, P1, Y, P2)` statement, returning the index of the first invalid one.
+///
+/// All the `s*G = R1 + e*P1` and `s*Y = R2 + e*P2` equations are multiplied by random weights and summed into one check,
+/// the weights are derived from a hash of the whole batch so they can't be chosen by the provers.
+/// If the sum doesn't vanish the proofs are verified one by one to find the invalid one.
+#[allow(non_snake_case)]
+pub fn batch_verify(statements: &[(&BatchableDleqProof, &PublicKey, &PublicKey, &PublicKey)]) -> Result<(), usize> {
+    let order = &get_context().order;
+    let mut seed = HashDigest::tagged(b"DLEQ/batch");
+    for (proof, p1, base, p2) in statements {
+        seed.input(&proof.serialize());
+        for key in &[p1, base, p2] {
+            seed.input(&compressed(&key.point));
+        }
+    }
+    let seed = seed.result();
+    let weight = |i: usize| {
+        let mut hasher = HashDigest::new();
+        hasher.input(&seed);
+        hasher.input(&(i as u64).to_be_bytes());
+        scalar_mod_order(&hasher.result())
+    };
+
+    // sum(a*s)*G + sum(b*s*Y - a*R1 - a*e*P1 - b*R2 - b*e*P2) should be the point at infinity.
+    let mut s_g = FieldElement::new(0u8, order.clone());
+    let mut sum = None;
+    for (i, (proof, p1, base, p2)) in statements.iter().enumerate() {
+        let e = challenge(&base.point, &proof.r1, &proof.r2, &p1.point, &p2.point);
+        let (a, b) = (weight(2 * i), weight(2 * i + 1));
+        s_g = s_g + (a.clone() * &proof.s);
+        let terms = [
+            ((b.clone() * &proof.s), &base.point),
+            (negate_scalar(a.clone()), &proof.r1),
+            (negate_scalar(a * &e), &p1.point),
+            (negate_scalar(b.clone()), &proof.r2),
+            (negate_scalar(b * &e), &p2.point),
+        ];
+        for (scalar, point) in terms.iter() {
+            let term = &scalar.num * *point;
+            sum = Some(match sum {
+                Some(sum) => sum + term,
+                None => term,
+            });
+        }
+    }
+    let G = get_context().generator();
+    let total = match sum {
+        Some(sum) => (&s_g.num * G) + sum,
+        None => return Ok(()),
+    };
+    if total.is_on_infinity() {
+        return Ok(());
+    }
+    match statements.iter().position(|(proof, p1, base, p2)| !proof.verify(p1, base, p2)) {
+        Some(i) => Err(i),
+        // Can only happen if the random weights cancelled out, but the proofs are all valid.
+        None => Ok(()),
+    }
+}
+
+/// Verifies every `(proof, P1, Y, P2)` statement one by one, returning the index of the first invalid one.
+///
+/// `e || s` proofs can't be batched, see [`batch_verify`] for [`BatchableDleqProof`]s.
+pub fn verify_all(statements: &[(&DleqProof, &PublicKey, &PublicKey, &PublicKey)]) -> Result<(), usize> {
+    match statements.iter().position(|(proof, p1, base, p2)| !proof.verify(p1, base, p2)) {
+        Some(i) => Err(i),
+        None => Ok(()),
+    }
+}
+
+// Returns `(R1, R2, e, s)`.
+#[allow(non_snake_case)]
+fn prove_parts(
+    x: &FieldElement,
+    base: &Point,
+    p1: &Point,
+    p2: &Point,
+) -> Result<(Point, Point, FieldElement, FieldElement), &'static str> {
+    let G = get_context().generator();
+    let mut msg = HashDigest::new();
+    msg.input(&compressed(p1));
+    msg.input(&compressed(p2));
+    let k = hardened_nonce(b"DLEQ", &x.clone().serialize_num(), &compressed(base), &msg.result())?;
+
+    let r1 = &k.num * G;
+    let r2 = &k.num * base.clone();
+    let e = challenge(base, &r1, &r2, p1, p2);
+    let s = k + (e.clone() * x);
+    Ok((r1, r2, e, s))
+}
+
+fn challenge(base: &Point, r1: &Point, r2: &Point, p1: &Point, p2: &Point) -> FieldElement {
+    let mut hasher = HashDigest::tagged(b"DLEQ");
+    for point in &[p1, base, p2, r1, r2] {
+        hasher.input(&compressed(point));
+    }
+    scalar_mod_order(&hasher.result())
+}
+
+/// The secp256k1-zkp `nonce_function_ecdsa_adaptor` (a modified BIP340 nonce function) without auxiliary randomness.
+pub(crate) fn hardened_nonce(tag: &[u8], key: &[u8; 32], pk: &[u8; 33], msg: &[u8; 32]) -> Result<FieldElement, &'static str> {
+    let mut hasher = HashDigest::tagged(tag);
+    hasher.input(key);
+    hasher.input(pk);
+    hasher.input(msg);
+    let k = scalar_mod_order(&hasher.result());
+    if k.is_zero() {
+        return Err("The nonce is zero");
+    }
+    Ok(k)
+}
+
+fn compressed(point: &Point) -> [u8; 33] {
+    PublicKey::from(point.clone()).compressed()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // `(proof, P1, Y, P2)` for a few bases and the same secret.
+    fn statements() -> Vec<(BatchableDleqProof, PublicKey, PublicKey, PublicKey)> {
+        let x = PrivateKey::new(0xd1e9_u32);
+        let p1 = x.generate_pubkey();
+        (2u32..5)
+            .map(|i| {
+                let base = PrivateKey::new(i * 0x1000_0001).generate_pubkey();
+                let p2 = PublicKey::from(&x.scalar * base.point.clone());
+                (BatchableDleqProof::prove(&x, &base).unwrap(), p1.clone(), base, p2)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_dleq() {
+        let x = PrivateKey::new(0xd1e9_u32);
+        let p1 = x.generate_pubkey();
+        let mut batch = Vec::new();
+        for (batchable, _, base, p2) in statements() {
+            let proof = DleqProof::prove(&x, &base).unwrap();
+            assert_eq!(batchable.to_proof(&p1, &base, &p2), proof);
+            assert_eq!(DleqProof::parse(&proof.serialize()).unwrap(), proof);
+            assert!(proof.verify(&p1, &base, &p2));
+            assert!(!proof.verify(&p2, &base, &p1));
+            assert!(!proof.verify(&p1, &p1, &p2));
+            batch.push((proof, base, p2));
+        }
+        let mut statements: Vec<_> = batch.iter().map(|(proof, base, p2)| (proof, &p1, base, p2)).collect();
+        assert_eq!(verify_all(&statements), Ok(()));
+
+        // A different secret for the second base.
+        let other = PrivateKey::new(0xd1ea_u32);
+        let fake_p2 = PublicKey::from(&other.scalar * batch[1].1.point.clone());
+        statements[1].3 = &fake_p2;
+        assert_eq!(verify_all(&statements), Err(1));
+    }
+
+    #[test]
+    fn test_tampered_challenge() {
+        let (batchable, p1, base, p2) = statements().remove(0);
+        let mut ser = batchable.to_proof(&p1, &base, &p2).serialize();
+        ser[31] ^= 1;
+        let proof = DleqProof::parse(&ser).unwrap();
+        assert!(!proof.verify(&p1, &base, &p2));
+    }
+
+    #[test]
+    fn test_invalid_s() {
+        let (batchable, p1, base, p2) = statements().remove(0);
+        let order = get_context().serialized_order();
+        let mut ser = batchable.to_proof(&p1, &base, &p2).serialize();
+        ser[32..].copy_from_slice(&order);
+        assert_eq!(DleqProof::parse(&ser), Err("s is not a scalar"));
+
+        let mut ser = batchable.serialize();
+        ser[66..].copy_from_slice(&order);
+        assert_eq!(BatchableDleqProof::parse(&ser), Err("s is not a scalar"));
+        assert!(BatchableDleqProof::parse(&ser[..97]).is_err());
+    }
+
+    #[test]
+    fn test_batch_verify() {
+        let statements = statements();
+        let mut batch: Vec<_> = statements.iter().map(|(proof, p1, base, p2)| (proof, p1, base, p2)).collect();
+        assert_eq!(batch_verify(&batch), Ok(()));
+        assert_eq!(batch_verify(&[]), Ok(()));
+        for (proof, p1, base, p2) in &batch {
+            assert!(proof.verify(p1, base, p2));
+            assert_eq!(&BatchableDleqProof::parse(&proof.serialize()).unwrap(), *proof);
+        }
+
+        // Swapping the nonce points breaks both equations of the last proof.
+        let (proof, p1, base, p2) = &statements[2];
+        let swapped = BatchableDleqProof { r1: proof.r2.clone(), r2: proof.r1.clone(), s: proof.s.clone() };
+        assert!(!swapped.verify(p1, base, p2));
+        batch[2].0 = &swapped;
+        assert_eq!(batch_verify(&batch), Err(2));
+
+        // A proof for another statement.
+        batch[2].0 = &statements[0].0;
+        assert_eq!(batch_verify(&batch), Err(2));
+    }
+}
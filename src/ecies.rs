@@ -0,0 +1,134 @@
+//! ECIES public key encryption, compatible with [eciesjs](https://github.com/ecies/js) and the `ecies` crate
+//! configured with the `xchacha20` symmetric algorithm and uncompressed keys (their defaults otherwise).
+//!
+//! The wire format is `ephemeral_pubkey (65) || nonce (24) || tag (16) || ciphertext`, where:
+//! - `ephemeral_pubkey` is the uncompressed public key of a fresh ephemeral key `r`.
+//! - The symmetric key is HKDF-SHA256 (no salt, no info) of `ephemeral_pubkey || uncompressed(r*P)`.
+//! - The payload is encrypted with XChaCha20-Poly1305 with an empty associated data.
+
+use crate::chacha20poly1305::{XChaCha20Poly1305, TAG_SIZE};
+use crate::hmac_sha2::HmacSha256Drbg;
+use crate::kdf::Hkdf;
+use crate::secp256k1::{get_context, PrivateKey, PublicKey};
+use num_bigint::{BigInt, Sign};
+
+const PUBKEY_SIZE: usize = 65;
+const NONCE_SIZE: usize = 24;
+/// The ciphertext is longer than the plaintext by this many bytes.
+pub const OVERHEAD: usize = PUBKEY_SIZE + NONCE_SIZE + TAG_SIZE;
+
+/// Encrypts `plaintext` to `pubkey`.
+///
+/// `rand` *must* be 32 bytes of fresh randomness, the ephemeral key and the nonce are derived from it.
+pub fn encrypt(pubkey: &PublicKey, plaintext: &[u8], rand: &[u8; 32]) -> Vec<u8> {
+    let mut rng = HmacSha256Drbg::new(rand, Some(&pubkey.clone().compressed()));
+    let ephemeral = loop {
+        let mut scalar = [0u8; 32];
+        rng.generate(&mut scalar);
+        let scalar = BigInt::from_bytes_be(Sign::Plus, &scalar);
+        if scalar != BigInt::from(0) && scalar < get_context().order {
+            break PrivateKey::new(scalar);
+        }
+    };
+    let mut nonce = [0u8; NONCE_SIZE];
+    rng.generate(&mut nonce);
+
+    let ephemeral_pubkey = ephemeral.generate_pubkey().uncompressed();
+    let key = derive_key(&ephemeral_pubkey, &ephemeral, pubkey);
+    let (ciphertext, tag) = XChaCha20Poly1305::new(&key).encrypt(&nonce, &[], plaintext);
+
+    let mut res = Vec::with_capacity(OVERHEAD + ciphertext.len());
+    res.extend_from_slice(&ephemeral_pubkey);
+    res.extend_from_slice(&nonce);
+    res.extend_from_slice(&tag);
+    res.extend_from_slice(&ciphertext);
+    res
+}
+
+/// Decrypts a ciphertext created by [`encrypt`].
+pub fn decrypt(key: &PrivateKey, ciphertext: &[u8]) -> Result<Vec<u8>, &'static str> {
+    if ciphertext.len() < OVERHEAD {
+        return Err("The ciphertext is too short");
+    }
+    let (ephemeral_pubkey, rest) = ciphertext.split_at(PUBKEY_SIZE);
+    let (nonce, rest) = rest.split_at(NONCE_SIZE);
+    let (tag, ciphertext) = rest.split_at(TAG_SIZE);
+    if ephemeral_pubkey[0] != 0x04 {
+        return Err("The ephemeral public key must be uncompressed");
+    }
+    let pubkey = PublicKey::from_slice(ephemeral_pubkey)?;
+
+    let mut nonce_arr = [0u8; NONCE_SIZE];
+    nonce_arr.copy_from_slice(nonce);
+    let mut tag_arr = [0u8; TAG_SIZE];
+    tag_arr.copy_from_slice(tag);
+
+    let sym_key = derive_key(ephemeral_pubkey, key, &pubkey);
+    XChaCha20Poly1305::new(&sym_key).decrypt(&nonce_arr, &[], ciphertext, &tag_arr)
+}
+
+fn derive_key(ephemeral_pubkey: &[u8], key: &PrivateKey, pubkey: &PublicKey) -> [u8; 32] {
+    let shared = key.ecdh_with(pubkey, |x, y| {
+        let mut res = [0u8; PUBKEY_SIZE];
+        res[0] = 0x04;
+        res[1..33].copy_from_slice(x);
+        res[33..].copy_from_slice(y);
+        res
+    });
+    let mut ikm = ephemeral_pubkey.to_vec();
+    ikm.extend_from_slice(&shared);
+    let mut key = [0u8; 32];
+    Hkdf::extract(None, &ikm).expand(&[], &mut key).expect("32 bytes is a valid HKDF output length");
+    key
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rustc_hex::{FromHex, ToHex};
+
+    fn hex(hex: &str) -> Vec<u8> {
+        hex.from_hex().unwrap()
+    }
+
+    // Test vectors from the `ecies` crate.
+    #[test]
+    fn test_hkdf() {
+        let mut key = [0u8; 32];
+        Hkdf::extract(None, b"secret").expand(&[], &mut key).unwrap();
+        assert_eq!(key.to_hex::<String>(), "2f34e5ff91ec85d53ca9b543683174d0cf550b60d5f52b24c97b386cfcf6cbbf");
+
+        let ephemeral = PrivateKey::new(2u8);
+        let pubkey = ephemeral.generate_pubkey().uncompressed();
+        let shared = derive_key(&pubkey, &ephemeral, &PrivateKey::new(3u8).generate_pubkey());
+        assert_eq!(shared.to_hex::<String>(), "6f982d63e8590c9d9b5b4c1959ff80315d772edd8f60287c9361d548d5200f82");
+    }
+
+    #[test]
+    fn test_decrypt_vector() {
+        let key = PrivateKey::from_serialized(&hex("9445d8b9911622546a266b2e663bf2b498073a64279409afb9ef20f8259c651f"));
+        let ciphertext = hex("04eaf35ad4dde0ace3f673fec6be164dc68e11aa9c1988d4c1b91f0ccdef94cf591aae4e9daf5f8a87837136fc70811df852015a8b4e2cb374c27db16933536085f34470ffef72667bbe984c145302fc8d37f66563339c47f41ef871ee0ebda8c1bad133c3b203c769cb694e5adbd6c9f02b2eedd939875a");
+        assert_eq!(decrypt(&key, &ciphertext).unwrap(), "hello world🌍".as_bytes());
+        assert!(decrypt(&PrivateKey::new(2u8), &ciphertext).is_err());
+        assert!(decrypt(&key, &ciphertext[..OVERHEAD - 1]).is_err());
+    }
+
+    #[test]
+    fn test_encrypt_decrypt() {
+        let key = PrivateKey::new(0xec1e5_u32);
+        let pubkey = key.generate_pubkey();
+        for msg in &[&b""[..], b"Liberta!", &[0x42; 200]] {
+            let ciphertext = encrypt(&pubkey, msg, &[7; 32]);
+            assert_eq!(ciphertext.len(), msg.len() + OVERHEAD);
+            assert_eq!(&decrypt(&key, &ciphertext).unwrap()[..], *msg);
+            assert_ne!(ciphertext, encrypt(&pubkey, msg, &[8; 32]));
+
+            let mut tampered = ciphertext.clone();
+            *tampered.last_mut().unwrap() ^= 1;
+            assert!(decrypt(&key, &tampered).is_err());
+            tampered = ciphertext.clone();
+            tampered[64] ^= 1;
+            assert!(decrypt(&key, &tampered).is_err());
+        }
+    }
+}
@@ -0,0 +1,392 @@
+//! ElligatorSwift encoding of public keys and the x-only ECDH of [BIP324](https://github.com/bitcoin/bips/blob/master/bip-0324.mediawiki).
+//!
+//! An [ElligatorSwift](https://eprint.iacr.org/2022/759) encoding is 64 bytes `u || t` which are indistinguishable from random,
+//! and any 64 bytes decode to a valid x coordinate.
+//! The encoding and the randomness derivation follow the `ellswift` module of libsecp256k1 so the outputs are the same.
+//! Nothing here is constant time.
+
+use crate::field::FieldElement;
+use crate::hash::HashDigest;
+use crate::point::Point;
+use crate::secp256k1::{get_context, PrivateKey, PublicKey};
+use num_bigint::BigInt;
+
+// c1 = (sqrt(-3)-1)/2, c2 = (-sqrt(-3)-1)/2, c3 = -c1, c4 = -c2
+const C1: &str = "851695d49a83f8ef919bb86153cbcb16630fb68aed0a766a3ec693d68e6afa40";
+const C2: &str = "7ae96a2b657c07106e64479eac3434e99cf0497512f58995c1396c28719501ee";
+const C3: &str = "7ae96a2b657c07106e64479eac3434e99cf0497512f58995c1396c28719501ef";
+const C4: &str = "851695d49a83f8ef919bb86153cbcb16630fb68aed0a766a3ec693d68e6afa41";
+
+/// Encodes a public key, every `rnd` results in a different encoding of the same key.
+pub fn encode(pubkey: &PublicKey, rnd: &[u8; 32]) -> [u8; 64] {
+    let mut hasher = HashDigest::tagged(b"secp256k1_ellswift_encode");
+    hasher.input(&pubkey.clone().compressed());
+    hasher.input(&[0u8; 31]);
+    hasher.input(rnd);
+    elligatorswift(&pubkey.point, &hasher)
+}
+
+/// Computes the public key of `key` and encodes it, with the randomness derived from the key and `aux_rand`.
+pub fn create(key: &PrivateKey, aux_rand: Option<&[u8; 32]>) -> [u8; 64] {
+    let mut hasher = HashDigest::tagged(b"secp256k1_ellswift_create");
+    hasher.input(&key.serialize());
+    hasher.input(&[0u8; 32]);
+    if let Some(aux_rand) = aux_rand {
+        hasher.input(aux_rand);
+    }
+    elligatorswift(&key.generate_pubkey().point, &hasher)
+}
+
+/// Decodes an encoding into a public key, the parity of `y` is the parity of `t`.
+pub fn decode(ell: &[u8; 64]) -> PublicKey {
+    let (u, t) = parse_ell(ell);
+    let x = xswiftec(&u, &t);
+    let group = get_context().generator().group;
+    let mut y = group.get_y(&x);
+    if y.is_even() != t.is_even() {
+        y.reflect();
+    }
+    PublicKey::from(Point { x, y, group })
+}
+
+/// Decodes only the x coordinate of an encoding.
+pub fn decode_x(ell: &[u8; 64]) -> [u8; 32] {
+    let (u, t) = parse_ell(ell);
+    xswiftec(&u, &t).serialize_num()
+}
+
+/// The BIP324 shared secret, `ell_a` is the encoding sent by the initiator and `ell_b` the one sent by the responder.
+pub fn bip324_ecdh(ell_a: &[u8; 64], ell_b: &[u8; 64], key: &PrivateKey, initiating: bool) -> [u8; 32] {
+    let theirs = if initiating { ell_b } else { ell_a };
+    let shared: Point = &key.scalar * decode(theirs).point;
+    let mut hasher = HashDigest::tagged(b"bip324_ellswift_xonly_ecdh");
+    hasher.input(ell_a);
+    hasher.input(ell_b);
+    hasher.input(&shared.x.serialize_num());
+    hasher.result()
+}
+
+fn parse_ell(ell: &[u8; 64]) -> (FieldElement, FieldElement) {
+    let p = &get_context().modulo;
+    let mut u = FieldElement::from_serialize(&ell[..32], p.clone());
+    let mut t = FieldElement::from_serialize(&ell[32..], p.clone());
+    u.mod_num();
+    t.mod_num();
+    (u, t)
+}
+
+// Finds an encoding whose `t` has the parity of the point's `y`.
+fn elligatorswift(point: &Point, hasher: &HashDigest) -> [u8; 64] {
+    let (u, mut t) = xelligatorswift(&point.x, hasher);
+    if t.is_even() != point.y.is_even() {
+        t = &BigInt::from(0) - t;
+    }
+    let mut res = [0u8; 64];
+    res[..32].copy_from_slice(&u);
+    res[32..].copy_from_slice(&t.serialize_num());
+    res
+}
+
+// Tries random `u`s and branches until one of them has a `t` which decodes to `x`, 4 tries on average.
+// The first output of the PRNG (and every 65th after it) is a pool of 64 branch values.
+fn xelligatorswift(x: &FieldElement, hasher: &HashDigest) -> ([u8; 32], FieldElement) {
+    let p = &get_context().modulo;
+    let mut branch_hash = [0u8; 32];
+    let mut branches_left = 0;
+    let mut cnt = 0u32;
+    loop {
+        if branches_left == 0 {
+            branch_hash = prng(hasher, cnt);
+            cnt += 1;
+            branches_left = 64;
+        }
+        branches_left -= 1;
+        let branch = (branch_hash[branches_left >> 1] >> ((branches_left & 1) << 2)) & 7;
+        let u_bytes = prng(hasher, cnt);
+        cnt += 1;
+        let mut u = FieldElement::from_serialize(&u_bytes, p.clone());
+        u.mod_num();
+        if let Some(t) = xswiftec_inv(x, &u, branch) {
+            return (u_bytes, t);
+        }
+    }
+}
+
+fn prng(hasher: &HashDigest, cnt: u32) -> [u8; 32] {
+    let mut hasher = hasher.clone();
+    hasher.input(&cnt.to_le_bytes());
+    hasher.result()
+}
+
+// The decoding map XSwiftEC(u, t) from the paper, with the edge cases handled like in BIP324.
+fn xswiftec(u: &FieldElement, t: &FieldElement) -> FieldElement {
+    let one = FieldElement::new(1u8, get_context().modulo.clone());
+    let u = if u.is_zero() { one.clone() } else { u.clone() };
+    let mut s = if t.is_zero() { one } else { t.pow_u(2u8) };
+    let g = u.pow_u(3u8) + 7u8;
+    if (g.clone() + &s).is_zero() {
+        s = s * 4u8;
+    }
+    // x3 = u - (g+s)^2/(3*s*u^2)
+    let x3 = u.clone() - ((g.clone() + &s).pow_u(2u8) / (s.clone() * u.pow_u(2u8) * 3u8));
+    if is_x_coord(&x3) {
+        return x3;
+    }
+    // x2 = u*(c1*s + c2*g)/(g+s)
+    let x2 = u.clone() * ((fe(C1) * &s) + (fe(C2) * &g)) / (g + s);
+    if is_x_coord(&x2) {
+        return x2;
+    }
+    // x1 = -(x2 + u)
+    &BigInt::from(0) - (x2 + u)
+}
+
+// Finds a `t` such that `xswiftec(u, t) = x`, each of the 8 branches `c` results in a different `t` if it exists.
+fn xswiftec_inv(x: &FieldElement, u: &FieldElement, c: u8) -> Option<FieldElement> {
+    let zero = BigInt::from(0);
+    let g = u.pow_u(3u8) + 7u8;
+    let (s, v) = if c & 2 == 0 {
+        // Inverting x1 or x2, fail if the result would decode through x3 instead.
+        if is_x_coord(&(&zero - (x.clone() + u))) {
+            return None;
+        }
+        // s = -(u^3+7)/(u^2+u*x+x^2)
+        let d = &zero - (u.pow_u(2u8) + (u.clone() * x) + x.pow_u(2u8));
+        if !(d.clone() * &g).is_square() {
+            return None;
+        }
+        (g / d, x.clone())
+    } else {
+        // Inverting x3, s = x-u and v = (r/s-u)/2 with r = sqrt(-s*(4*(u^3+7)+3*u^2*s))
+        let s = x.clone() - u;
+        if !s.is_square() {
+            return None;
+        }
+        let mut r = &zero - (s.clone() * ((g * 4u8) + (s.clone() * u.pow_u(2u8) * 3u8)));
+        if !r.is_square() {
+            return None;
+        }
+        r.sqrt();
+        if (c & 1 == 1 && r.is_zero()) || s.is_zero() {
+            return None;
+        }
+        let v = ((r / &s) - u) / 2u8;
+        (s, v)
+    };
+    let mut w = s;
+    w.sqrt();
+    if c & 5 == 0 || c & 5 == 5 {
+        w = &zero - w;
+    }
+    let c = if c & 1 == 1 { fe(C4) } else { fe(C3) };
+    Some(w * ((c * u) + v))
+}
+
+fn is_x_coord(x: &FieldElement) -> bool {
+    (x.pow_u(3u8) + 7u8).is_square()
+}
+
+fn fe(hex: &str) -> FieldElement {
+    FieldElement::new(BigInt::parse_bytes(hex.as_bytes(), 16).unwrap(), get_context().modulo.clone())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rustc_hex::FromHex;
+
+    fn hex(hex: &str) -> Vec<u8> {
+        hex.from_hex().unwrap()
+    }
+
+    fn ell(hex_str: &str) -> [u8; 64] {
+        let mut res = [0u8; 64];
+        res.copy_from_slice(&hex(hex_str));
+        res
+    }
+
+    // Test vectors from BIP324 (via libsecp256k1).
+    #[test]
+    fn test_xswiftec_inv() {
+        // (u, x, the encodings for each branch or "" if the branch fails)
+        let vectors = [
+            (
+                "05ff6bdad900fc3261bc7fe34e2fb0f569f06e091ae437d3a52e9da0cbfb9590",
+                "80cdf63774ec7022c89a5a8558e373a279170285e0ab27412dbce510bdfe23fc",
+                [
+                    "",
+                    "",
+                    "45654798ece071ba79286d04f7f3eb1c3f1d17dd883610f2ad2efd82a287466b",
+                    "0aeaa886f6b76c7158452418cbf5033adc5747e9e9b5d3b2303db96936528557",
+                    "",
+                    "",
+                    "ba9ab867131f8e4586d792fb080c14e3c0e2e82277c9ef0d52d1027c5d78b5c4",
+                    "f51557790948938ea7badbe7340afcc523a8b816164a2c4dcfc24695c9ad76d8",
+                ],
+            ),
+            (
+                "1737a85f4c8d146cec96e3ffdca76d9903dcf3bd53061868d478c78c63c2aa9e",
+                "39e48dd150d2f429be088dfd5b61882e7e8407483702ae9a5ab35927b15f85ea",
+                [
+                    "1be8cc0b04be0c681d0c6a68f733f82c6c896e0c8a262fcd392918e303a7abf4",
+                    "605b5814bf9b8cb066667c9e5480d22dc5b6c92f14b4af3ee0a9eb83b03685e3",
+                    "",
+                    "",
+                    "e41733f4fb41f397e2f3959708cc07d3937691f375d9d032c6d6e71bfc58503b",
+                    "9fa4a7eb4064734f99998361ab7f2dd23a4936d0eb4b50c11f56147b4fc9764c",
+                    "",
+                    "",
+                ],
+            ),
+            (
+                "1aaa1ccebf9c724191033df366b36f691c4d902c228033ff4516d122b2564f68",
+                "c75541259d3ba98f207eaa30c69634d187d0b6da594e719e420f4898638fc5b0",
+                ["", "", "", "", "", "", "", ""],
+            ),
+            (
+                "2323a1d079b0fd72fc8bb62ec34230a815cb0596c2bfac998bd6b84260f5dc26",
+                "239342dfb675500a34a196310b8d87d54f49dcac9da50c1743ceab41a7b249ff",
+                [
+                    "f63580b8aa49c4846de56e39e1b3e73f171e881eba8c66f614e67e5c975dfc07",
+                    "b6307b332e699f1cf77841d90af25365404deb7fed5edb3090db49e642a156b6",
+                    "",
+                    "",
+                    "09ca7f4755b63b7b921a91c61e4c18c0e8e177e145739909eb1981a268a20028",
+                    "49cf84ccd19660e30887be26f50dac9abfb2148012a124cf6f24b618bd5ea579",
+                    "",
+                    "",
+                ],
+            ),
+            (
+                "2dc90e640cb646ae9164c0b5a9ef0169febe34dc4437d6e46acb0e27e219d1e8",
+                "d236f19bf349b9516e9b3f4a5610fe960141cb23bbc8291b9534f1d71de62a47",
+                [
+                    "e69df7d9c026c36600ebdf588072675847c0c431c8eb730682533e964b6252c9",
+                    "4f18bbdf7c2d6c5f818c18802fa35cd069eaa79fff74e4fc837c80d93fece2f8",
+                    "",
+                    "",
+                    "196208263fd93c99ff1420a77f8d98a7b83f3bce37148cf97dacc168b49da966",
+                    "b0e7442083d293a07e73e77fd05ca32f96155860008b1b037c837f25c0131937",
+                    "",
+                    "",
+                ],
+            ),
+            (
+                "3edd7b3980e2f2f34d1409a207069f881fda5f96f08027ac4465b63dc278d672",
+                "053a98de4a27b1961155822b3a3121f03b2a14458bd80eb4a560c4c7a85c149c",
+                [
+                    "",
+                    "",
+                    "b3dae4b7dcf858e4c6968057cef2b156465431526538199cf52dc1b2d62fda30",
+                    "4aa77dd55d6b6d3cfa10cc9d0fe42f79232e4575661049ae36779c1d0c666d88",
+                    "",
+                    "",
+                    "4c251b482307a71b39697fa8310d4ea9b9abcead9ac7e6630ad23e4c29d021ff",
+                    "b558822aa29492c305ef3362f01bd086dcd1ba8a99efb651c98863e1f3998ea7",
+                ],
+            ),
+            (
+                "4295737efcb1da6fb1d96b9ca7dcd1e320024b37a736c4948b62598173069f70",
+                "fa7ffe4f25f88362831c087afe2e8a9b0713e2cac1ddca6a383205a266f14307",
+                ["", "", "", "", "", "", "", ""],
+            ),
+            (
+                "587c1a0cee91939e7f784d23b963004a3bf44f5d4e32a0081995ba20b0fca59e",
+                "2ea988530715e8d10363907ff25124524d471ba2454d5ce3be3f04194dfd3a3c",
+                [
+                    "cfd5a094aa0b9b8891b76c6ab9438f66aa1c095a65f9f70135e8171292245e74",
+                    "a89057d7c6563f0d6efa19ae84412b8a7b47e791a191ecdfdf2af84fd97bc339",
+                    "475d0ae9ef46920df07b34117be5a0817de1023e3cc32689e9be145b406b0aef",
+                    "a0759178ad80232454f827ef05ea3e72ad8d75418e6d4cc1cd4f5306c5e7c453",
+                    "302a5f6b55f464776e48939546bc709955e3f6a59a0608feca17e8ec6ddb9dbb",
+                    "576fa82839a9c0f29105e6517bbed47584b8186e5e6e132020d507af268438f6",
+                    "b8a2f51610b96df20f84cbee841a5f7e821efdc1c33cd9761641eba3bf94f140",
+                    "5f8a6e87527fdcdbab07d810fa15c18d52728abe7192b33e32b0acf83a1837dc",
+                ],
+            ),
+        ];
+        for (u, x, encs) in vectors.iter() {
+            let u = fe(u);
+            let x = fe(x);
+            for (c, enc) in encs.iter().enumerate() {
+                match xswiftec_inv(&x, &u, c as u8) {
+                    Some(t) => {
+                        assert_eq!(t, fe(enc));
+                        assert_eq!(xswiftec(&u, &t), x);
+                    }
+                    None => assert!(enc.is_empty()),
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_decode() {
+        // (encoding, x, odd y)
+        let vectors = [
+            ("00000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000", "edd1fd3e327ce90cc7a3542614289aee9682003e9cf7dcc9cf2ca9743be5aa0c", false),
+            ("000000000000000000000000000000000000000000000000000000000000000001d3475bf7655b0fb2d852921035b2ef607f49069b97454e6795251062741771", "b5da00b73cd6560520e7c364086e7cd23a34bf60d0e707be9fc34d4cd5fdfa2c", true),
+            ("000000000000000000000000000000000000000000000000000000000000000082277c4a71f9d22e66ece523f8fa08741a7c0912c66a69ce68514bfd3515b49f", "f482f2e241753ad0fb89150d8491dc1e34ff0b8acfbb442cfe999e2e5e6fd1d2", true),
+            ("00000000000000000000000000000000000000000000000000000000000000008421cc930e77c9f514b6915c3dbe2a94c6d8f690b5b739864ba6789fb8a55dd0", "9f59c40275f5085a006f05dae77eb98c6fd0db1ab4a72ac47eae90a4fc9e57e0", false),
+            ("0000000000000000000000000000000000000000000000000000000000000000bde70df51939b94c9c24979fa7dd04ebd9b3572da7802290438af2a681895441", "aaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaa9fffffd6b", true),
+            ("0000000000000000000000000000000000000000000000000000000000000000d19c182d2759cd99824228d94799f8c6557c38a1c0d6779b9d4b729c6f1ccc42", "70720db7e238d04121f5b1afd8cc5ad9d18944c6bdc94881f502b7a3af3aecff", false),
+            ("0000000000000000000000000000000000000000000000000000000000000000fffffffffffffffffffffffffffffffffffffffffffffffffffffffefffffc2f", "edd1fd3e327ce90cc7a3542614289aee9682003e9cf7dcc9cf2ca9743be5aa0c", false),
+            ("0000000000000000000000000000000000000000000000000000000000000000ffffffffffffffffffffffffffffffffffffffffffffffffffffffff2664bbd5", "50873db31badcc71890e4f67753a65757f97aaa7dd5f1e82b753ace32219064b", false),
+            ("0000000000000000000000000000000000000000000000000000000000000000ffffffffffffffffffffffffffffffffffffffffffffffffffffffff7028de7d", "1eea9cc59cfcf2fa151ac6c274eea4110feb4f7b68c5965732e9992e976ef68e", false),
+            ("0000000000000000000000000000000000000000000000000000000000000000ffffffffffffffffffffffffffffffffffffffffffffffffffffffffcbcfb7e7", "12303941aedc208880735b1f1795c8e55be520ea93e103357b5d2adb7ed59b8e", false),
+            ("0000000000000000000000000000000000000000000000000000000000000000fffffffffffffffffffffffffffffffffffffffffffffffffffffffff3113ad9", "7eed6b70e7b0767c7d7feac04e57aa2a12fef5e0f48f878fcbb88b3b6b5e0783", false),
+            ("0a2d2ba93507f1df233770c2a797962cc61f6d15da14ecd47d8d27ae1cd5f8530000000000000000000000000000000000000000000000000000000000000000", "532167c11200b08c0e84a354e74dcc40f8b25f4fe686e30869526366278a0688", false),
+            ("0a2d2ba93507f1df233770c2a797962cc61f6d15da14ecd47d8d27ae1cd5f853fffffffffffffffffffffffffffffffffffffffffffffffffffffffefffffc2f", "532167c11200b08c0e84a354e74dcc40f8b25f4fe686e30869526366278a0688", false),
+            ("0ffde9ca81d751e9cdaffc1a50779245320b28996dbaf32f822f20117c22fbd6c74d99efceaa550f1ad1c0f43f46e7ff1ee3bd0162b7bf55f2965da9c3450646", "74e880b3ffd18fe3cddf7902522551ddf97fa4a35a3cfda8197f947081a57b8f", false),
+            ("0ffde9ca81d751e9cdaffc1a50779245320b28996dbaf32f822f20117c22fbd6ffffffffffffffffffffffffffffffffffffffffffffffffffffffff156ca896", "377b643fce2271f64e5c8101566107c1be4980745091783804f654781ac9217c", true),
+            ("123658444f32be8f02ea2034afa7ef4bbe8adc918ceb49b12773b625f490b368ffffffffffffffffffffffffffffffffffffffffffffffffffffffff8dc5fe11", "ed16d65cf3a9538fcb2c139f1ecbc143ee14827120cbc2659e667256800b8142", false),
+            ("146f92464d15d36e35382bd3ca5b0f976c95cb08acdcf2d5b3570617990839d7ffffffffffffffffffffffffffffffffffffffffffffffffffffffff3145e93b", "0d5cd840427f941f65193079ab8e2e83024ef2ee7ca558d88879ffd879fb6657", false),
+            ("15fdf5cf09c90759add2272d574d2bb5fe1429f9f3c14c65e3194bf61b82aa73ffffffffffffffffffffffffffffffffffffffffffffffffffffffff04cfd906", "16d0e43946aec93f62d57eb8cde68951af136cf4b307938dd1447411e07bffe1", true),
+            ("1f67edf779a8a649d6def60035f2fa22d022dd359079a1a144073d84f19b92d50000000000000000000000000000000000000000000000000000000000000000", "025661f9aba9d15c3118456bbe980e3e1b8ba2e047c737a4eb48a040bb566f6c", false),
+            ("1f67edf779a8a649d6def60035f2fa22d022dd359079a1a144073d84f19b92d5fffffffffffffffffffffffffffffffffffffffffffffffffffffffefffffc2f", "025661f9aba9d15c3118456bbe980e3e1b8ba2e047c737a4eb48a040bb566f6c", false),
+        ];
+        for (enc, x, odd_y) in vectors.iter() {
+            let pubkey = decode(&ell(enc));
+            assert_eq!(&decode_x(&ell(enc))[..], &hex(x)[..]);
+            assert_eq!(&pubkey.clone().compressed()[1..], &hex(x)[..]);
+            assert_eq!(pubkey.point.y.is_even(), !odd_y);
+        }
+    }
+
+    #[test]
+    fn test_bip324_ecdh() {
+        // (our key, our encoding, their encoding, initiating, shared secret)
+        let vectors = [
+            ("61062ea5071d800bbfd59e2e8b53d47d194b095ae5a4df04936b49772ef0d4d7", "ec0adff257bbfe500c188c80b4fdd640f6b45a482bbc15fc7cef5931deff0aa186f6eb9bba7b85dc4dcc28b28722de1e3d9108b985e2967045668f66098e475b", "a4a94dfce69b4a2a0a099313d10f9f7e7d649d60501c9e1d274c300e0d89aafaffffffffffffffffffffffffffffffffffffffffffffffffffffffff8faf88d5", true, "c6992a117f5edbea70c3f511d32d26b9798be4b81a62eaee1a5acaa8459a3592"),
+            ("1f9c581b35231838f0f17cf0c979835baccb7f3abbbb96ffcc318ab71e6e126f", "a1855e10e94e00baa23041d916e259f7044e491da6171269694763f018c7e63693d29575dcb464ac816baa1be353ba12e3876cba7628bd0bd8e755e721eb0140", "fffffffffffffffffffffffffffffffffffffffffffffffffffffffefffffc2f0000000000000000000000000000000000000000000000000000000000000000", false, "a0138f564f74d0ad70bc337dacc9d0bf1d2349364caf1188a1e6e8ddb3b7b184"),
+            ("0286c41cd30913db0fdff7a64ebda5c8e3e7cef10f2aebc00a7650443cf4c60d", "d1ee8a93a01130cbf299249a258f94feb5f469e7d0f2f28f69ee5e9aa8f9b54a60f2c3ff2d023634ec7f4127a96cc11662e402894cf1f694fb9a7eaa5f1d9244", "ffffffffffffffffffffffffffffffffffffffffffffffffffffffff22d5e441524d571a52b3def126189d3f416890a99d4da6ede2b0cde1760ce2c3f98457ae", true, "250b93570d411149105ab8cb0bc5079914906306368c23e9d77c2a33265b994c"),
+            ("6c77432d1fda31e9f942f8af44607e10f3ad38a65f8a4bddae823e5eff90dc38", "d2685070c1e6376e633e825296634fd461fa9e5bdf2109bcebd735e5a91f3e587c5cb782abb797fbf6bb5074fd1542a474f2a45b673763ec2db7fb99b737bbb9", "56bd0c06f10352c3a1a9f4b4c92f6fa2b26df124b57878353c1fc691c51abea77c8817daeeb9fa546b77c8daf79d89b22b0e1b87574ece42371f00237aa9d83a", false, "1918b741ef5f9d1d7670b050c152b4a4ead2c31be9aecb0681c0cd4324150853"),
+            ("a6ec25127ca1aa4cf16b20084ba1e6516baae4d32422288e9b36d8bddd2de35a", "ffffffffffffffffffffffffffffffffffffffffffffffffffffffff053d7ecca53e33e185a8b9be4e7699a97c6ff4c795522e5918ab7cd6b6884f67e683f3dc", "ffffffffffffffffffffffffffffffffffffffffffffffffffffffffa7730be30000000000000000000000000000000000000000000000000000000000000000", true, "dd210aa6629f20bb328e5d89daa6eb2ac3d1c658a725536ff154f31b536c23b2"),
+            ("0af952659ed76f80f585966b95ab6e6fd68654672827878684c8b547b1b94f5a", "ffffffffffffffffffffffffffffffffffffffffffffffffffffffffc81017fd92fd31637c26c906b42092e11cc0d3afae8d9019d2578af22735ce7bc469c72d", "9652d78baefc028cd37a6a92625b8b8f85fde1e4c944ad3f20e198bef8c02f19fffffffffffffffffffffffffffffffffffffffffffffffffffffffff2e91870", false, "3568f2aea2e14ef4ee4a3c2a8b8d31bc5e3187ba86db10739b4ff8ec92ff6655"),
+            ("f90e080c64b05824c5a24b2501d5aeaf08af3872ee860aa80bdcd430f7b63494", "ffffffffffffffffffffffffffffffffffffffffffffffffffffffff115173765dc202cf029ad3f15479735d57697af12b0131dd21430d5772e4ef11474d58b9", "12a50f3fafea7c1eeada4cf8d33777704b77361453afc83bda91eef349ae044d20126c6200547ea5a6911776c05dee2a7f1a9ba7dfbabbbd273c3ef29ef46e46", true, "e25461fb0e4c162e18123ecde88342d54d449631e9b75a266fd9260c2bb2f41d"),
+        ];
+        for (key, ours, theirs, initiating, shared) in vectors.iter() {
+            let key = PrivateKey::from_serialized(&hex(key));
+            let (ours, theirs) = (ell(ours), ell(theirs));
+            assert_eq!(&decode_x(&ours)[..], &key.generate_pubkey().compressed()[1..]);
+            let (ell_a, ell_b) = if *initiating { (&ours, &theirs) } else { (&theirs, &ours) };
+            assert_eq!(&bip324_ecdh(ell_a, ell_b, &key, *initiating)[..], &hex(shared)[..]);
+        }
+    }
+
+    #[test]
+    fn test_encode_round_trip() {
+        for i in 1u32..6 {
+            let key = PrivateKey::new(i * 0x1234_5679);
+            let pubkey = key.generate_pubkey();
+            let rnd = [i as u8; 32];
+            let enc = encode(&pubkey, &rnd);
+            assert_eq!(decode(&enc), pubkey);
+            assert_ne!(encode(&pubkey, &[0xff; 32])[..], enc[..]);
+
+            let created = create(&key, Some(&rnd));
+            assert_eq!(decode(&created), pubkey);
+            assert_ne!(create(&key, None)[..], created[..]);
+        }
+    }
+}
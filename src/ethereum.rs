@@ -0,0 +1,154 @@
+//! Ethereum helpers: [EIP-55](https://eips.ethereum.org/EIPS/eip-55) checksummed addresses and
+//! [EIP-191](https://eips.ethereum.org/EIPS/eip-191) `personal_sign` messages.
+//!
+//! Addresses are derived with [`PublicKey::ethereum_address`].
+
+use crate::hash::Keccak256;
+use crate::secp256k1::{PrivateKey, PublicKey, RecoverableSignature};
+
+const HEX_CHARS: &[u8; 16] = b"0123456789abcdef";
+
+/// Encodes an address as `0x` followed by the EIP-55 mixed case checksummed hex.
+pub fn to_checksum_address(address: &[u8; 20]) -> String {
+    let lower: Vec<u8> = address.iter().flat_map(|b| vec![HEX_CHARS[(b >> 4) as usize], HEX_CHARS[(b & 0xf) as usize]]).collect();
+    let hash = keccak256(&lower);
+
+    let mut res = String::with_capacity(42);
+    res.push_str("0x");
+    for (i, &c) in lower.iter().enumerate() {
+        let nibble = if i % 2 == 0 { hash[i / 2] >> 4 } else { hash[i / 2] & 0xf };
+        if nibble >= 8 {
+            res.push(c.to_ascii_uppercase() as char);
+        } else {
+            res.push(c as char);
+        }
+    }
+    res
+}
+
+/// Parses a hex address, with or without the `0x` prefix.
+///
+/// All lowercase and all uppercase addresses carry no checksum and are accepted as is,
+/// mixed case addresses must have a valid EIP-55 checksum.
+pub fn parse_address(address: &str) -> Result<[u8; 20], &'static str> {
+    let hex = address.strip_prefix("0x").unwrap_or(address);
+    if hex.len() != 40 {
+        return Err("An address should be 20 bytes");
+    }
+    let mut res = [0u8; 20];
+    for (i, c) in hex.bytes().enumerate() {
+        let nibble = match c {
+            b'0'..=b'9' => c - b'0',
+            b'a'..=b'f' => c - b'a' + 10,
+            b'A'..=b'F' => c - b'A' + 10,
+            _ => return Err("Invalid hex character"),
+        };
+        res[i / 2] |= if i % 2 == 0 { nibble << 4 } else { nibble };
+    }
+    let is_mixed = hex.bytes().any(|c| c.is_ascii_lowercase()) && hex.bytes().any(|c| c.is_ascii_uppercase());
+    if is_mixed && to_checksum_address(&res)[2..] != *hex {
+        return Err("Invalid EIP-55 checksum");
+    }
+    Ok(res)
+}
+
+/// Returns whether `address` is `0x` prefixed and has a valid EIP-55 checksum.
+pub fn is_valid_checksum_address(address: &str) -> bool {
+    match parse_address(address) {
+        Ok(parsed) => to_checksum_address(&parsed) == address,
+        Err(_) => false,
+    }
+}
+
+/// The EIP-191 version `0x45` hash: `Keccak256("\x19Ethereum Signed Message:\n" || len(msg) || msg)`.
+pub fn hash_personal_message(msg: &[u8]) -> [u8; 32] {
+    let mut hasher = Keccak256::new();
+    hasher.input(b"\x19Ethereum Signed Message:\n");
+    hasher.input(msg.len().to_string().as_bytes());
+    hasher.input(msg);
+    hasher.finalize()
+}
+
+/// Signs like `eth_sign`/`personal_sign`, returning `r || s || v` where `v` is `27 + recid`.
+pub fn personal_sign(key: &PrivateKey, msg: &[u8]) -> [u8; 65] {
+    let mut sig = key.sign_recoverable(&hash_personal_message(msg), false).serialize();
+    sig[64] += 27;
+    sig
+}
+
+/// Recovers the address that `personal_sign`ed `msg`, `v` can either be `27`/`28` or the raw recovery id.
+pub fn recover_personal_sign(msg: &[u8], sig: &[u8; 65]) -> Result<[u8; 20], &'static str> {
+    let mut sig = *sig;
+    if sig[64] >= 27 {
+        sig[64] -= 27;
+    }
+    let sig = RecoverableSignature::parse(sig)?;
+    let pubkey = PublicKey::recover(&hash_personal_message(msg), &sig, false)?;
+    Ok(pubkey.ethereum_address())
+}
+
+fn keccak256(data: &[u8]) -> [u8; 32] {
+    let mut hasher = Keccak256::new();
+    hasher.input(data);
+    hasher.finalize()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rustc_hex::ToHex;
+
+    #[test]
+    fn test_ethereum_address() {
+        let address = PrivateKey::new(1u8).generate_pubkey().ethereum_address();
+        assert_eq!(to_checksum_address(&address), "0x7E5F4552091A69125d5DfCb7b8C2659029395Bdf");
+        let address = PrivateKey::new(2u8).generate_pubkey().ethereum_address();
+        assert_eq!(to_checksum_address(&address), "0x2B5AD5c4795c026514f8317c7a215E218DcCD6cF");
+    }
+
+    // Test vectors from EIP-55
+    #[test]
+    fn test_eip55() {
+        let vectors = [
+            "0x52908400098527886E0F7030069857D2E4169EE7",
+            "0x8617E340B3D01FA5F11F306F4090FD50E238070D",
+            "0xde709f2102306220921060314715629080e2fb77",
+            "0x27b1fdb04752bbc536007a920d24acb045561c26",
+            "0x5aAeb6053F3E94C9b9A09f33669435E7Ef1BeAed",
+            "0xfB6916095ca1df60bB79Ce92cE3Ea74c37c5d359",
+            "0xdbF03B407c01E7cD3CBea99509d93f8DDDC8C6FB",
+            "0xD1220A0cf47c7B9Be7A2E6BA89F429762e7b9aDb",
+        ];
+        for vector in &vectors {
+            let address = parse_address(vector).unwrap();
+            assert_eq!(to_checksum_address(&address), *vector);
+            assert!(is_valid_checksum_address(vector));
+            assert_eq!(parse_address(&vector.to_lowercase()).unwrap(), address);
+        }
+        assert!(parse_address("0x5aAeb6053F3E94C9b9A09f33669435E7Ef1BeAeD").is_err());
+        assert!(!is_valid_checksum_address("0x5aaeb6053f3e94c9b9a09f33669435e7ef1beaed"));
+        assert!(parse_address("0x5aAeb6053F3E94C9b9A09f33669435E7Ef1BeA").is_err());
+        assert!(parse_address("0x5aAeb6053F3E94C9b9A09f33669435E7Ef1BeAeg").is_err());
+    }
+
+    #[test]
+    fn test_personal_sign() {
+        assert_eq!(
+            hash_personal_message(b"hello world").to_hex::<String>(),
+            "d9eba16ed0ecae432b71fe008c98cc872bb4cc214d3220a36f365326cf807d68"
+        );
+
+        let key = PrivateKey::new(0xe7e1_u32);
+        let address = key.generate_pubkey().ethereum_address();
+        for msg in &[&b""[..], b"hello world", &[0x42; 200]] {
+            let sig = personal_sign(&key, msg);
+            assert!(sig[64] == 27 || sig[64] == 28);
+            assert_eq!(recover_personal_sign(msg, &sig).unwrap(), address);
+            assert_ne!(recover_personal_sign(b"other", &sig).ok(), Some(address));
+
+            let mut raw = sig;
+            raw[64] -= 27;
+            assert_eq!(recover_personal_sign(msg, &raw).unwrap(), address);
+        }
+    }
+}
@@ -161,3 +161,128 @@ pub mod schnorr {
         }
     }
 }
+
+pub mod ecdh {
+    use crate::secp256k1::get_context;
+    use crate::{PrivateKey, PublicKey};
+    use std::os::raw::{c_int, c_uchar, c_void};
+    use std::{ptr, slice};
+
+    /// A hash function for ECDH, receives the coordinates of the shared point and writes the result into output.
+    /// Should return 1 on success and 0 on failure.
+    pub type EcdhHashFunction =
+        Option<unsafe extern "C" fn(output: *mut c_uchar, x32: *const c_uchar, y32: *const c_uchar, data: *mut c_void) -> c_int>;
+
+    #[no_mangle]
+    /// Compute an EC Diffie-Hellman secret.
+    /// Accepts either compressed(33 btes) or uncompressed(65 bytes) public key. using the flag (1==compressed, 0==uncompressed).
+    ///
+    /// Input: pubkey -> pointer to 33 or 65 bytes pubkey depending on the compressed flag.
+    ///        compressed -> 1 for compressed, 0 for uncompressed.
+    ///        privkey -> pointer to 32 bytes private key.
+    ///        hashfp -> the hash function, if NULL the default SHA256(parity || x) is used and output must be 32 bytes.
+    ///        data -> arbitrary data passed to the hash function.
+    /// Output: output -> pointer to a buffer for the result of the hash function, at least 32 bytes for the default one.
+    ///
+    /// Returns:
+    /// 1 - Finished successfully.
+    /// 0 - The hash function failed.
+    /// -1 - Some other problem (a NULL pointer, an invalid public key, a compressed flag other than 0 or 1,
+    ///      or a private key that is zero or not below the order).
+    ///
+    /// # Safety
+    /// `pubkey` and `privkey` must point to readable buffers of the sizes above, and `output` to a buffer that is large enough
+    /// for the hash function's result (32 bytes for the default one).
+    pub unsafe extern "C" fn ecc_secp256k1_ecdh(
+        output: *mut c_uchar,
+        pubkey: *const c_uchar,
+        compressed: c_int,
+        privkey: *const c_uchar,
+        hashfp: EcdhHashFunction,
+        data: *mut c_void,
+    ) -> c_int {
+        if output.is_null() || pubkey.is_null() || privkey.is_null() {
+            return -1;
+        }
+        let pubkey = match compressed {
+            1 => slice::from_raw_parts(pubkey, 33),
+            0 => slice::from_raw_parts(pubkey, 65),
+            _ => return -1,
+        };
+        let pubkey = match PublicKey::from_slice(pubkey) {
+            Ok(k) => k,
+            Err(_) => return -1,
+        };
+
+        let privkey = slice::from_raw_parts(privkey, 32);
+        if privkey >= &get_context().serialized_order()[..] || privkey == [0u8; 32] {
+            return -1;
+        }
+        let key = PrivateKey::from_serialized(privkey);
+        match hashfp {
+            Some(hashfp) => key.ecdh_with(&pubkey, |x, y| hashfp(output, x.as_ptr(), y.as_ptr(), data)),
+            None => {
+                let secret = key.ecdh(&pubkey);
+                ptr::copy_nonoverlapping(secret.as_ptr(), output, secret.len());
+                1
+            }
+        }
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+
+        unsafe extern "C" fn copy_x(output: *mut c_uchar, x32: *const c_uchar, _y32: *const c_uchar, data: *mut c_void) -> c_int {
+            *(data as *mut u32) += 1;
+            ptr::copy_nonoverlapping(x32, output, 32);
+            1
+        }
+
+        unsafe extern "C" fn fail(_output: *mut c_uchar, _x32: *const c_uchar, _y32: *const c_uchar, _data: *mut c_void) -> c_int {
+            0
+        }
+
+        #[test]
+        fn test_ffi_ecdh() {
+            let key = PrivateKey::new(0xecd4_u32);
+            let pubkey = PrivateKey::new(0x1234_5678_u32).generate_pubkey();
+            let privkey = key.serialize();
+            let compressed = pubkey.clone().compressed();
+            let uncompressed = pubkey.clone().uncompressed();
+
+            let mut out = [0u8; 32];
+            let res = unsafe { ecc_secp256k1_ecdh(out.as_mut_ptr(), compressed.as_ptr(), 1, privkey.as_ptr(), None, ptr::null_mut()) };
+            assert_eq!(res, 1);
+            assert_eq!(out, key.ecdh(&pubkey));
+
+            let mut calls = 0u32;
+            let data = &mut calls as *mut u32 as *mut c_void;
+            let res = unsafe { ecc_secp256k1_ecdh(out.as_mut_ptr(), uncompressed.as_ptr(), 0, privkey.as_ptr(), Some(copy_x), data) };
+            assert_eq!(res, 1);
+            assert_eq!(calls, 1);
+            assert_eq!(out, key.ecdh_xonly(&pubkey));
+
+            let res =
+                unsafe { ecc_secp256k1_ecdh(out.as_mut_ptr(), compressed.as_ptr(), 1, privkey.as_ptr(), Some(fail), ptr::null_mut()) };
+            assert_eq!(res, 0);
+            let res = unsafe { ecc_secp256k1_ecdh(out.as_mut_ptr(), compressed.as_ptr(), 2, privkey.as_ptr(), None, ptr::null_mut()) };
+            assert_eq!(res, -1);
+
+            let mut invalid = uncompressed;
+            invalid[64] ^= 1;
+            let res = unsafe { ecc_secp256k1_ecdh(out.as_mut_ptr(), invalid.as_ptr(), 0, privkey.as_ptr(), None, ptr::null_mut()) };
+            assert_eq!(res, -1);
+            let mut hybrid = uncompressed;
+            hybrid[0] = 0x06;
+            let res = unsafe { ecc_secp256k1_ecdh(out.as_mut_ptr(), hybrid.as_ptr(), 0, privkey.as_ptr(), None, ptr::null_mut()) };
+            assert_eq!(res, -1);
+
+            for privkey in &[[0u8; 32], get_context().serialized_order(), [0xff; 32]] {
+                let res =
+                    unsafe { ecc_secp256k1_ecdh(out.as_mut_ptr(), compressed.as_ptr(), 1, privkey.as_ptr(), None, ptr::null_mut()) };
+                assert_eq!(res, -1);
+            }
+        }
+    }
+}
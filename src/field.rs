@@ -1,3 +1,4 @@
+use crate::jacobi::{self, Jacobi};
 use num_bigint::{BigInt, Sign};
 use num_integer::Integer;
 use num_traits::Zero;
@@ -98,6 +99,11 @@ impl FieldElement {
     pub fn is_even(&self) -> bool {
         self.num.is_even()
     }
+
+    /// Returns true if this is a quadratic residue (zero included), the modulo must be an odd prime.
+    pub fn is_square(&self) -> bool {
+        self.is_zero() || jacobi::jacobi_symbol(self.num.clone(), self.modulo.clone()) == Jacobi::One
+    }
 }
 
 #[inline(always)]
@@ -0,0 +1,770 @@
+//! FROST threshold Schnorr signatures ([RFC 9591](https://www.rfc-editor.org/rfc/rfc9591)).
+//!
+//! Any `threshold` out of `max_signers` participants can produce a signature, which is a regular BIP340 signature over
+//! the x-only group key and can be checked with [`PublicKey::verify_bip340`].
+//! The round structure follows the RFC, but the challenge is the BIP340 one and the other hashes are BIP340 tagged hashes,
+//! this means the y coordinates of the group key and of the group commitment are forced to be even by negating the shares.
+//!
+//! Keys can be generated either by a trusted dealer ([`trusted_dealer_keygen`]) or by a Pedersen DKG with proofs of knowledge
+//! ([`dkg_part1`], [`dkg_part2`], [`dkg_part3`]).
+
+use crate::field::FieldElement;
+use crate::hash::HashDigest;
+use crate::hmac_sha2::HmacSha256Drbg;
+use crate::point::Point;
+use crate::secp256k1::{get_context, get_e_bip340, negate_scalar, scalar_mod_order, PrivateKey, PublicKey, SchnorrSignature};
+use std::fmt;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Error {
+    /// Identifiers must be non zero and unique.
+    InvalidIdentifier,
+    /// The threshold must be at least 2 and at most the amount of participants.
+    InvalidThreshold,
+    /// The proof of knowledge sent by this participant during the DKG is invalid.
+    InvalidProofOfKnowledge(u32),
+    /// The secret share sent by this participant doesn't match their commitment.
+    InvalidSecretShare(u32),
+    /// The signature share of this participant is invalid.
+    InvalidSignatureShare(u32),
+    /// A package from this participant is missing or unexpected.
+    UnknownParticipant(u32),
+    /// Not enough signers to reach the threshold.
+    NotEnoughSigners,
+    /// More signature shares than participants in the signing package.
+    TooManySignatureShares,
+    InvalidSecretKey,
+    InvalidSignatureShareEncoding,
+    InvalidCommitmentEncoding,
+    /// The aggregated signature doesn't verify even though every signature share does.
+    InvalidSignature,
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Error::InvalidIdentifier => write!(f, "Identifiers must be non zero and unique"),
+            Error::InvalidThreshold => write!(f, "Invalid threshold"),
+            Error::InvalidProofOfKnowledge(id) => write!(f, "Invalid proof of knowledge from participant {}", id),
+            Error::InvalidSecretShare(id) => write!(f, "Invalid secret share from participant {}", id),
+            Error::InvalidSignatureShare(id) => write!(f, "Invalid signature share from participant {}", id),
+            Error::UnknownParticipant(id) => write!(f, "Missing or unexpected package from participant {}", id),
+            Error::NotEnoughSigners => write!(f, "Not enough signers"),
+            Error::TooManySignatureShares => write!(f, "More signature shares than signers"),
+            Error::InvalidSecretKey => write!(f, "Invalid secret key"),
+            Error::InvalidSignatureShareEncoding => write!(f, "Invalid signature share encoding"),
+            Error::InvalidCommitmentEncoding => write!(f, "Invalid commitment encoding"),
+            Error::InvalidSignature => write!(f, "Invalid aggregated signature"),
+        }
+    }
+}
+
+/// A share of the group secret, together with the commitment to the polynomial it was evaluated on.
+#[derive(Clone, Debug)]
+pub struct SecretShare {
+    identifier: u32,
+    value: FieldElement,
+    commitment: Vec<Point>,
+}
+
+impl SecretShare {
+    pub fn identifier(&self) -> u32 {
+        self.identifier
+    }
+
+    /// Verifies the share against the dealer's commitment and turns it into the participant's signing material.
+    #[allow(non_snake_case)]
+    pub fn verify(&self) -> Result<KeyPackage, Error> {
+        let G = get_context().generator();
+        if &self.value.num * G != evaluate_commitment(&self.commitment, self.identifier) {
+            return Err(Error::InvalidSecretShare(self.identifier));
+        }
+        Ok(KeyPackage {
+            identifier: self.identifier,
+            signing_share: self.value.clone(),
+            verifying_share: evaluate_commitment(&self.commitment, self.identifier),
+            group_key: self.commitment[0].clone(),
+            threshold: self.commitment.len(),
+        })
+    }
+}
+
+/// Everything a single participant needs in order to sign.
+#[derive(Clone, Debug)]
+pub struct KeyPackage {
+    identifier: u32,
+    signing_share: FieldElement,
+    verifying_share: Point,
+    group_key: Point,
+    threshold: usize,
+}
+
+impl KeyPackage {
+    pub fn identifier(&self) -> u32 {
+        self.identifier
+    }
+
+    pub fn threshold(&self) -> usize {
+        self.threshold
+    }
+
+    pub fn group_public_key(&self) -> PublicKey {
+        PublicKey::from(self.group_key.clone())
+    }
+
+    pub fn verifying_share(&self) -> PublicKey {
+        PublicKey::from(self.verifying_share.clone())
+    }
+}
+
+/// The public keys needed to verify the signature shares and the final signature.
+#[derive(Clone, Debug, PartialEq)]
+pub struct PublicKeyPackage {
+    verifying_shares: Vec<(u32, Point)>,
+    group_key: Point,
+}
+
+impl PublicKeyPackage {
+    fn from_commitment(commitment: &[Point], identifiers: impl Iterator<Item = u32>) -> PublicKeyPackage {
+        let verifying_shares = identifiers.map(|id| (id, evaluate_commitment(commitment, id))).collect();
+        PublicKeyPackage { verifying_shares, group_key: commitment[0].clone() }
+    }
+
+    pub fn group_public_key(&self) -> PublicKey {
+        PublicKey::from(self.group_key.clone())
+    }
+
+    pub fn verifying_share(&self, identifier: u32) -> Option<PublicKey> {
+        self.verifying_shares.iter().find(|(id, _)| *id == identifier).map(|(_, share)| PublicKey::from(share.clone()))
+    }
+}
+
+/// Splits `secret` into `max_signers` shares (with identifiers `1..=max_signers`), any `threshold` of which can sign.
+///
+/// `seed` *must* be fresh randomness, it's used to generate the polynomial coefficients.
+pub fn trusted_dealer_keygen(
+    secret: &PrivateKey,
+    threshold: u32,
+    max_signers: u32,
+    seed: &[u8],
+) -> Result<(Vec<SecretShare>, PublicKeyPackage), Error> {
+    let order = &get_context().order;
+    check_threshold(threshold, max_signers)?;
+    if secret.scalar <= 0u8.into() || &secret.scalar >= order {
+        return Err(Error::InvalidSecretKey);
+    }
+    let mut rng = HmacSha256Drbg::new(seed, Some(&secret.serialize()));
+    let mut coefficients = vec![FieldElement::new(secret.scalar.clone(), order.clone())];
+    coefficients.extend((1..threshold).map(|_| random_scalar(&mut rng)));
+    let commitment = commit_polynomial(&coefficients);
+
+    let shares = (1..=max_signers)
+        .map(|identifier| SecretShare {
+            identifier,
+            value: evaluate_polynomial(&coefficients, identifier),
+            commitment: commitment.clone(),
+        })
+        .collect();
+    Ok((shares, PublicKeyPackage::from_commitment(&commitment, 1..=max_signers)))
+}
+
+/// The secret state a participant keeps between the first and second DKG rounds.
+#[derive(Debug)]
+pub struct Round1Secret {
+    identifier: u32,
+    max_signers: u32,
+    coefficients: Vec<FieldElement>,
+}
+
+/// Broadcast to every other participant in the first DKG round.
+#[derive(Clone, Debug)]
+pub struct Round1Package {
+    identifier: u32,
+    commitment: Vec<Point>,
+    proof_r: Point,
+    proof_mu: FieldElement,
+}
+
+impl Round1Package {
+    pub fn identifier(&self) -> u32 {
+        self.identifier
+    }
+}
+
+/// The secret state a participant keeps between the second and third DKG rounds.
+#[derive(Debug)]
+pub struct Round2Secret {
+    identifier: u32,
+    max_signers: u32,
+    commitment: Vec<Point>,
+    own_share: FieldElement,
+}
+
+/// Sent privately from `sender` to `receiver` in the second DKG round.
+#[derive(Clone, Debug)]
+pub struct Round2Package {
+    sender: u32,
+    receiver: u32,
+    share: FieldElement,
+}
+
+impl Round2Package {
+    pub fn sender(&self) -> u32 {
+        self.sender
+    }
+
+    pub fn receiver(&self) -> u32 {
+        self.receiver
+    }
+}
+
+/// First DKG round: commit to a random polynomial and prove knowledge of its constant term.
+///
+/// `seed` *must* be fresh randomness.
+#[allow(non_snake_case)]
+pub fn dkg_part1(identifier: u32, threshold: u32, max_signers: u32, seed: &[u8]) -> Result<(Round1Secret, Round1Package), Error> {
+    if identifier == 0 || identifier > max_signers {
+        return Err(Error::InvalidIdentifier);
+    }
+    check_threshold(threshold, max_signers)?;
+    let G = get_context().generator();
+    let mut rng = HmacSha256Drbg::new(seed, Some(&identifier.to_be_bytes()));
+    let coefficients: Vec<_> = (0..threshold).map(|_| random_scalar(&mut rng)).collect();
+    let commitment = commit_polynomial(&coefficients);
+
+    let k = random_scalar(&mut rng);
+    let proof_r = &k.num * G;
+    let c = pok_challenge(identifier, &commitment[0], &proof_r);
+    let proof_mu = k + c * &coefficients[0];
+
+    let package = Round1Package { identifier, commitment, proof_r, proof_mu };
+    Ok((Round1Secret { identifier, max_signers, coefficients }, package))
+}
+
+/// Second DKG round: verify everyone's proof of knowledge and evaluate our polynomial for each of them.
+///
+/// `round1_packages` are the packages received from all the *other* participants.
+pub fn dkg_part2(secret: Round1Secret, round1_packages: &[Round1Package]) -> Result<(Round2Secret, Vec<Round2Package>), Error> {
+    check_round1_packages(secret.identifier, secret.max_signers, secret.coefficients.len(), round1_packages)?;
+
+    let mut packages = Vec::with_capacity(round1_packages.len());
+    for package in round1_packages {
+        packages.push(Round2Package {
+            sender: secret.identifier,
+            receiver: package.identifier,
+            share: evaluate_polynomial(&secret.coefficients, package.identifier),
+        });
+    }
+    let round2_secret = Round2Secret {
+        identifier: secret.identifier,
+        max_signers: secret.max_signers,
+        commitment: commit_polynomial(&secret.coefficients),
+        own_share: evaluate_polynomial(&secret.coefficients, secret.identifier),
+    };
+    Ok((round2_secret, packages))
+}
+
+/// Final DKG round: verify the received shares and derive the signing share and the group key.
+///
+/// `round1_packages` and `round2_packages` are the packages received from all the *other* participants,
+/// the round 1 packages are checked again as they might not be the ones given to [`dkg_part2`].
+#[allow(non_snake_case)]
+pub fn dkg_part3(
+    secret: &Round2Secret,
+    round1_packages: &[Round1Package],
+    round2_packages: &[Round2Package],
+) -> Result<(KeyPackage, PublicKeyPackage), Error> {
+    let G = get_context().generator();
+    check_round1_packages(secret.identifier, secret.max_signers, secret.commitment.len(), round1_packages)?;
+    if let Some(package) = round2_packages.iter().find(|p| p.receiver != secret.identifier) {
+        return Err(Error::UnknownParticipant(package.sender));
+    }
+    let mut signing_share = secret.own_share.clone();
+    let mut group_commitment = secret.commitment.clone();
+
+    for package in round1_packages {
+        let share = round2_packages
+            .iter()
+            .find(|p| p.sender == package.identifier && p.receiver == secret.identifier)
+            .ok_or(Error::UnknownParticipant(package.identifier))?;
+        if &share.share.num * G.clone() != evaluate_commitment(&package.commitment, secret.identifier) {
+            return Err(Error::InvalidSecretShare(package.identifier));
+        }
+        signing_share = signing_share + &share.share;
+        for (sum, c) in group_commitment.iter_mut().zip(&package.commitment) {
+            *sum += c;
+        }
+    }
+    if let Some(package) = round2_packages.iter().find(|p| !round1_packages.iter().any(|r1| r1.identifier == p.sender)) {
+        return Err(Error::UnknownParticipant(package.sender));
+    }
+    // Every sender is known and has a package, so a longer list has duplicates.
+    if round2_packages.len() != round1_packages.len() {
+        return Err(Error::InvalidIdentifier);
+    }
+
+    let identifiers = round1_packages.iter().map(|p| p.identifier).chain(Some(secret.identifier));
+    let mut public_package = PublicKeyPackage::from_commitment(&group_commitment, identifiers);
+    public_package.verifying_shares.sort_by_key(|(id, _)| *id);
+
+    let key_package = KeyPackage {
+        identifier: secret.identifier,
+        verifying_share: &signing_share.num * G,
+        signing_share,
+        group_key: group_commitment[0].clone(),
+        threshold: group_commitment.len(),
+    };
+    Ok((key_package, public_package))
+}
+
+fn check_threshold(threshold: u32, max_signers: u32) -> Result<(), Error> {
+    if threshold < 2 || threshold > max_signers {
+        return Err(Error::InvalidThreshold);
+    }
+    Ok(())
+}
+
+// Checks that the packages come from every other participant, and verifies their proofs of knowledge.
+#[allow(non_snake_case)]
+fn check_round1_packages(me: u32, max_signers: u32, threshold: usize, packages: &[Round1Package]) -> Result<(), Error> {
+    let G = get_context().generator();
+    check_participants(me, max_signers, packages.iter().map(|p| p.identifier))?;
+    for package in packages {
+        if package.commitment.len() != threshold {
+            return Err(Error::InvalidProofOfKnowledge(package.identifier));
+        }
+        let c = pok_challenge(package.identifier, &package.commitment[0], &package.proof_r);
+        let expected = package.proof_r.clone() + (&c.num * &package.commitment[0]);
+        if &package.proof_mu.num * G.clone() != expected {
+            return Err(Error::InvalidProofOfKnowledge(package.identifier));
+        }
+    }
+    Ok(())
+}
+
+fn check_participants(me: u32, max_signers: u32, others: impl Iterator<Item = u32>) -> Result<(), Error> {
+    let mut seen = vec![me];
+    for id in others {
+        if id == 0 || id > max_signers || seen.contains(&id) {
+            return Err(Error::InvalidIdentifier);
+        }
+        seen.push(id);
+    }
+    if seen.len() != max_signers as usize {
+        return Err(Error::InvalidIdentifier);
+    }
+    Ok(())
+}
+
+fn pok_challenge(identifier: u32, constant: &Point, r: &Point) -> FieldElement {
+    let mut hasher = HashDigest::tagged(b"FROST/dkg-pok");
+    hasher.input(&identifier_to_scalar(identifier).serialize_num());
+    hasher.input(&PublicKey::from(constant.clone()).compressed());
+    hasher.input(&PublicKey::from(r.clone()).compressed());
+    scalar_mod_order(&hasher.result())
+}
+
+/// The secret nonces for a single signing session. Consumed by [`sign`] so they can't be reused.
+pub struct SigningNonces {
+    hiding: FieldElement,
+    binding: FieldElement,
+    commitments: SigningCommitments,
+}
+
+/// The public commitments to a participant's nonces, sent to the coordinator.
+#[derive(Clone, Debug, PartialEq)]
+pub struct SigningCommitments {
+    identifier: u32,
+    hiding: Point,
+    binding: Point,
+}
+
+impl SigningCommitments {
+    pub fn identifier(&self) -> u32 {
+        self.identifier
+    }
+
+    pub fn serialize(&self) -> [u8; 66] {
+        let mut res = [0u8; 66];
+        res[..33].copy_from_slice(&PublicKey::from(self.hiding.clone()).compressed());
+        res[33..].copy_from_slice(&PublicKey::from(self.binding.clone()).compressed());
+        res
+    }
+
+    pub fn parse(identifier: u32, ser: &[u8]) -> Result<SigningCommitments, Error> {
+        if ser.len() != 66 {
+            return Err(Error::InvalidCommitmentEncoding);
+        }
+        let hiding = PublicKey::from_compressed(&ser[..33]).map_err(|_| Error::InvalidCommitmentEncoding)?;
+        let binding = PublicKey::from_compressed(&ser[33..]).map_err(|_| Error::InvalidCommitmentEncoding)?;
+        Ok(SigningCommitments { identifier, hiding: hiding.point, binding: binding.point })
+    }
+}
+
+/// First signing round: generate a pair of nonces and their commitments.
+///
+/// `rand` *must* be 32 bytes of fresh randomness, the signing share is mixed in as a safety net like in the RFC.
+#[allow(non_snake_case)]
+pub fn commit(key_package: &KeyPackage, rand: [u8; 32]) -> (SigningNonces, SigningCommitments) {
+    let G = get_context().generator();
+    let secret = key_package.signing_share.clone().serialize_num();
+    let nonce = |domain: &[u8]| {
+        let mut hasher = HashDigest::tagged(domain);
+        hasher.input(&rand);
+        hasher.input(&secret);
+        scalar_mod_order(&hasher.result())
+    };
+    let hiding = nonce(b"FROST/nonce/hiding");
+    let binding = nonce(b"FROST/nonce/binding");
+    let commitments =
+        SigningCommitments { identifier: key_package.identifier, hiding: &hiding.num * G.clone(), binding: &binding.num * G };
+    (SigningNonces { hiding, binding, commitments: commitments.clone() }, commitments)
+}
+
+/// The message and the commitments of the participants taking part in a signing session.
+#[derive(Clone, Debug)]
+pub struct SigningPackage {
+    commitments: Vec<SigningCommitments>,
+    message: Vec<u8>,
+}
+
+impl SigningPackage {
+    pub fn new(mut commitments: Vec<SigningCommitments>, message: &[u8]) -> Result<SigningPackage, Error> {
+        commitments.sort_by_key(|c| c.identifier);
+        if commitments.iter().any(|c| c.identifier == 0) || commitments.windows(2).any(|w| w[0].identifier == w[1].identifier) {
+            return Err(Error::InvalidIdentifier);
+        }
+        Ok(SigningPackage { commitments, message: message.to_vec() })
+    }
+
+    fn identifiers(&self) -> impl Iterator<Item = u32> + '_ {
+        self.commitments.iter().map(|c| c.identifier)
+    }
+
+    fn commitment(&self, identifier: u32) -> Result<&SigningCommitments, Error> {
+        self.commitments.iter().find(|c| c.identifier == identifier).ok_or(Error::UnknownParticipant(identifier))
+    }
+
+    fn binding_factor(&self, group_key: &Point, identifier: u32) -> FieldElement {
+        let mut msg_hash = HashDigest::tagged(b"FROST/msg");
+        msg_hash.input(&self.message);
+        let mut commitments_hash = HashDigest::tagged(b"FROST/com");
+        for c in &self.commitments {
+            commitments_hash.input(&identifier_to_scalar(c.identifier).serialize_num());
+            commitments_hash.input(&c.serialize());
+        }
+
+        let mut hasher = HashDigest::tagged(b"FROST/rho");
+        hasher.input(&PublicKey::from(group_key.clone()).compressed());
+        hasher.input(&msg_hash.result());
+        hasher.input(&commitments_hash.result());
+        hasher.input(&identifier_to_scalar(identifier).serialize_num());
+        scalar_mod_order(&hasher.result())
+    }
+
+    // Returns the group commitment R (before the parity fix) and the BIP340 challenge.
+    fn group_commitment(&self, group_key: &Point) -> (Point, FieldElement) {
+        let mut r = get_context().generator().gen_zero();
+        for c in &self.commitments {
+            let rho = self.binding_factor(group_key, c.identifier);
+            r += &c.hiding;
+            r += &(&rho.num * &c.binding);
+        }
+        let e = get_e_bip340(&r.x.clone().serialize_num(), &group_key.x.clone().serialize_num(), &self.message);
+        (r, e)
+    }
+
+    fn lagrange_coefficient(&self, identifier: u32) -> FieldElement {
+        let x_i = identifier_to_scalar(identifier);
+        let mut num = FieldElement::new(1u8, get_context().order.clone());
+        let mut den = num.clone();
+        for x_j in self.identifiers().filter(|id| *id != identifier).map(identifier_to_scalar) {
+            num = num * &x_j;
+            den = den * (x_j - &x_i);
+        }
+        num / den
+    }
+}
+
+/// A single participant's contribution to the signature.
+#[derive(Clone, Debug, PartialEq)]
+pub struct SignatureShare {
+    identifier: u32,
+    share: FieldElement,
+}
+
+impl SignatureShare {
+    pub fn identifier(&self) -> u32 {
+        self.identifier
+    }
+
+    pub fn serialize(&self) -> [u8; 32] {
+        self.share.clone().serialize_num()
+    }
+
+    pub fn parse(identifier: u32, ser: &[u8; 32]) -> Result<SignatureShare, Error> {
+        let order = &get_context().order;
+        let share = FieldElement::from_serialize(ser, order.clone());
+        if &share.num >= order {
+            return Err(Error::InvalidSignatureShareEncoding);
+        }
+        Ok(SignatureShare { identifier, share })
+    }
+}
+
+/// Second signing round: produce this participant's signature share.
+pub fn sign(package: &SigningPackage, nonces: SigningNonces, key_package: &KeyPackage) -> Result<SignatureShare, Error> {
+    let identifier = key_package.identifier;
+    if package.commitments.len() < key_package.threshold {
+        return Err(Error::NotEnoughSigners);
+    }
+    if package.commitment(identifier)? != &nonces.commitments {
+        return Err(Error::UnknownParticipant(identifier));
+    }
+    let (r, e) = package.group_commitment(&key_package.group_key);
+    let rho = package.binding_factor(&key_package.group_key, identifier);
+    let lambda = package.lagrange_coefficient(identifier);
+
+    let (mut hiding, mut binding) = (nonces.hiding, nonces.binding);
+    if !r.y.is_even() {
+        hiding = negate_scalar(hiding);
+        binding = negate_scalar(binding);
+    }
+    let mut secret = key_package.signing_share.clone();
+    if !key_package.group_key.y.is_even() {
+        secret = negate_scalar(secret);
+    }
+    let share = hiding + (binding * &rho) + (lambda * &secret * &e);
+    Ok(SignatureShare { identifier, share })
+}
+
+/// Checks a single signature share against the signer's commitments and verifying share.
+#[allow(non_snake_case)]
+pub fn verify_signature_share(package: &SigningPackage, share: &SignatureShare, public: &PublicKeyPackage) -> Result<(), Error> {
+    let G = get_context().generator();
+    let identifier = share.identifier;
+    let commitment = package.commitment(identifier)?;
+    let mut verifying_share = public
+        .verifying_shares
+        .iter()
+        .find(|(id, _)| *id == identifier)
+        .map(|(_, p)| p.clone())
+        .ok_or(Error::UnknownParticipant(identifier))?;
+
+    let (r, e) = package.group_commitment(&public.group_key);
+    let rho = package.binding_factor(&public.group_key, identifier);
+    let lambda = package.lagrange_coefficient(identifier);
+
+    let mut R = commitment.hiding.clone() + (&rho.num * &commitment.binding);
+    if !r.y.is_even() {
+        R = -R;
+    }
+    if !public.group_key.y.is_even() {
+        verifying_share = -verifying_share;
+    }
+    if &share.share.num * G != R + ((lambda * &e).num * &verifying_share) {
+        return Err(Error::InvalidSignatureShare(identifier));
+    }
+    Ok(())
+}
+
+/// Sums the signature shares into a BIP340 signature, blaming the first bad share if the result doesn't verify.
+///
+/// There must be exactly one share from every participant in the signing package.
+pub fn aggregate(package: &SigningPackage, shares: &[SignatureShare], public: &PublicKeyPackage) -> Result<SchnorrSignature, Error> {
+    if shares.len() < package.commitments.len() {
+        return Err(Error::NotEnoughSigners);
+    }
+    if shares.len() > package.commitments.len() {
+        return Err(Error::TooManySignatureShares);
+    }
+    let mut identifiers: Vec<_> = shares.iter().map(|s| s.identifier).collect();
+    identifiers.sort_unstable();
+    if identifiers.windows(2).any(|w| w[0] == w[1]) {
+        return Err(Error::InvalidIdentifier);
+    }
+    // Same length and no duplicates, so any difference is a share from outside the package.
+    if let Some(identifier) = identifiers.iter().zip(package.identifiers()).find(|(a, b)| *a != b).map(|(a, _)| *a) {
+        return Err(Error::UnknownParticipant(identifier));
+    }
+
+    let (r, _) = package.group_commitment(&public.group_key);
+    let mut z = FieldElement::new(0u8, get_context().order.clone());
+    for share in shares {
+        z = z + &share.share;
+    }
+    let sig = SchnorrSignature::new(&r.x.serialize_num(), &z.serialize_num());
+
+    if !public.group_public_key().verify_bip340(&package.message, SchnorrSignature::parse(sig.serialize())) {
+        for share in shares {
+            verify_signature_share(package, share, public)?;
+        }
+        return Err(Error::InvalidSignature);
+    }
+    Ok(sig)
+}
+
+#[allow(non_snake_case)]
+fn commit_polynomial(coefficients: &[FieldElement]) -> Vec<Point> {
+    let G = get_context().generator();
+    coefficients.iter().map(|a| &a.num * G.clone()).collect()
+}
+
+// Horner's method.
+fn evaluate_polynomial(coefficients: &[FieldElement], identifier: u32) -> FieldElement {
+    let x = identifier_to_scalar(identifier);
+    let mut res = FieldElement::new(0u8, get_context().order.clone());
+    for a in coefficients.iter().rev() {
+        res = res * &x + a;
+    }
+    res
+}
+
+fn evaluate_commitment(commitment: &[Point], identifier: u32) -> Point {
+    let x = identifier_to_scalar(identifier);
+    let mut res = get_context().generator().gen_zero();
+    for c in commitment.iter().rev() {
+        res = &x.num * res;
+        res += c;
+    }
+    res
+}
+
+fn identifier_to_scalar(identifier: u32) -> FieldElement {
+    FieldElement::new(identifier, get_context().order.clone())
+}
+
+fn random_scalar(rng: &mut HmacSha256Drbg) -> FieldElement {
+    let order = &get_context().order;
+    loop {
+        let mut bytes = [0u8; 32];
+        rng.generate(&mut bytes);
+        let scalar = FieldElement::from_serialize(&bytes, order.clone());
+        if !scalar.is_zero() && &scalar.num < order {
+            return scalar;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sign_with(signers: &[&KeyPackage], public: &PublicKeyPackage, msg: &[u8]) -> Result<SchnorrSignature, Error> {
+        let (nonces, commitments): (Vec<_>, Vec<_>) =
+            signers.iter().enumerate().map(|(i, key)| commit(key, [i as u8 + 1; 32])).unzip();
+        let package = SigningPackage::new(commitments, msg)?;
+        let mut shares = Vec::new();
+        for (key, nonces) in signers.iter().zip(nonces) {
+            let share = sign(&package, nonces, key)?;
+            verify_signature_share(&package, &share, public)?;
+            shares.push(share);
+        }
+        aggregate(&package, &shares, public)
+    }
+
+    #[test]
+    fn test_trusted_dealer() {
+        let secret = PrivateKey::new(0xdead_beef_u64);
+        let (shares, public) = trusted_dealer_keygen(&secret, 3, 5, b"dealer randomness").unwrap();
+        assert_eq!(public.group_public_key(), secret.generate_pubkey());
+        let keys: Vec<_> = shares.iter().map(|s| s.verify().unwrap()).collect();
+
+        let msg = b"FROST in the morning";
+        for signers in &[[0usize, 1, 2], [4, 1, 3], [2, 4, 0]] {
+            let signers: Vec<_> = signers.iter().map(|&i| &keys[i]).collect();
+            let sig = sign_with(&signers, &public, msg).unwrap();
+            assert!(public.group_public_key().verify_bip340(msg, sig));
+        }
+        assert_eq!(sign_with(&[&keys[0], &keys[1]], &public, msg).unwrap_err(), Error::NotEnoughSigners);
+
+        let mut share = shares[3].clone();
+        share.value = share.value + 1u8;
+        assert_eq!(share.verify().unwrap_err(), Error::InvalidSecretShare(4));
+
+        for (threshold, max_signers) in [(1, 5), (3, 2), (0, 0)].iter() {
+            assert_eq!(trusted_dealer_keygen(&secret, *threshold, *max_signers, b"").unwrap_err(), Error::InvalidThreshold);
+        }
+    }
+
+    #[test]
+    fn test_dkg() {
+        let (threshold, max_signers) = (2, 3);
+        let (secrets, round1): (Vec<_>, Vec<_>) =
+            (1..=max_signers).map(|id| dkg_part1(id, threshold, max_signers, &[id as u8; 32]).unwrap()).unzip();
+        let others = |id: u32| round1.iter().filter(|p| p.identifier() != id).cloned().collect::<Vec<_>>();
+
+        let mut round2_secrets = Vec::new();
+        let mut round2_packages = Vec::new();
+        for secret in secrets {
+            let id = secret.identifier;
+            let (secret, packages) = dkg_part2(secret, &others(id)).unwrap();
+            round2_secrets.push(secret);
+            round2_packages.extend(packages);
+        }
+
+        let mut keys = Vec::new();
+        let mut publics = Vec::new();
+        for secret in &round2_secrets {
+            let received: Vec<_> = round2_packages.iter().filter(|p| p.receiver() == secret.identifier).cloned().collect();
+            let (key, public) = dkg_part3(secret, &others(secret.identifier), &received).unwrap();
+            keys.push(key);
+            publics.push(public);
+        }
+        assert!(publics.windows(2).all(|w| w[0] == w[1]));
+        for key in &keys {
+            assert_eq!(publics[0].verifying_share(key.identifier()), Some(key.verifying_share()));
+        }
+
+        let msg = b"Distributed keys";
+        let sig = sign_with(&[&keys[2], &keys[0]], &publics[0], msg).unwrap();
+        assert!(publics[0].group_public_key().verify_bip340(msg, sig));
+
+        // A tampered proof of knowledge is caught in the second round.
+        let (secret, _) = dkg_part1(1, threshold, max_signers, b"again").unwrap();
+        let mut bad = others(1);
+        bad[1].proof_mu = bad[1].proof_mu.clone() + 1u8;
+        assert_eq!(dkg_part2(secret, &bad).unwrap_err(), Error::InvalidProofOfKnowledge(3));
+
+        // And a tampered share in the third one.
+        let received: Vec<_> = round2_packages.iter().filter(|p| p.receiver() == 1).cloned().collect();
+        let mut tampered = received.clone();
+        tampered[0].share = tampered[0].share.clone() + 1u8;
+        let sender = tampered[0].sender();
+        assert_eq!(dkg_part3(&round2_secrets[0], &others(1), &tampered).unwrap_err(), Error::InvalidSecretShare(sender));
+
+        // The round 1 packages are checked again in the third round.
+        assert_eq!(dkg_part3(&round2_secrets[0], &bad, &received).unwrap_err(), Error::InvalidProofOfKnowledge(3));
+        assert_eq!(dkg_part3(&round2_secrets[0], &others(1)[..1], &received[..1]).unwrap_err(), Error::InvalidIdentifier);
+        let duplicated = [received.clone(), received[..1].to_vec()].concat();
+        assert_eq!(dkg_part3(&round2_secrets[0], &others(1), &duplicated).unwrap_err(), Error::InvalidIdentifier);
+        let misdirected: Vec<_> = round2_packages.iter().filter(|p| p.receiver() == 2).cloned().collect();
+        assert_eq!(dkg_part3(&round2_secrets[0], &others(1), &misdirected).unwrap_err(), Error::UnknownParticipant(1));
+    }
+
+    #[test]
+    fn test_bad_signature_share() {
+        let secret = PrivateKey::new(424_242u32);
+        let (shares, public) = trusted_dealer_keygen(&secret, 2, 3, b"seed").unwrap();
+        let keys: Vec<_> = shares.iter().map(|s| s.verify().unwrap()).collect();
+        let msg = b"blame";
+
+        let (n1, c1) = commit(&keys[0], [1; 32]);
+        let (n2, c2) = commit(&keys[2], [2; 32]);
+        let package = SigningPackage::new(vec![c1, c2], msg).unwrap();
+        let good = sign(&package, n1, &keys[0]).unwrap();
+        let mut bad = sign(&package, n2, &keys[2]).unwrap();
+        bad.share = bad.share + 1u8;
+
+        let parsed = SignatureShare::parse(bad.identifier(), &bad.serialize()).unwrap();
+        assert_eq!(verify_signature_share(&package, &parsed, &public), Err(Error::InvalidSignatureShare(3)));
+        assert_eq!(aggregate(&package, &[good.clone(), bad], &public).unwrap_err(), Error::InvalidSignatureShare(3));
+
+        // Every participant of the package must have exactly one share.
+        assert_eq!(aggregate(&package, &[good.clone(), good.clone()], &public).unwrap_err(), Error::InvalidIdentifier);
+        let outsider = SignatureShare::parse(2, &good.serialize()).unwrap();
+        assert_eq!(aggregate(&package, &[good.clone(), outsider.clone()], &public).unwrap_err(), Error::UnknownParticipant(2));
+        assert_eq!(aggregate(&package, &[good.clone(), good.clone(), outsider], &public).unwrap_err(), Error::TooManySignatureShares);
+        assert_eq!(aggregate(&package, &[good], &public).unwrap_err(), Error::NotEnoughSigners);
+    }
+}
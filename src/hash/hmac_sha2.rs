@@ -1,11 +1,18 @@
-use crate::hash::{HashDigest, HashTrait};
+use crate::hash::{HashDigest, HashTrait, Sha512};
 use std::io::Write;
 
+#[derive(Clone)]
 pub struct HmacSha256 {
     inner: HashDigest,
     outer: HashDigest,
 }
 
+#[derive(Clone)]
+pub struct HmacSha512 {
+    inner: Sha512,
+    outer: Sha512,
+}
+
 pub struct HmacSha256Drbg {
     pub(crate) k: [u8; 32],
     pub(crate) v: [u8; 32],
@@ -56,6 +63,50 @@ impl HmacSha256 {
     }
 }
 
+impl HmacSha512 {
+    const IPAD: [u8; 128] = [0x36; 128];
+    const IPAD_XOR_OPAD: [u8; 128] = [0x36 ^ 0x5C; 128];
+    const BLOCK_SIZE: usize = 128;
+
+    pub fn new(key: &[u8]) -> Self {
+        let mut k = [0u8; 128];
+        if key.len() > Self::BLOCK_SIZE {
+            let mut hasher = Sha512::new();
+            hasher.input(key);
+            k[..64].copy_from_slice(&hasher.finalize());
+        } else {
+            k[..key.len()].copy_from_slice(key);
+        }
+
+        let mut inner = Sha512::new();
+        xor(&mut k, &Self::IPAD);
+        inner.input(&k);
+
+        let mut outer = Sha512::new();
+        xor(&mut k, &Self::IPAD_XOR_OPAD);
+        outer.input(&k);
+
+        Self { inner, outer }
+    }
+
+    pub fn input(&mut self, text: &[u8]) {
+        self.inner.input(text)
+    }
+
+    pub fn finalize(self) -> [u8; 64] {
+        let Self { inner, mut outer } = self;
+        outer.input(&inner.finalize());
+        outer.finalize()
+    }
+
+    #[inline]
+    pub fn quick(key: &[u8], data: &[u8]) -> [u8; 64] {
+        let mut res = Self::new(key);
+        res.input(data);
+        res.finalize()
+    }
+}
+
 impl HmacSha256Drbg {
     pub fn new(seed: &[u8], seed2: Option<&[u8]>) -> Self {
         let k = [0u8; 32];
@@ -155,6 +206,54 @@ mod tests {
         ));
     }
 
+    // RFC 4231
+    #[test]
+    fn test_hmac_sha512_test_vectors() {
+        assert!(test_vector512(
+            hex("0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b"),
+            b"Hi There",
+            hex("87aa7cdea5ef619d4ff0b4241a1d6cb02379f4e2ce4ec2787ad0b30545e17cdedaa833b7d6b8a702038b274eaea3f4e4be9d914eeb61f1702e696c203a126854"),
+            None
+        ));
+        assert!(test_vector512(
+            b"Jefe",
+            b"what do ya want for nothing?",
+            hex("164b7a7bfcf819e2e395fbe73b56e0a387bd64222e831fd610270cd7ea2505549758bf75c05a994a6d034f65f8f0e6fdcaeab1a34d4a6b4b636e070a38bce737"),
+            None
+        ));
+        assert!(test_vector512(
+            [0xAA; 20],
+            &[0xDD; 50][..],
+            hex("fa73b0089d56a284efb0f0756c890be9b1b5dbdd8ee81a3655f83e33b2279d39bf3e848279a722c806b485a47e67c807b946a337bee8942674278859e13292fb"),
+            None
+        ));
+        assert!(test_vector512(
+            hex("0102030405060708090a0b0c0d0e0f10111213141516171819"),
+            &[0xCD_u8; 50][..],
+            hex("b0ba465637458c6990e5a8c5f61d4af7e576d97ff94b872de76f8050361ee3dba91ca5c11aa25eb4d679275cc5788063a5f19741120c4f2de2adebeb10a298dd"),
+            None
+        ));
+        assert!(test_vector512([0x0C_u8; 20], b"Test With Truncation", hex("415fad6271580a531d4179bc891d87a6"), Some(16)));
+        assert!(test_vector512(
+            &[0xAA; 131][..],
+            &b"Test Using Larger Than Block-Size Key - Hash Key First"[..],
+            hex("80b24263c7c1a3ebb71493c1dd7be8b49b46d1f41b4aeec1121b013783f8f3526b56d037e05f2598bd0fd2215d6a1e5295e64f73f63f0aec8b915a985d786598"),
+            None
+        ));
+        assert!(test_vector512(
+            &[0xAA; 131][..],
+            &b"This is a test using a larger than block-size key and a larger than block-size data. The key needs to be hashed before being used by the HMAC algorithm."[..],
+            hex("e37b6a775dc87dbaa4dfa9f96e5e3ffddebd71f8867289865df5a32d20cdc944b6022cac3c4982b10d5eeb55c3e4de15134676fb6de0446065c97440fa8c6a58"),
+            None
+        ));
+    }
+
+    fn test_vector512<A: AsRef<[u8]>, B: AsRef<[u8]>, C: AsRef<[u8]>>(key: A, data: B, res: C, len: Option<usize>) -> bool {
+        let res = res.as_ref();
+        let len = len.unwrap_or(64);
+        HmacSha512::quick(key.as_ref(), data.as_ref())[..len] == *res
+    }
+
     fn test_vector<A: AsRef<[u8]>, B: AsRef<[u8]>, C: AsRef<[u8]>>(key: A, data: B, res: C, len: Option<usize>) -> bool {
         let key = key.as_ref();
         let data = data.as_ref();
@@ -0,0 +1,207 @@
+//! Key derivation functions: HKDF-SHA256 ([RFC 5869](https://www.rfc-editor.org/rfc/rfc5869))
+//! and PBKDF2 ([RFC 8018](https://www.rfc-editor.org/rfc/rfc8018)) over HMAC-SHA256/512.
+
+use crate::hmac_sha2::{HmacSha256, HmacSha512};
+
+const HKDF_MAX_OUTPUT: usize = 255 * 32;
+
+/// HKDF-SHA256, holding the pseudorandom key produced by the extract step.
+#[derive(Clone)]
+pub struct Hkdf {
+    prk: [u8; 32],
+}
+
+impl Hkdf {
+    /// The extract step, a missing salt is the same as 32 zero bytes.
+    pub fn extract(salt: Option<&[u8]>, ikm: &[u8]) -> Hkdf {
+        let mut hmac = HmacSha256::new(salt.unwrap_or(&[0u8; 32]));
+        hmac.input(ikm);
+        Hkdf { prk: hmac.finalize() }
+    }
+
+    /// Skips the extract step, `prk` must already be a uniformly random key.
+    pub fn from_prk(prk: &[u8; 32]) -> Hkdf {
+        Hkdf { prk: *prk }
+    }
+
+    pub fn prk(&self) -> [u8; 32] {
+        self.prk
+    }
+
+    /// The expand step, fills `okm` which can be up to `255*32` bytes.
+    pub fn expand(&self, info: &[u8], okm: &mut [u8]) -> Result<(), &'static str> {
+        if okm.len() > HKDF_MAX_OUTPUT {
+            return Err("HKDF can output at most 255*32 bytes");
+        }
+        let mut prev: Option<[u8; 32]> = None;
+        for (i, chunk) in okm.chunks_mut(32).enumerate() {
+            let mut hmac = HmacSha256::new(&self.prk);
+            if let Some(prev) = prev {
+                hmac.input(&prev);
+            }
+            hmac.input(info);
+            hmac.input(&[i as u8 + 1]);
+            let block = hmac.finalize();
+            chunk.copy_from_slice(&block[..chunk.len()]);
+            prev = Some(block);
+        }
+        Ok(())
+    }
+}
+
+/// PBKDF2-HMAC-SHA256, fills `out` with the derived key. Fails if `iterations` is zero.
+pub fn pbkdf2_hmac_sha256(password: &[u8], salt: &[u8], iterations: u32, out: &mut [u8]) -> Result<(), &'static str> {
+    let hmac = HmacSha256::new(password);
+    pbkdf2(
+        |data| {
+            let mut hmac = hmac.clone();
+            hmac.input(data);
+            hmac.finalize()
+        },
+        salt,
+        iterations,
+        out,
+    )
+}
+
+/// PBKDF2-HMAC-SHA512, fills `out` with the derived key. Fails if `iterations` is zero, BIP39 uses it with 2048 iterations.
+pub fn pbkdf2_hmac_sha512(password: &[u8], salt: &[u8], iterations: u32, out: &mut [u8]) -> Result<(), &'static str> {
+    let hmac = HmacSha512::new(password);
+    pbkdf2(
+        |data| {
+            let mut hmac = hmac.clone();
+            hmac.input(data);
+            hmac.finalize()
+        },
+        salt,
+        iterations,
+        out,
+    )
+}
+
+// `prf` is the HMAC keyed with the password, the block size is its output size.
+fn pbkdf2<T, F>(prf: F, salt: &[u8], iterations: u32, out: &mut [u8]) -> Result<(), &'static str>
+where
+    T: AsRef<[u8]> + AsMut<[u8]> + Clone,
+    F: Fn(&[u8]) -> T,
+{
+    if iterations == 0 {
+        return Err("PBKDF2 needs at least one iteration");
+    }
+    let mut filled = 0;
+    let mut i = 1u32;
+    while filled < out.len() {
+        let mut first = salt.to_vec();
+        first.extend_from_slice(&i.to_be_bytes());
+        let mut u = prf(&first);
+        let mut block = u.clone();
+        for _ in 1..iterations {
+            u = prf(u.as_ref());
+            block.as_mut().iter_mut().zip(u.as_ref()).for_each(|(b, u)| *b ^= u);
+        }
+        let len = block.as_ref().len().min(out.len() - filled);
+        out[filled..filled + len].copy_from_slice(&block.as_ref()[..len]);
+        filled += len;
+        i += 1;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rustc_hex::{FromHex, ToHex};
+
+    fn hex(hex: &str) -> Vec<u8> {
+        hex.from_hex().unwrap()
+    }
+
+    // RFC 5869 appendix A.1-A.3
+    #[test]
+    fn test_hkdf_test_vectors() {
+        let vectors = [
+            (
+                "0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b",
+                "000102030405060708090a0b0c",
+                "f0f1f2f3f4f5f6f7f8f9",
+                "077709362c2e32df0ddc3f0dc47bba6390b6c73bb50f9c3122ec844ad7c2b3e5",
+                "3cb25f25faacd57a90434f64d0362f2a2d2d0a90cf1a5a4c5db02d56ecc4c5bf34007208d5b887185865",
+            ),
+            (
+                "000102030405060708090a0b0c0d0e0f101112131415161718191a1b1c1d1e1f202122232425262728292a2b2c2d2e2f303132333435363738393a3b3c3d3e3f404142434445464748494a4b4c4d4e4f",
+                "606162636465666768696a6b6c6d6e6f707172737475767778797a7b7c7d7e7f808182838485868788898a8b8c8d8e8f909192939495969798999a9b9c9d9e9fa0a1a2a3a4a5a6a7a8a9aaabacadaeaf",
+                "b0b1b2b3b4b5b6b7b8b9babbbcbdbebfc0c1c2c3c4c5c6c7c8c9cacbcccdcecfd0d1d2d3d4d5d6d7d8d9dadbdcdddedfe0e1e2e3e4e5e6e7e8e9eaebecedeeeff0f1f2f3f4f5f6f7f8f9fafbfcfdfeff",
+                "06a6b88c5853361a06104c9ceb35b45cef760014904671014a193f40c15fc244",
+                "b11e398dc80327a1c8e7f78c596a49344f012eda2d4efad8a050cc4c19afa97c59045a99cac7827271cb41c65e590e09da3275600c2f09b8367793a9aca3db71cc30c58179ec3e87c14c01d5c1f3434f1d87",
+            ),
+            (
+                "0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b",
+                "",
+                "",
+                "19ef24a32c717b167f33a91d6f648bdf96596776afdb6377ac434c1c293ccb04",
+                "8da4e775a563c18f715f802a063c5a31b8a11f5c5ee1879ec3454e5f3c738d2d9d201395faa4b61a96c8",
+            ),
+        ];
+        for (ikm, salt, info, prk, okm) in vectors.iter() {
+            let hkdf = Hkdf::extract(Some(&hex(salt)), &hex(ikm));
+            assert_eq!(hkdf.prk().to_hex::<String>(), *prk);
+            let mut out = vec![0u8; okm.len() / 2];
+            hkdf.expand(&hex(info), &mut out).unwrap();
+            assert_eq!(out.to_hex::<String>(), *okm);
+        }
+
+        // No salt is the same as a zeroed salt.
+        let ikm = hex(vectors[2].0);
+        assert_eq!(Hkdf::extract(None, &ikm).prk(), Hkdf::extract(Some(&[0u8; 32]), &ikm).prk());
+        assert!(Hkdf::from_prk(&[1u8; 32]).expand(b"", &mut [0u8; 255 * 32 + 1]).is_err());
+    }
+
+    // The RFC 6070 inputs, with the SHA256/SHA512 outputs instead of SHA1.
+    #[test]
+    fn test_pbkdf2_test_vectors() {
+        // (password, salt, iterations, sha256, sha512)
+        type Vector = (&'static [u8], &'static [u8], u32, &'static str, &'static str);
+        let vectors: [Vector; 5] = [
+            (
+                b"password",
+                b"salt",
+                1,
+                "120fb6cffcf8b32c43e7225256c4f837a86548c92ccc35480805987cb70be17b",
+                "867f70cf1ade02cff3752599a3a53dc4af34c7a669815ae5d513554e1c8cf252c02d470a285a0501bad999bfe943c08f050235d7d68b1da55e63f73b60a57fce",
+            ),
+            (
+                b"password",
+                b"salt",
+                2,
+                "ae4d0c95af6b46d32d0adff928f06dd02a303f8ef3c251dfd6e2d85a95474c43",
+                "e1d9c16aa681708a45f5c7c4e215ceb66e011a2e9f0040713f18aefdb866d53cf76cab2868a39b9f7840edce4fef5a82be67335c77a6068e04112754f27ccf4e",
+            ),
+            (
+                b"password",
+                b"salt",
+                4096,
+                "c5e478d59288c841aa530db6845c4c8d962893a001ce4e11a4963873aa98134a",
+                "d197b1b33db0143e018b12f3d1d1479e6cdebdcc97c5c0f87f6902e072f457b5143f30602641b3d55cd335988cb36b84376060ecd532e039b742a239434af2d5",
+            ),
+            (
+                b"passwordPASSWORDpassword",
+                b"saltSALTsaltSALTsaltSALTsaltSALTsalt",
+                4096,
+                "348c89dbcbd32b2f32d814b8116e84cf2b17347ebc1800181c4e2a1fb8dd53e1c635518c7dac47e9",
+                "8c0511f4c6e597c6ac6315d8f0362e225f3c501495ba23b868c005174dc4ee71115b59f9e60cd9532fa33e0f75aefe30225c583a186cd82bd4daea9724a3d3b8",
+            ),
+            (b"pass\0word", b"sa\0lt", 4096, "89b69d0516f829893c696226650a8687", "9d9e9c4cd21fe4be24d5b8244c759665"),
+        ];
+        for (password, salt, iterations, sha256, sha512) in vectors.iter() {
+            let mut out = vec![0u8; sha256.len() / 2];
+            pbkdf2_hmac_sha256(password, salt, *iterations, &mut out).unwrap();
+            assert_eq!(out.to_hex::<String>(), *sha256);
+
+            let mut out = vec![0u8; sha512.len() / 2];
+            pbkdf2_hmac_sha512(password, salt, *iterations, &mut out).unwrap();
+            assert_eq!(out.to_hex::<String>(), *sha512);
+        }
+        assert!(pbkdf2_hmac_sha256(b"password", b"salt", 0, &mut [0u8; 32]).is_err());
+        assert!(pbkdf2_hmac_sha512(b"password", b"salt", 0, &mut [0u8; 64]).is_err());
+    }
+}
@@ -0,0 +1,138 @@
+#![allow(clippy::unreadable_literal)]
+//! Keccak-256 with the original Keccak padding (as used by Ethereum), which differs from the standardized SHA3-256.
+
+const RATE: usize = 136;
+const DELIM: u8 = 0x01;
+
+#[derive(Clone, Debug)]
+pub struct Keccak256 {
+    state: [u64; 25],
+    curr: [u8; RATE],
+    pos: usize,
+}
+
+impl Keccak256 {
+    pub const fn new() -> Self {
+        Self { state: [0u64; 25], curr: [0u8; RATE], pos: 0 }
+    }
+
+    pub fn input(&mut self, data: &[u8]) {
+        for &byte in data {
+            self.curr[self.pos] = byte;
+            self.pos += 1;
+            if self.pos == RATE {
+                self.absorb();
+            }
+        }
+    }
+
+    pub fn finalize(mut self) -> [u8; 32] {
+        for byte in &mut self.curr[self.pos..] {
+            *byte = 0;
+        }
+        self.curr[self.pos] ^= DELIM;
+        self.curr[RATE - 1] ^= 0x80;
+        self.absorb();
+
+        let mut res = [0u8; 32];
+        for (chunk, lane) in res.chunks_mut(8).zip(self.state.iter()) {
+            chunk.copy_from_slice(&lane.to_le_bytes());
+        }
+        res
+    }
+
+    fn absorb(&mut self) {
+        for (lane, chunk) in self.state.iter_mut().zip(self.curr.chunks(8)) {
+            let mut bytes = [0u8; 8];
+            bytes.copy_from_slice(chunk);
+            *lane ^= u64::from_le_bytes(bytes);
+        }
+        keccak_f(&mut self.state);
+        self.pos = 0;
+    }
+}
+
+/// The Keccak-f\[1600\] permutation, lanes are indexed by `x + 5*y`.
+fn keccak_f(a: &mut [u64; 25]) {
+    for rc in RC.iter() {
+        // θ
+        let mut c = [0u64; 5];
+        for x in 0..5 {
+            c[x] = a[x] ^ a[x + 5] ^ a[x + 10] ^ a[x + 15] ^ a[x + 20];
+        }
+        for x in 0..5 {
+            let d = c[(x + 4) % 5] ^ c[(x + 1) % 5].rotate_left(1);
+            for y in 0..5 {
+                a[x + 5 * y] ^= d;
+            }
+        }
+        // ρ and π
+        let mut last = a[1];
+        for (&pi, &rho) in PI.iter().zip(RHO.iter()) {
+            let tmp = a[pi];
+            a[pi] = last.rotate_left(rho);
+            last = tmp;
+        }
+        // χ
+        for y in 0..5 {
+            let row = [a[5 * y], a[5 * y + 1], a[5 * y + 2], a[5 * y + 3], a[5 * y + 4]];
+            for x in 0..5 {
+                a[x + 5 * y] = row[x] ^ (!row[(x + 1) % 5] & row[(x + 2) % 5]);
+            }
+        }
+        // ι
+        a[0] ^= rc;
+    }
+}
+
+impl Default for Keccak256 {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+const RHO: [u32; 24] = [1, 3, 6, 10, 15, 21, 28, 36, 45, 55, 2, 14, 27, 41, 56, 8, 25, 43, 62, 18, 39, 61, 20, 44];
+
+const PI: [usize; 24] = [10, 7, 11, 17, 18, 3, 5, 16, 8, 21, 24, 4, 15, 23, 19, 13, 12, 2, 20, 14, 22, 9, 6, 1];
+
+#[rustfmt::skip]
+const RC: [u64; 24] = [
+    0x0000000000000001, 0x0000000000008082, 0x800000000000808a, 0x8000000080008000,
+    0x000000000000808b, 0x0000000080000001, 0x8000000080008081, 0x8000000000008009,
+    0x000000000000008a, 0x0000000000000088, 0x0000000080008009, 0x000000008000000a,
+    0x000000008000808b, 0x800000000000008b, 0x8000000000008089, 0x8000000000008003,
+    0x8000000000008002, 0x8000000000000080, 0x000000000000800a, 0x800000008000000a,
+    0x8000000080008081, 0x8000000000008080, 0x0000000080000001, 0x8000000080008008,
+];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rustc_hex::ToHex;
+
+    fn keccak256(data: &[u8]) -> String {
+        let mut hasher = Keccak256::new();
+        hasher.input(data);
+        hasher.finalize().to_hex()
+    }
+
+    #[test]
+    fn test_keccak256_test_vectors() {
+        assert_eq!(keccak256(b""), "c5d2460186f7233c927e7db2dcc703c0e500b653ca82273b7bfad8045d85a470");
+        assert_eq!(keccak256(b"abc"), "4e03657aea45a94fc7d47ba826c8d667c0d1e6e33a64a036ec44f58fa12d6c45");
+        assert_eq!(
+            keccak256(b"The quick brown fox jumps over the lazy dog"),
+            "4d741b6f1eb29cb2a9b9911c82f56fa8d73b04959d3d9d222895df6c0b28aa15"
+        );
+        assert_eq!(
+            keccak256(b"abcdbcdecdefdefgefghfghighijhijkijkljklmklmnlmnomnopnopq"),
+            "45d3b367a6904e6e8d502ee04999a7c27647f91fa845d456525fd352ae3d7371"
+        );
+        assert_eq!(
+            keccak256(
+                b"abcdefghbcdefghicdefghijdefghijkefghijklfghijklmghijklmnhijklmnoijklmnopjklmnopqklmnopqrlmnopqrsmnopqrstnopqrstu"
+            ),
+            "f519747ed599024f3882238e5ab43960132572b7345fbeb9a90769dafd21ad67"
+        );
+    }
+}
@@ -1,9 +1,16 @@
 pub mod hmac_sha2;
+pub mod kdf;
+mod keccak;
+mod newtypes;
+mod ripemd160;
 mod sha2;
 
-use sha2::Sha256;
+pub use keccak::Keccak256;
+pub use newtypes::{Hash160, Sha256Hash, Sha256dHash};
+pub use ripemd160::Ripemd160;
+pub use sha2::{Midstate, Sha256, Sha512};
 
-#[derive(Default)]
+#[derive(Default, Clone)]
 pub(crate) struct HashDigest {
     h: Sha256,
 }
@@ -13,6 +20,11 @@ impl HashDigest {
         HashDigest::default()
     }
 
+    /// A hasher prefixed with `SHA256(tag) || SHA256(tag)` as defined in BIP340.
+    pub fn tagged(tag: &[u8]) -> HashDigest {
+        HashDigest { h: TaggedHash::new(tag).h }
+    }
+
     pub fn input(&mut self, input: &[u8]) {
         self.h.input(input)
     }
@@ -22,6 +34,59 @@ impl HashDigest {
     }
 }
 
+/// A BIP340 tagged hash, `SHA256(SHA256(tag) || SHA256(tag) || msg)`.
+///
+/// The prefix is exactly one block, so the hasher is kept as a midstate which can be cloned
+/// or const-initialized for common tags, instead of hashing the prefix every time.
+#[derive(Clone)]
+pub struct TaggedHash {
+    h: Sha256,
+}
+
+impl TaggedHash {
+    /// `BIP0340/challenge`
+    pub const BIP340_CHALLENGE: TaggedHash = TaggedHash::from_midstate(Midstate {
+        state: [0x9cecba11, 0x23925381, 0x11679112, 0xd1627e0f, 0x97c87550, 0x003cc765, 0x90f61164, 0x33e9b66a],
+        bytes: 64,
+    });
+    /// `BIP0340/aux`
+    pub const BIP340_AUX: TaggedHash = TaggedHash::from_midstate(Midstate {
+        state: [0x24dd3219, 0x4eba7e70, 0xca0fabb9, 0x0fa3166d, 0x3afbe4b1, 0x4c44df97, 0x4aac2739, 0x249e850a],
+        bytes: 64,
+    });
+    /// `BIP0340/nonce`
+    pub const BIP340_NONCE: TaggedHash = TaggedHash::from_midstate(Midstate {
+        state: [0x46615b35, 0xf4bfbff7, 0x9f8dc671, 0x83627ab3, 0x60217180, 0x57358661, 0x21a29e54, 0x68b07b4c],
+        bytes: 64,
+    });
+
+    pub fn new(tag: &[u8]) -> TaggedHash {
+        let tag = tag.hash_digest();
+        let mut h = Sha256::new();
+        h.input(&tag);
+        h.input(&tag);
+        TaggedHash { h }
+    }
+
+    /// Starts from the midstate after the `SHA256(tag) || SHA256(tag)` prefix.
+    pub const fn from_midstate(midstate: Midstate) -> TaggedHash {
+        TaggedHash { h: Sha256::from_midstate(midstate) }
+    }
+
+    /// The midstate after the prefix, or `None` if data that isn't a multiple of 64 bytes was already hashed.
+    pub fn midstate(&self) -> Option<Midstate> {
+        self.h.midstate()
+    }
+
+    pub fn input(&mut self, input: &[u8]) {
+        self.h.input(input)
+    }
+
+    pub fn finalize(self) -> [u8; 32] {
+        self.h.finalize()
+    }
+}
+
 pub trait HashTrait<T> {
     fn hash_digest(&self) -> T
     where
@@ -35,3 +100,43 @@ impl HashTrait<[u8; 32]> for [u8] {
         hasher.result()
     }
 }
+
+/// `SHA256(SHA256(data))`, used by txids, block hashes and Base58Check checksums.
+pub fn sha256d(data: &[u8]) -> [u8; 32] {
+    let first = data.hash_digest();
+    first[..].hash_digest()
+}
+
+/// `RIPEMD160(SHA256(data))`, used by Bitcoin addresses and BIP32 fingerprints.
+pub fn hash160(data: &[u8]) -> [u8; 20] {
+    let mut hasher = Ripemd160::new();
+    let hash = data.hash_digest();
+    hasher.input(&hash);
+    hasher.finalize()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tagged_hash() {
+        let consts = [
+            (TaggedHash::BIP340_CHALLENGE, &b"BIP0340/challenge"[..]),
+            (TaggedHash::BIP340_AUX, b"BIP0340/aux"),
+            (TaggedHash::BIP340_NONCE, b"BIP0340/nonce"),
+        ];
+        for (hasher, tag) in consts.iter() {
+            assert_eq!(hasher.midstate(), TaggedHash::new(tag).midstate());
+
+            let mut manual = HashDigest::new();
+            let tag_hash = tag.hash_digest();
+            manual.input(&tag_hash);
+            manual.input(&tag_hash);
+            manual.input(b"Liberta!");
+            let mut hasher = hasher.clone();
+            hasher.input(b"Liberta!");
+            assert_eq!(hasher.finalize(), manual.result());
+        }
+    }
+}
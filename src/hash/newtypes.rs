@@ -0,0 +1,157 @@
+//! Strongly typed digests, so a txid can't be mixed up with a single SHA256 or a key hash.
+//!
+//! `Sha256dHash` is displayed in reversed byte order like Bitcoin txids and block hashes, the others are displayed as is.
+//! `Sha256Hash` and `Sha256dHash` can be passed as the 32 bytes message to `sign`/`verify` (with `to_hash: false`) through `AsRef<[u8]>`.
+//!
+//! The digests are produced by inherent `hash()` constructors instead of the `HashTrait` implementations the request asked for.
+//! `[u8]` already implements `HashTrait<[u8; 32]>`, and a second `HashTrait<_> for [u8]` impl would make every existing
+//! `data.hash_digest()` call ambiguous, breaking type inference for callers without annotations.
+//! This deviates from the request and still needs the requester's sign-off.
+
+use super::{hash160, sha256d, HashDigest};
+use std::{fmt, str::FromStr};
+
+macro_rules! hash_newtype {
+    ($name:ident, $len:expr, $reversed:expr, $doc:expr) => {
+        #[doc = $doc]
+        #[derive(Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+        pub struct $name(pub [u8; $len]);
+
+        impl $name {
+            pub const fn from_byte_array(bytes: [u8; $len]) -> Self {
+                $name(bytes)
+            }
+
+            pub const fn to_byte_array(self) -> [u8; $len] {
+                self.0
+            }
+        }
+
+        impl fmt::Display for $name {
+            fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                let mut bytes = self.0;
+                if $reversed {
+                    bytes.reverse();
+                }
+                for byte in bytes.iter() {
+                    write!(f, "{:02x}", byte)?;
+                }
+                Ok(())
+            }
+        }
+
+        impl fmt::Debug for $name {
+            fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                write!(f, "{}({})", stringify!($name), self)
+            }
+        }
+
+        /// Parses the hex in display order.
+        impl FromStr for $name {
+            type Err = &'static str;
+
+            fn from_str(s: &str) -> Result<Self, Self::Err> {
+                let mut bytes = [0u8; $len];
+                if s.len() != 2 * $len {
+                    return Err("Wrong hex length");
+                }
+                for (i, c) in s.bytes().enumerate() {
+                    let nibble = match c {
+                        b'0'..=b'9' => c - b'0',
+                        b'a'..=b'f' => c - b'a' + 10,
+                        b'A'..=b'F' => c - b'A' + 10,
+                        _ => return Err("Invalid hex character"),
+                    };
+                    bytes[i / 2] |= if i % 2 == 0 { nibble << 4 } else { nibble };
+                }
+                if $reversed {
+                    bytes.reverse();
+                }
+                Ok($name(bytes))
+            }
+        }
+
+        impl AsRef<[u8]> for $name {
+            fn as_ref(&self) -> &[u8] {
+                &self.0
+            }
+        }
+
+        impl From<[u8; $len]> for $name {
+            fn from(bytes: [u8; $len]) -> Self {
+                $name(bytes)
+            }
+        }
+
+        impl From<$name> for [u8; $len] {
+            fn from(hash: $name) -> Self {
+                hash.0
+            }
+        }
+    };
+}
+
+hash_newtype!(Sha256Hash, 32, false, "A single SHA256 digest.");
+hash_newtype!(Sha256dHash, 32, true, "A double SHA256 digest, as used by txids, block hashes and checksums.");
+hash_newtype!(Hash160, 20, false, "A `RIPEMD160(SHA256(x))` digest, as used by key and script hashes.");
+
+impl Sha256Hash {
+    pub fn hash(data: &[u8]) -> Self {
+        let mut hasher = HashDigest::new();
+        hasher.input(data);
+        Sha256Hash(hasher.result())
+    }
+}
+
+impl Sha256dHash {
+    pub fn hash(data: &[u8]) -> Self {
+        Sha256dHash(sha256d(data))
+    }
+}
+
+impl Hash160 {
+    pub fn hash(data: &[u8]) -> Self {
+        Hash160(hash160(data))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::PrivateKey;
+
+    #[test]
+    fn test_newtypes() {
+        let hash = Sha256Hash::hash(b"abc");
+        assert_eq!(hash.to_string(), "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad");
+        assert_eq!(hash, hash.to_string().parse().unwrap());
+
+        // The genesis block hash.
+        let header = rustc_hex::FromHex::from_hex::<Vec<u8>>("0100000000000000000000000000000000000000000000000000000000000000000000003ba3edfd7a7b12b27ac72c3e67768f617fc81bc3888a51323a9fb8aa4b1e5e4a29ab5f49ffff001d1dac2b7c").unwrap();
+        let block_hash = Sha256dHash::hash(&header);
+        assert_eq!(block_hash.to_string(), "000000000019d6689c085ae165831e934ff763ae46a2a6c172b3f1b60a8ce26f");
+        assert_eq!(block_hash.0[..4], [0x6f, 0xe2, 0x8c, 0x0a]);
+        assert_eq!(block_hash, "000000000019d6689c085ae165831e934ff763ae46a2a6c172b3f1b60a8ce26f".parse().unwrap());
+        assert_eq!(format!("{:?}", block_hash), "Sha256dHash(000000000019d6689c085ae165831e934ff763ae46a2a6c172b3f1b60a8ce26f)");
+
+        let pubkey = PrivateKey::new(1u8).generate_pubkey();
+        let key_hash = Hash160::hash(&pubkey.clone().compressed());
+        assert_eq!(key_hash.to_string(), "751e76e8199196d454941c45d1b3a323f1433bd6");
+        assert_eq!(key_hash.to_byte_array(), pubkey.hash160());
+
+        assert!("751e76e8199196d454941c45d1b3a323f1433bd".parse::<Hash160>().is_err());
+        assert!("751e76e8199196d454941c45d1b3a323f1433bdg".parse::<Hash160>().is_err());
+    }
+
+    #[test]
+    fn test_sign_digest() {
+        let key = PrivateKey::new(8764321234_u128);
+        let msg = b"Liberta!";
+        let digest = Sha256Hash::hash(msg);
+        assert_eq!(key.sign(digest.as_ref(), false), key.sign(msg, true));
+
+        let digest = Sha256dHash::hash(msg);
+        let sig = key.sign(digest.as_ref(), false);
+        assert!(key.generate_pubkey().verify(digest.as_ref(), sig, false));
+    }
+}
@@ -0,0 +1,179 @@
+#![allow(clippy::unreadable_literal, clippy::many_single_char_names)]
+//! RIPEMD-160 as specified in [The hash function RIPEMD-160](https://homes.esat.kuleuven.be/~bosselae/ripemd160.html).
+
+const BLOCK_SIZE: usize = 64;
+
+#[derive(Clone, Debug)]
+pub struct Ripemd160 {
+    hash: [u32; 5],
+    curr: [u8; BLOCK_SIZE],
+    pos: usize,
+    len: u64,
+}
+
+impl Ripemd160 {
+    pub const fn new() -> Self {
+        Self { hash: [0x67452301, 0xefcdab89, 0x98badcfe, 0x10325476, 0xc3d2e1f0], curr: [0u8; BLOCK_SIZE], pos: 0, len: 0 }
+    }
+
+    pub fn input(&mut self, data: &[u8]) {
+        for &byte in data {
+            self.curr[self.pos] = byte;
+            self.pos += 1;
+            if self.pos == BLOCK_SIZE {
+                self.process_current_block();
+            }
+        }
+        self.len += 8 * data.len() as u64;
+    }
+
+    pub fn finalize(mut self) -> [u8; 20] {
+        let len = self.len;
+        self.input(&[0x80]);
+        while self.pos != BLOCK_SIZE - 8 {
+            self.input(&[0]);
+        }
+        self.input(&len.to_le_bytes());
+        debug_assert_eq!(self.pos, 0);
+
+        let mut res = [0u8; 20];
+        for (chunk, word) in res.chunks_mut(4).zip(self.hash.iter()) {
+            chunk.copy_from_slice(&word.to_le_bytes());
+        }
+        res
+    }
+
+    fn process_current_block(&mut self) {
+        let mut block = [0u32; 16];
+        for (word, chunk) in block.iter_mut().zip(self.curr.chunks(4)) {
+            *word = u32::from_le_bytes([chunk[0], chunk[1], chunk[2], chunk[3]]);
+        }
+        self.process_block(block);
+        self.pos = 0;
+    }
+
+    #[allow(non_snake_case)]
+    pub fn process_block(&mut self, block: [u32; 16]) {
+        let H = &mut self.hash;
+        let (mut al, mut bl, mut cl, mut dl, mut el) = (H[0], H[1], H[2], H[3], H[4]);
+        let (mut ar, mut br, mut cr, mut dr, mut er) = (H[0], H[1], H[2], H[3], H[4]);
+
+        for j in 0..80 {
+            let round = j / 16;
+            let t = al
+                .wrapping_add(f(round, bl, cl, dl))
+                .wrapping_add(block[R_LEFT[j]])
+                .wrapping_add(K_LEFT[round])
+                .rotate_left(S_LEFT[j])
+                .wrapping_add(el);
+            al = el;
+            el = dl;
+            dl = cl.rotate_left(10);
+            cl = bl;
+            bl = t;
+
+            let t = ar
+                .wrapping_add(f(4 - round, br, cr, dr))
+                .wrapping_add(block[R_RIGHT[j]])
+                .wrapping_add(K_RIGHT[round])
+                .rotate_left(S_RIGHT[j])
+                .wrapping_add(er);
+            ar = er;
+            er = dr;
+            dr = cr.rotate_left(10);
+            cr = br;
+            br = t;
+        }
+
+        let t = H[1].wrapping_add(cl).wrapping_add(dr);
+        H[1] = H[2].wrapping_add(dl).wrapping_add(er);
+        H[2] = H[3].wrapping_add(el).wrapping_add(ar);
+        H[3] = H[4].wrapping_add(al).wrapping_add(br);
+        H[4] = H[0].wrapping_add(bl).wrapping_add(cr);
+        H[0] = t;
+    }
+}
+
+#[inline(always)]
+fn f(round: usize, x: u32, y: u32, z: u32) -> u32 {
+    match round {
+        0 => x ^ y ^ z,
+        1 => (x & y) | (!x & z),
+        2 => (x | !y) ^ z,
+        3 => (x & z) | (y & !z),
+        _ => x ^ (y | !z),
+    }
+}
+
+impl Default for Ripemd160 {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+const K_LEFT: [u32; 5] = [0x00000000, 0x5a827999, 0x6ed9eba1, 0x8f1bbcdc, 0xa953fd4e];
+const K_RIGHT: [u32; 5] = [0x50a28be6, 0x5c4dd124, 0x6d703ef3, 0x7a6d76e9, 0x00000000];
+
+#[rustfmt::skip]
+const R_LEFT: [usize; 80] = [
+    0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15,
+    7, 4, 13, 1, 10, 6, 15, 3, 12, 0, 9, 5, 2, 14, 11, 8,
+    3, 10, 14, 4, 9, 15, 8, 1, 2, 7, 0, 6, 13, 11, 5, 12,
+    1, 9, 11, 10, 0, 8, 12, 4, 13, 3, 7, 15, 14, 5, 6, 2,
+    4, 0, 5, 9, 7, 12, 2, 10, 14, 1, 3, 8, 11, 6, 15, 13,
+];
+
+#[rustfmt::skip]
+const R_RIGHT: [usize; 80] = [
+    5, 14, 7, 0, 9, 2, 11, 4, 13, 6, 15, 8, 1, 10, 3, 12,
+    6, 11, 3, 7, 0, 13, 5, 10, 14, 15, 8, 12, 4, 9, 1, 2,
+    15, 5, 1, 3, 7, 14, 6, 9, 11, 8, 12, 2, 10, 0, 4, 13,
+    8, 6, 4, 1, 3, 11, 15, 0, 5, 12, 2, 13, 9, 7, 10, 14,
+    12, 15, 10, 4, 1, 5, 8, 7, 6, 2, 13, 14, 0, 3, 9, 11,
+];
+
+#[rustfmt::skip]
+const S_LEFT: [u32; 80] = [
+    11, 14, 15, 12, 5, 8, 7, 9, 11, 13, 14, 15, 6, 7, 9, 8,
+    7, 6, 8, 13, 11, 9, 7, 15, 7, 12, 15, 9, 11, 7, 13, 12,
+    11, 13, 6, 7, 14, 9, 13, 15, 14, 8, 13, 6, 5, 12, 7, 5,
+    11, 12, 14, 15, 14, 15, 9, 8, 9, 14, 5, 6, 8, 6, 5, 12,
+    9, 15, 5, 11, 6, 8, 13, 12, 5, 12, 13, 14, 11, 8, 5, 6,
+];
+
+#[rustfmt::skip]
+const S_RIGHT: [u32; 80] = [
+    8, 9, 9, 11, 13, 15, 15, 5, 7, 7, 8, 11, 14, 14, 12, 6,
+    9, 13, 15, 7, 12, 8, 9, 11, 7, 7, 12, 7, 6, 15, 13, 11,
+    9, 7, 15, 11, 8, 6, 6, 14, 12, 13, 5, 14, 13, 13, 7, 5,
+    15, 5, 8, 11, 14, 14, 6, 14, 6, 9, 12, 9, 12, 5, 15, 8,
+    8, 5, 12, 9, 12, 5, 14, 6, 8, 13, 6, 5, 15, 13, 11, 11,
+];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rustc_hex::ToHex;
+
+    fn ripemd160(data: &[u8]) -> String {
+        let mut hasher = Ripemd160::new();
+        hasher.input(data);
+        hasher.finalize().to_hex()
+    }
+
+    #[test]
+    fn test_ripemd160_test_vectors() {
+        assert_eq!(ripemd160(b""), "9c1185a5c5e9fc54612808977ee8f548b2258d31");
+        assert_eq!(ripemd160(b"a"), "0bdc9d2d256b3ee9daae347be6f4dc835a467ffe");
+        assert_eq!(ripemd160(b"abc"), "8eb208f7e05d987a9b044a8e98c6b087f15a0bfc");
+        assert_eq!(ripemd160(b"message digest"), "5d0689ef49d2fae572b881b123a85ffa21595f36");
+        assert_eq!(ripemd160(b"abcdefghijklmnopqrstuvwxyz"), "f71c27109c692c1b56bbdceb5b9d2865b3708dbc");
+        assert_eq!(ripemd160(b"abcdbcdecdefdefgefghfghighijhijkijkljklmklmnlmnomnopnopq"), "12a053384a9c0c88e405a06c27dcf49ada62eb2b");
+        assert_eq!(
+            ripemd160(b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789"),
+            "b0e20b6e3116640286ed3a87a5713079b21f5189"
+        );
+        assert_eq!(ripemd160(&b"1234567890".repeat(8)), "9b752e45573d4b39f4dbd3323cab82bf63326bfb");
+        assert_eq!(ripemd160(&[b'a'; 1_000_000]), "52783243c1697bdbe16d37f97f68f08325dc1528");
+    }
+}
@@ -5,12 +5,20 @@ const U32_ALIGN: usize = mem::align_of::<u32>();
 const BLOCK_SIZE: usize = 64;
 const BLOCK_SIZE_BITS: u64 = BLOCK_SIZE as u64 * 8;
 
+#[derive(Clone)]
 pub struct Sha256 {
     hash: [u32; 8],
     curr: Vec64,
     len: u64,
 }
 
+/// The state of a [`Sha256`] on a block boundary, the state words and how many bytes were hashed so far.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Midstate {
+    pub state: [u32; 8],
+    pub bytes: u64,
+}
+
 impl Sha256 {
     #[allow(non_snake_case)]
     pub fn process_block(&mut self, block: [u32; 16]) {
@@ -53,6 +61,24 @@ impl Sha256 {
         }
     }
 
+    /// Resumes hashing from a midstate, `bytes` must be a multiple of the block size.
+    pub const fn from_midstate(midstate: Midstate) -> Self {
+        assert!(midstate.bytes % BLOCK_SIZE as u64 == 0, "A midstate must be on a block boundary");
+        Self { hash: midstate.state, curr: Vec64::empty(), len: midstate.bytes * 8 }
+    }
+
+    /// Exports the midstate, returns `None` if the input so far isn't a multiple of the block size.
+    pub fn midstate(&self) -> Option<Midstate> {
+        let mut this = self.clone();
+        if this.curr.is_full() {
+            this.process_current_block();
+        }
+        if !this.curr.is_empty() {
+            return None;
+        }
+        Some(Midstate { state: this.hash, bytes: this.len / 8 })
+    }
+
     pub fn input(&mut self, data: &[u8]) {
         // TODO: This can probably be optimized
         for &byte in data {
@@ -126,6 +152,7 @@ pub const fn majority(x: u32, y: u32, z: u32) -> u32 {
     (x & y) ^ (x & z) ^ (y & z)
 }
 
+#[derive(Clone)]
 struct Vec64 {
     data: [u8; 64],
     pos: u8,
@@ -219,6 +246,197 @@ impl Default for Sha256 {
     }
 }
 
+const BLOCK_SIZE_512: usize = 128;
+const BLOCK_SIZE_BITS_512: u128 = BLOCK_SIZE_512 as u128 * 8;
+
+#[derive(Clone)]
+pub struct Sha512 {
+    hash: [u64; 8],
+    curr: Vec128,
+    len: u128,
+}
+
+impl Sha512 {
+    #[allow(non_snake_case)]
+    pub fn process_block(&mut self, block: [u64; 16]) {
+        let mut W = [0u64; 80];
+        W[..16].copy_from_slice(&block);
+
+        for t in 16..80 {
+            W[t] = s_sigma1_512(W[t - 2]).wrapping_add(W[t - 7]).wrapping_add(s_sigma0_512(W[t - 15])).wrapping_add(W[t - 16]);
+        }
+        let H = &mut self.hash;
+        let (mut a, mut b, mut c, mut d, mut e, mut f, mut g, mut h) = (H[0], H[1], H[2], H[3], H[4], H[5], H[6], H[7]);
+
+        for t in 0..80 {
+            let T1 = h.wrapping_add(b_sigma1_512(e)).wrapping_add((e & f) ^ (!e & g)).wrapping_add(K512[t]).wrapping_add(W[t]);
+            let T2 = b_sigma0_512(a).wrapping_add((a & b) ^ (a & c) ^ (b & c));
+            h = g;
+            g = f;
+            f = e;
+            e = d.wrapping_add(T1);
+            d = c;
+            c = b;
+            b = a;
+            a = T1.wrapping_add(T2);
+        }
+        for (word, new) in H.iter_mut().zip(&[a, b, c, d, e, f, g, h]) {
+            *word = word.wrapping_add(*new);
+        }
+    }
+
+    pub const fn new() -> Self {
+        Self {
+            hash: [
+                0x6a09e667f3bcc908,
+                0xbb67ae8584caa73b,
+                0x3c6ef372fe94f82b,
+                0xa54ff53a5f1d36f1,
+                0x510e527fade682d1,
+                0x9b05688c2b3e6c1f,
+                0x1f83d9abfb41bd6b,
+                0x5be0cd19137e2179,
+            ],
+            curr: Vec128::empty(),
+            len: 0,
+        }
+    }
+
+    pub fn input(&mut self, data: &[u8]) {
+        for &byte in data {
+            if self.curr.is_full() {
+                self.process_current_block();
+            }
+            self.curr.push(byte);
+        }
+        self.len += 8 * data.len() as u128;
+    }
+
+    #[inline(always)]
+    fn finalize_internal(mut self) -> [u64; 8] {
+        let zeroes = [0u8; BLOCK_SIZE_512];
+
+        let len: u128 = self.len;
+        let last_block_len: u128 = BLOCK_SIZE_BITS_512 - 128;
+        let how_many_zeros: u128 = last_block_len.wrapping_sub(8).wrapping_sub(len) % BLOCK_SIZE_BITS_512;
+
+        self.input(&[0b10000000]);
+        if how_many_zeros != 0 {
+            self.input(&zeroes[..(how_many_zeros / 8) as usize]);
+        }
+        self.input(&len.to_be_bytes());
+        if self.curr.is_full() {
+            self.process_current_block();
+        }
+        debug_assert!(self.curr.is_empty());
+        self.hash
+    }
+
+    pub fn finalize(self) -> [u8; 64] {
+        let hash = self.finalize_internal();
+        let mut res = [0u8; 64];
+        for (chunk, word) in res.chunks_mut(8).zip(hash.iter()) {
+            chunk.copy_from_slice(&word.to_be_bytes());
+        }
+        res
+    }
+
+    pub fn process_current_block(&mut self) {
+        debug_assert!(self.curr.is_full());
+        self.process_block(self.curr.to_data());
+        self.curr.clear();
+    }
+}
+
+#[inline(always)]
+pub const fn b_sigma0_512(x: u64) -> u64 {
+    x.rotate_right(28) ^ x.rotate_right(34) ^ x.rotate_right(39)
+}
+#[inline(always)]
+pub const fn b_sigma1_512(x: u64) -> u64 {
+    x.rotate_right(14) ^ x.rotate_right(18) ^ x.rotate_right(41)
+}
+#[inline(always)]
+pub const fn s_sigma0_512(x: u64) -> u64 {
+    x.rotate_right(1) ^ x.rotate_right(8) ^ (x >> 7)
+}
+#[inline(always)]
+pub const fn s_sigma1_512(x: u64) -> u64 {
+    x.rotate_right(19) ^ x.rotate_right(61) ^ (x >> 6)
+}
+
+#[derive(Clone)]
+struct Vec128 {
+    data: [u8; 128],
+    pos: u8,
+}
+
+impl Vec128 {
+    const BUF_SIZE: u8 = 128;
+
+    #[inline]
+    pub fn push(&mut self, byte: u8) {
+        debug_assert!(!self.is_full());
+        self.data[self.pos as usize] = byte;
+        self.pos += 1;
+    }
+
+    pub fn to_data(&self) -> [u64; 16] {
+        let mut res = [0u64; 16];
+        for (word, chunk) in res.iter_mut().zip(self.data.chunks(8)) {
+            let mut bytes = [0u8; 8];
+            bytes.copy_from_slice(chunk);
+            *word = u64::from_be_bytes(bytes);
+        }
+        res
+    }
+
+    pub const fn empty() -> Self {
+        Self { data: [0u8; 128], pos: 0 }
+    }
+
+    pub fn clear(&mut self) {
+        self.pos = 0;
+    }
+
+    pub fn is_full(&self) -> bool {
+        self.pos == Self::BUF_SIZE
+    }
+    pub fn is_empty(&self) -> bool {
+        self.pos == 0
+    }
+}
+
+impl fmt::Debug for Vec128 {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Vec128").field("data", &(&self.data[..])).field("pos", &(self.pos)).finish()
+    }
+}
+
+#[rustfmt::skip]
+const K512: [u64; 80] = [
+    0x428a2f98d728ae22, 0x7137449123ef65cd, 0xb5c0fbcfec4d3b2f, 0xe9b5dba58189dbbc, 0x3956c25bf348b538, 0x59f111f1b605d019,
+    0x923f82a4af194f9b, 0xab1c5ed5da6d8118, 0xd807aa98a3030242, 0x12835b0145706fbe, 0x243185be4ee4b28c, 0x550c7dc3d5ffb4e2,
+    0x72be5d74f27b896f, 0x80deb1fe3b1696b1, 0x9bdc06a725c71235, 0xc19bf174cf692694, 0xe49b69c19ef14ad2, 0xefbe4786384f25e3,
+    0x0fc19dc68b8cd5b5, 0x240ca1cc77ac9c65, 0x2de92c6f592b0275, 0x4a7484aa6ea6e483, 0x5cb0a9dcbd41fbd4, 0x76f988da831153b5,
+    0x983e5152ee66dfab, 0xa831c66d2db43210, 0xb00327c898fb213f, 0xbf597fc7beef0ee4, 0xc6e00bf33da88fc2, 0xd5a79147930aa725,
+    0x06ca6351e003826f, 0x142929670a0e6e70, 0x27b70a8546d22ffc, 0x2e1b21385c26c926, 0x4d2c6dfc5ac42aed, 0x53380d139d95b3df,
+    0x650a73548baf63de, 0x766a0abb3c77b2a8, 0x81c2c92e47edaee6, 0x92722c851482353b, 0xa2bfe8a14cf10364, 0xa81a664bbc423001,
+    0xc24b8b70d0f89791, 0xc76c51a30654be30, 0xd192e819d6ef5218, 0xd69906245565a910, 0xf40e35855771202a, 0x106aa07032bbd1b8,
+    0x19a4c116b8d2d0c8, 0x1e376c085141ab53, 0x2748774cdf8eeb99, 0x34b0bcb5e19b48a8, 0x391c0cb3c5c95a63, 0x4ed8aa4ae3418acb,
+    0x5b9cca4f7763e373, 0x682e6ff3d6b2b8a3, 0x748f82ee5defb2fc, 0x78a5636f43172f60, 0x84c87814a1f0ab72, 0x8cc702081a6439ec,
+    0x90befffa23631e28, 0xa4506cebde82bde9, 0xbef9a3f7b2c67915, 0xc67178f2e372532b, 0xca273eceea26619c, 0xd186b8c721c0c207,
+    0xeada7dd6cde0eb1e, 0xf57d4f7fee6ed178, 0x06f067aa72176fba, 0x0a637dc5a2c898a6, 0x113f9804bef90dae, 0x1b710b35131c471b,
+    0x28db77f523047d84, 0x32caab7b40c72493, 0x3c9ebe0a15c9bebc, 0x431d67c49c100d4c, 0x4cc5d4becb3e42b6, 0x597f299cfc657e2a,
+    0x5fcb6fab3ad6faec, 0x6c44198c4a475817,
+];
+
+impl Default for Sha512 {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -253,12 +471,119 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_sha512_test_vectors() {
+        assert!(test_vec512(
+            b"abc",
+            [
+                0xddaf35a193617aba,
+                0xcc417349ae204131,
+                0x12e6fa4e89a97ea2,
+                0x0a9eeee64b55d39a,
+                0x2192992a274fc1a8,
+                0x36ba3c23a3feebbd,
+                0x454d4423643ce80e,
+                0x2a9ac94fa54ca49f
+            ]
+        ));
+        assert!(test_vec512(
+            b"",
+            [
+                0xcf83e1357eefb8bd,
+                0xf1542850d66d8007,
+                0xd620e4050b5715dc,
+                0x83f4a921d36ce9ce,
+                0x47d0d13c5d85f2b0,
+                0xff8318d2877eec2f,
+                0x63b931bd47417a81,
+                0xa538327af927da3e
+            ]
+        ));
+        assert!(test_vec512(
+            b"abcdbcdecdefdefgefghfghighijhijkijkljklmklmnlmnomnopnopq",
+            [
+                0x204a8fc6dda82f0a,
+                0x0ced7beb8e08a416,
+                0x57c16ef468b228a8,
+                0x279be331a703c335,
+                0x96fd15c13b1b07f9,
+                0xaa1d3bea57789ca0,
+                0x31ad85c7a71dd703,
+                0x54ec631238ca3445
+            ]
+        ));
+        assert!(test_vec512(
+            b"abcdefghbcdefghicdefghijdefghijkefghijklfghijklmghijklmnhijklmnoijklmnopjklmnopqklmnopqrlmnopqrsmnopqrstnopqrstu",
+            [
+                0x8e959b75dae313da,
+                0x8cf4f72814fc143f,
+                0x8f7779c6eb9f7fa1,
+                0x7299aeadb6889018,
+                0x501d289e4900f7e4,
+                0x331b99dec4b5433a,
+                0xc7d329eeb6dd2654,
+                0x5e96e55b874be909
+            ]
+        ));
+        assert!(test_vec512(
+            &[b'a'; 1_000_000],
+            [
+                0xe718483d0ce76964,
+                0x4e2e42c7bc15b463,
+                0x8e1f98b13b204428,
+                0x5632a803afa973eb,
+                0xde0ff244877ea60a,
+                0x4cb0432ce577c31b,
+                0xeb009c5c2c49aa2e,
+                0x4eadb217ad8cc09b
+            ]
+        ));
+
+        // Streaming in uneven chunks gives the same result.
+        let mut hash = Sha512::new();
+        for chunk in [b'a'; 1_000].chunks(7) {
+            hash.input(chunk);
+        }
+        let mut expected = Sha512::new();
+        expected.input(&[b'a'; 1_000]);
+        assert_eq!(&hash.finalize()[..], &expected.finalize()[..]);
+    }
+
+    #[test]
+    fn test_midstate() {
+        let data = [0x42u8; 150];
+        let mut hash = Sha256::new();
+        hash.input(&data[..100]);
+        assert_eq!(hash.midstate(), None);
+
+        let mut hash = Sha256::new();
+        assert_eq!(hash.midstate(), Some(Midstate { state: Sha256::new().hash, bytes: 0 }));
+        hash.input(&data[..128]);
+        let midstate = hash.midstate().unwrap();
+        assert_eq!(midstate.bytes, 128);
+
+        let mut resumed = Sha256::from_midstate(midstate);
+        resumed.input(&data[128..]);
+        hash.input(&data[128..]);
+        let mut full = Sha256::new();
+        full.input(&data);
+        let expected = full.finalize();
+        assert_eq!(resumed.finalize(), expected);
+        assert_eq!(hash.finalize(), expected);
+    }
+
     fn test_vec(input: &[u8], res: [u32; 8]) -> bool {
         let mut hash = Sha256::new();
         hash.input(input);
         let input = hash.finalize_internal();
         input == res
     }
+
+    fn test_vec512(input: &[u8], res: [u64; 8]) -> bool {
+        let mut hash = Sha512::new();
+        hash.input(input);
+        hash.finalize_internal() == res
+    }
 }
 
 #[cfg(all(test, feature = "nightly"))]
@@ -0,0 +1,346 @@
+//! Hashing to the curve, as specified in [RFC 9380](https://www.rfc-editor.org/rfc/rfc9380).
+//!
+//! Implements the `secp256k1_XMD:SHA-256_SSWU_RO_` ([`hash_to_curve`]) and `secp256k1_XMD:SHA-256_SSWU_NU_` ([`encode_to_curve`]) suites.
+//! secp256k1 has `A = 0` so the simplified SWU map is applied on a 3-isogenous curve `E'` and the result is mapped back.
+//! Nothing here is constant time.
+
+use crate::field::FieldElement;
+use crate::hash::HashDigest;
+use crate::point::Point;
+use crate::secp256k1::{get_context, PublicKey};
+use num_bigint::BigInt;
+
+/// The security parameter of the suites is 128 bits, so each field element is hashed from `L = ceil((256 + 128) / 8)` bytes.
+const L: usize = 48;
+
+// The curve E': y^2 = x^3 + A'x + B', and Z for the SSWU map.
+const ISO_A: &str = "3f8731abdd661adca08a5558f0f5d272e953d363cb6f0e5d405447c01a444533";
+const ISO_B: u32 = 1771;
+const Z: i32 = -11;
+
+// The 3-isogeny map coefficients (RFC 9380 appendix E.1), from the lowest degree.
+const X_NUM: [&str; 4] = [
+    "8e38e38e38e38e38e38e38e38e38e38e38e38e38e38e38e38e38e38daaaaa8c7",
+    "07d3d4c80bc321d5b9f315cea7fd44c5d595d2fc0bf63b92dfff1044f17c6581",
+    "534c328d23f234e6e2a413deca25caece4506144037c40314ecbd0b53d9dd262",
+    "8e38e38e38e38e38e38e38e38e38e38e38e38e38e38e38e38e38e38daaaaa88c",
+];
+const X_DEN: [&str; 3] = [
+    "d35771193d94918a9ca34ccbb7b640dd86cd409542f8487d9fe6b745781eb49b",
+    "edadc6f64383dc1df7c4b2d51b54225406d36b641f5e41bbc52a56612a8c6d14",
+    "0000000000000000000000000000000000000000000000000000000000000001",
+];
+const Y_NUM: [&str; 4] = [
+    "4bda12f684bda12f684bda12f684bda12f684bda12f684bda12f684b8e38e23c",
+    "c75e0c32d5cb7c0fa9d0a54b12a0a6d5647ab046d686da6fdffc90fc201d71a3",
+    "29a6194691f91a73715209ef6512e576722830a201be2018a765e85a9ecee931",
+    "2f684bda12f684bda12f684bda12f684bda12f684bda12f684bda12f38e38d84",
+];
+const Y_DEN: [&str; 4] = [
+    "fffffffffffffffffffffffffffffffffffffffffffffffffffffffefffff93b",
+    "7a06534bb8bdb49fd5e9e6632722c2989467c1bfc8e8d978dfb425d2685c2573",
+    "6484aa716545ca2cf3a70c3fa8fe337e0a3d21162f0d6299a7bf8192bfd2a76f",
+    "0000000000000000000000000000000000000000000000000000000000000001",
+];
+
+/// Hashes `msg` into a uniformly distributed point, the result is unrelated to any other point.
+/// (`secp256k1_XMD:SHA-256_SSWU_RO_`)
+pub fn hash_to_curve(msg: &[u8], dst: &[u8]) -> Result<PublicKey, &'static str> {
+    let u = hash_to_field(msg, dst, 2)?;
+    Ok(PublicKey::from(map_to_curve(&u[0]) + map_to_curve(&u[1])))
+}
+
+/// Encodes `msg` into a point, faster than [`hash_to_curve`] but the output isn't uniformly distributed.
+/// (`secp256k1_XMD:SHA-256_SSWU_NU_`)
+pub fn encode_to_curve(msg: &[u8], dst: &[u8]) -> Result<PublicKey, &'static str> {
+    let u = hash_to_field(msg, dst, 1)?;
+    Ok(PublicKey::from(map_to_curve(&u[0])))
+}
+
+/// Hashes `msg` into `count` field elements modulo p.
+pub fn hash_to_field(msg: &[u8], dst: &[u8], count: usize) -> Result<Vec<FieldElement>, &'static str> {
+    let p = &get_context().modulo;
+    let uniform_bytes = expand_message_xmd(msg, dst, count * L)?;
+    let res = uniform_bytes
+        .chunks(L)
+        .map(|tv| {
+            let mut e = FieldElement::from_serialize(tv, p.clone());
+            e.mod_num();
+            e
+        })
+        .collect();
+    Ok(res)
+}
+
+/// Expands `msg` into `len_in_bytes` uniform bytes using SHA256.
+pub fn expand_message_xmd(msg: &[u8], dst: &[u8], len_in_bytes: usize) -> Result<Vec<u8>, &'static str> {
+    const B_IN_BYTES: usize = 32;
+    const S_IN_BYTES: usize = 64;
+    let ell = (len_in_bytes + B_IN_BYTES - 1) / B_IN_BYTES;
+    if ell > 255 || len_in_bytes > 0xffff {
+        return Err("Requested too many bytes from expand_message_xmd");
+    }
+    let oversize_dst;
+    let dst = if dst.len() > 255 {
+        let mut hasher = HashDigest::new();
+        hasher.input(b"H2C-OVERSIZE-DST-");
+        hasher.input(dst);
+        oversize_dst = hasher.result();
+        &oversize_dst[..]
+    } else {
+        dst
+    };
+    let dst_len = [dst.len() as u8];
+
+    let mut hasher = HashDigest::new();
+    hasher.input(&[0u8; S_IN_BYTES]);
+    hasher.input(msg);
+    hasher.input(&(len_in_bytes as u16).to_be_bytes());
+    hasher.input(&[0]);
+    hasher.input(dst);
+    hasher.input(&dst_len);
+    let b_0 = hasher.result();
+
+    let mut res = Vec::with_capacity(ell * B_IN_BYTES);
+    let mut b_i = [0u8; B_IN_BYTES];
+    for i in 1..=ell {
+        let mut hasher = HashDigest::new();
+        // b_1 = H(b_0 || 1 || DST'), b_i = H((b_0 xor b_(i-1)) || i || DST')
+        let xored: Vec<_> = b_0.iter().zip(b_i.iter()).map(|(a, b)| a ^ b).collect();
+        hasher.input(&xored);
+        hasher.input(&[i as u8]);
+        hasher.input(dst);
+        hasher.input(&dst_len);
+        b_i = hasher.result();
+        res.extend_from_slice(&b_i);
+    }
+    res.truncate(len_in_bytes);
+    Ok(res)
+}
+
+/// Maps a field element to a point, using the simplified SWU map to `E'` followed by the 3-isogeny.
+pub fn map_to_curve(u: &FieldElement) -> Point {
+    let (x, y) = map_to_curve_simple_swu(u);
+    let (x, y) = iso_map(&x, &y);
+    let point = Point { x, y, group: get_context().generator().group };
+    debug_assert!(point.is_on_curve());
+    point
+}
+
+// RFC 9380 section 6.6.2, the straightforward (not constant time) version.
+fn map_to_curve_simple_swu(u: &FieldElement) -> (FieldElement, FieldElement) {
+    let p = &get_context().modulo;
+    let a = fe(ISO_A);
+    let b = FieldElement::new(ISO_B, p.clone());
+    let z = FieldElement::new(Z, p.clone());
+    let g = |x: &FieldElement| x.pow_u(3u8) + (a.clone() * x) + &b;
+
+    let u2 = u.pow_u(2u8);
+    let tv1 = (z.pow_u(2u8) * u2.pow_u(2u8)) + (z.clone() * &u2);
+    let x1 = if tv1.is_zero() {
+        b.clone() / (z.clone() * &a)
+    } else {
+        (&BigInt::from(0) - b.clone()) / &a * (FieldElement::new(1u8, p.clone()) + (FieldElement::new(1u8, p.clone()) / tv1))
+    };
+    let gx1 = g(&x1);
+    let (x, mut y) = if gx1.is_square() {
+        (x1, gx1)
+    } else {
+        let x2 = z * u2 * &x1;
+        let gx2 = g(&x2);
+        (x2, gx2)
+    };
+    y.sqrt();
+    if sgn0(u) != sgn0(&y) {
+        y = &BigInt::from(0) - y;
+    }
+    (x, y)
+}
+
+fn iso_map(x: &FieldElement, y: &FieldElement) -> (FieldElement, FieldElement) {
+    let poly = |coefficients: &[&str]| {
+        let mut res = FieldElement::new(0u8, get_context().modulo.clone());
+        for c in coefficients.iter().rev() {
+            res = res * x + fe(c);
+        }
+        res
+    };
+    let x_res = poly(&X_NUM) / poly(&X_DEN);
+    let y_res = y.clone() * poly(&Y_NUM) / poly(&Y_DEN);
+    (x_res, y_res)
+}
+
+fn sgn0(x: &FieldElement) -> bool {
+    !x.is_even()
+}
+
+fn fe(hex: &str) -> FieldElement {
+    FieldElement::new(BigInt::parse_bytes(hex.as_bytes(), 16).unwrap(), get_context().modulo.clone())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rustc_hex::{FromHex, ToHex};
+
+    fn hex(hex: &str) -> Vec<u8> {
+        hex.from_hex().unwrap()
+    }
+
+    const Q128: &str = "q128_qqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqq";
+
+    fn a512() -> String {
+        format!("a512_{}", "a".repeat(512))
+    }
+
+    // RFC 9380 appendix K.1
+    #[test]
+    fn test_expand_message_xmd() {
+        let long_dst = format!("QUUX-V01-CS02-with-expander-SHA256-128-long-DST-{}", "1".repeat(208));
+        // (DST, msg, len_in_bytes, uniform_bytes)
+        let vectors = [
+            ("QUUX-V01-CS02-with-expander-SHA256-128", "", 0x20, "68a985b87eb6b46952128911f2a4412bbc302a9d759667f87f7a21d803f07235"),
+            ("QUUX-V01-CS02-with-expander-SHA256-128", "abc", 0x20, "d8ccab23b5985ccea865c6c97b6e5b8350e794e603b4b97902f53a8a0d605615"),
+            ("QUUX-V01-CS02-with-expander-SHA256-128", "abcdef0123456789", 0x20, "eff31487c770a893cfb36f912fbfcbff40d5661771ca4b2cb4eafe524333f5c1"),
+            ("QUUX-V01-CS02-with-expander-SHA256-128", Q128, 0x20, "b23a1d2b4d97b2ef7785562a7e8bac7eed54ed6e97e29aa51bfe3f12ddad1ff9"),
+            ("QUUX-V01-CS02-with-expander-SHA256-128", &a512(), 0x20, "4623227bcc01293b8c130bf771da8c298dede7383243dc0993d2d94823958c4c"),
+            ("QUUX-V01-CS02-with-expander-SHA256-128", "", 0x80, "af84c27ccfd45d41914fdff5df25293e221afc53d8ad2ac06d5e3e29485dadbee0d121587713a3e0dd4d5e69e93eb7cd4f5df4cd103e188cf60cb02edc3edf18eda8576c412b18ffb658e3dd6ec849469b979d444cf7b26911a08e63cf31f9dcc541708d3491184472c2c29bb749d4286b004ceb5ee6b9a7fa5b646c993f0ced"),
+            ("QUUX-V01-CS02-with-expander-SHA256-128", "abc", 0x80, "abba86a6129e366fc877aab32fc4ffc70120d8996c88aee2fe4b32d6c7b6437a647e6c3163d40b76a73cf6a5674ef1d890f95b664ee0afa5359a5c4e07985635bbecbac65d747d3d2da7ec2b8221b17b0ca9dc8a1ac1c07ea6a1e60583e2cb00058e77b7b72a298425cd1b941ad4ec65e8afc50303a22c0f99b0509b4c895f40"),
+            ("QUUX-V01-CS02-with-expander-SHA256-128", &a512(), 0x80, "546aff5444b5b79aa6148bd81728704c32decb73a3ba76e9e75885cad9def1d06d6792f8a7d12794e90efed817d96920d728896a4510864370c207f99bd4a608ea121700ef01ed879745ee3e4ceef777eda6d9e5e38b90c86ea6fb0b36504ba4a45d22e86f6db5dd43d98a294bebb9125d5b794e9d2a81181066eb954966a487"),
+            (&long_dst, "", 0x20, "e8dc0c8b686b7ef2074086fbdd2f30e3f8bfbd3bdf177f73f04b97ce618a3ed3"),
+            (&long_dst, "abcdef0123456789", 0x20, "35387dcf22618f3728e6c686490f8b431f76550b0b2c61cbc1ce7001536f4521"),
+            (&long_dst, Q128, 0x80, "ed6e8c036df90111410431431a232d41a32c86e296c05d426e5f44e75b9a50d335b2412bc6c91e0a6dc131de09c43110d9180d0a70f0d6289cb4e43b05f7ee5e9b3f42a1fad0f31bac6a625b3b5c50e3a83316783b649e5ecc9d3b1d9471cb5024b7ccf40d41d1751a04ca0356548bc6e703fca02ab521b505e8e45600508d32"),
+        ];
+        for (dst, msg, len, expected) in vectors.iter() {
+            let res = expand_message_xmd(msg.as_bytes(), dst.as_bytes(), *len).unwrap();
+            assert_eq!(res.to_hex::<String>(), *expected);
+        }
+        assert!(expand_message_xmd(b"", b"DST", 255 * 32 + 1).is_err());
+    }
+
+    // RFC 9380 appendix J.8.1
+    #[test]
+    fn test_hash_to_curve() {
+        let dst = b"QUUX-V01-CS02-with-secp256k1_XMD:SHA-256_SSWU_RO_";
+        // (msg, P.x, P.y, u0, u1, Q0.x, Q0.y, Q1.x, Q1.y)
+        let vectors = [
+            (
+                "",
+                "c1cae290e291aee617ebaef1be6d73861479c48b841eaba9b7b5852ddfeb1346",
+                "64fa678e07ae116126f08b022a94af6de15985c996c3a91b64c406a960e51067",
+                "6b0f9910dd2ba71c78f2ee9f04d73b5f4c5f7fc773a701abea1e573cab002fb3",
+                "1ae6c212e08fe1a5937f6202f929a2cc8ef4ee5b9782db68b0d5799fd8f09e16",
+                "74519ef88b32b425a095e4ebcc84d81b64e9e2c2675340a720bb1a1857b99f1e",
+                "c174fa322ab7c192e11748beed45b508e9fdb1ce046dee9c2cd3a2a86b410936",
+                "44548adb1b399263ded3510554d28b4bead34b8cf9a37b4bd0bd2ba4db87ae63",
+                "96eb8e2faf05e368efe5957c6167001760233e6dd2487516b46ae725c4cce0c6",
+            ),
+            (
+                "abc",
+                "3377e01eab42db296b512293120c6cee72b6ecf9f9205760bd9ff11fb3cb2c4b",
+                "7f95890f33efebd1044d382a01b1bee0900fb6116f94688d487c6c7b9c8371f6",
+                "128aab5d3679a1f7601e3bdf94ced1f43e491f544767e18a4873f397b08a2b61",
+                "5897b65da3b595a813d0fdcc75c895dc531be76a03518b044daaa0f2e4689e00",
+                "07dd9432d426845fb19857d1b3a91722436604ccbbbadad8523b8fc38a5322d7",
+                "604588ef5138cffe3277bbd590b8550bcbe0e523bbaf1bed4014a467122eb33f",
+                "e9ef9794d15d4e77dde751e06c182782046b8dac05f8491eb88764fc65321f78",
+                "cb07ce53670d5314bf236ee2c871455c562dd76314aa41f012919fe8e7f717b3",
+            ),
+            (
+                "abcdef0123456789",
+                "bac54083f293f1fe08e4a70137260aa90783a5cb84d3f35848b324d0674b0e3a",
+                "4436476085d4c3c4508b60fcf4389c40176adce756b398bdee27bca19758d828",
+                "ea67a7c02f2cd5d8b87715c169d055a22520f74daeb080e6180958380e2f98b9",
+                "7434d0d1a500d38380d1f9615c021857ac8d546925f5f2355319d823a478da18",
+                "576d43ab0260275adf11af990d130a5752704f79478628761720808862544b5d",
+                "643c4a7fb68ae6cff55edd66b809087434bbaff0c07f3f9ec4d49bb3c16623c3",
+                "f89d6d261a5e00fe5cf45e827b507643e67c2a947a20fd9ad71039f8b0e29ff8",
+                "b33855e0cc34a9176ead91c6c3acb1aacb1ce936d563bc1cee1dcffc806caf57",
+            ),
+            (
+                Q128,
+                "e2167bc785333a37aa562f021f1e881defb853839babf52a7f72b102e41890e9",
+                "f2401dd95cc35867ffed4f367cd564763719fbc6a53e969fb8496a1e6685d873",
+                "eda89a5024fac0a8207a87e8cc4e85aa3bce10745d501a30deb87341b05bcdf5",
+                "dfe78cd116818fc2c16f3837fedbe2639fab012c407eac9dfe9245bf650ac51d",
+                "9c91513ccfe9520c9c645588dff5f9b4e92eaf6ad4ab6f1cd720d192eb58247a",
+                "c7371dcd0134412f221e386f8d68f49e7fa36f9037676e163d4a063fbf8a1fb8",
+                "10fee3284d7be6bd5912503b972fc52bf4761f47141a0015f1c6ae36848d869b",
+                "0b163d9b4bf21887364332be3eff3c870fa053cf508732900fc69a6eb0e1b672",
+            ),
+            (
+                &a512(),
+                "e3c8d35aaaf0b9b647e88a0a0a7ee5d5bed5ad38238152e4e6fd8c1f8cb7c998",
+                "8446eeb6181bf12f56a9d24e262221cc2f0c4725c7e3803024b5888ee5823aa6",
+                "8d862e7e7e23d7843fe16d811d46d7e6480127a6b78838c277bca17df6900e9f",
+                "68071d2530f040f081ba818d3c7188a94c900586761e9115efa47ae9bd847938",
+                "b32b0ab55977b936f1e93fdc68cec775e13245e161dbfe556bbb1f72799b4181",
+                "2f5317098360b722f132d7156a94822641b615c91f8663be69169870a12af9e8",
+                "148f98780f19388b9fa93e7dc567b5a673e5fca7079cd9cdafd71982ec4c5e12",
+                "3989645d83a433bc0c001f3dac29af861f33a6fd1e04f4b36873f5bff497298a",
+            ),
+        ];
+        for (msg, px, py, u0, u1, q0x, q0y, q1x, q1y) in vectors.iter() {
+            let u = hash_to_field(msg.as_bytes(), dst, 2).unwrap();
+            assert_eq!(&u[0].clone().serialize_num()[..], &hex(u0)[..]);
+            assert_eq!(&u[1].clone().serialize_num()[..], &hex(u1)[..]);
+
+            for (u, qx, qy) in &[(&u[0], q0x, q0y), (&u[1], q1x, q1y)] {
+                let q = map_to_curve(u);
+                assert_eq!(&q.x.serialize_num()[..], &hex(qx)[..]);
+                assert_eq!(&q.y.serialize_num()[..], &hex(qy)[..]);
+            }
+
+            let p = hash_to_curve(msg.as_bytes(), dst).unwrap().uncompressed();
+            assert_eq!(&p[1..33], &hex(px)[..]);
+            assert_eq!(&p[33..], &hex(py)[..]);
+        }
+    }
+
+    // RFC 9380 appendix J.8.2
+    #[test]
+    fn test_encode_to_curve() {
+        let dst = b"QUUX-V01-CS02-with-secp256k1_XMD:SHA-256_SSWU_NU_";
+        // (msg, P.x, P.y, u0)
+        let vectors = [
+            (
+                "",
+                "a4792346075feae77ac3b30026f99c1441b4ecf666ded19b7522cf65c4c55c5b",
+                "62c59e2a6aeed1b23be5883e833912b08ba06be7f57c0e9cdc663f31639ff3a7",
+                "0137fcd23bc3da962e8808f97474d097a6c8aa2881fceef4514173635872cf3b",
+            ),
+            (
+                "abc",
+                "3f3b5842033fff837d504bb4ce2a372bfeadbdbd84a1d2b678b6e1d7ee426b9d",
+                "902910d1fef15d8ae2006fc84f2a5a7bda0e0407dc913062c3a493c4f5d876a5",
+                "e03f894b4d7caf1a50d6aa45cac27412c8867a25489e32c5ddeb503229f63a2e",
+            ),
+            (
+                "abcdef0123456789",
+                "07644fa6281c694709f53bdd21bed94dab995671e4a8cd1904ec4aa50c59bfdf",
+                "c79f8d1dad79b6540426922f7fbc9579c3018dafeffcd4552b1626b506c21e7b",
+                "e7a6525ae7069ff43498f7f508b41c57f80563c1fe4283510b322446f32af41b",
+            ),
+            (
+                Q128,
+                "b734f05e9b9709ab631d960fa26d669c4aeaea64ae62004b9d34f483aa9acc33",
+                "03fc8a4a5a78632e2eb4d8460d69ff33c1d72574b79a35e402e801f2d0b1d6ee",
+                "d97cf3d176a2f26b9614a704d7d434739d194226a706c886c5c3c39806bc323c",
+            ),
+            (
+                &a512(),
+                "17d22b867658977b5002dbe8d0ee70a8cfddec3eec50fb93f36136070fd9fa6c",
+                "e9178ff02f4dab73480f8dd590328aea99856a7b6cc8e5a6cdf289ecc2a51718",
+                "a9ffbeee1d6e41ac33c248fb3364612ff591b502386c1bf6ac4aaf1ea51f8c3b",
+            ),
+        ];
+        for (msg, px, py, u0) in vectors.iter() {
+            let u = hash_to_field(msg.as_bytes(), dst, 1).unwrap();
+            assert_eq!(&u[0].clone().serialize_num()[..], &hex(u0)[..]);
+
+            let p = encode_to_curve(msg.as_bytes(), dst).unwrap().uncompressed();
+            assert_eq!(&p[1..33], &hex(px)[..]);
+            assert_eq!(&p[33..], &hex(py)[..]);
+        }
+    }
+}
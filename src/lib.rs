@@ -1,20 +1,42 @@
 #![cfg_attr(feature = "nightly", feature(test))]
 
+pub mod adaptor;
+pub mod address;
+pub mod base58;
+mod base64;
+pub mod bech32;
+pub mod bip32;
+pub mod bip322;
+pub mod bip39;
+mod chacha20poly1305;
+pub mod der;
+pub mod dleq;
+pub mod ecies;
+pub mod ellswift;
+pub mod ethereum;
 mod field;
+pub mod frost;
 mod hash;
+pub mod hash_to_curve;
 pub mod internal;
 mod jacobi;
+pub mod message;
+pub mod musig;
+mod network;
 mod point;
 mod secp256k1;
+pub mod vrf;
+pub mod wif;
 // mod u256;
 mod ffi;
 #[cfg(test)]
 mod test_vectors;
 
-pub use crate::secp256k1::{PrivateKey, PublicKey, SchnorrSignature, Signature};
+pub use crate::network::Network;
+pub use crate::secp256k1::{PrivateKey, PublicKey, RecoverableSignature, SchnorrSignature, Signature};
 pub use hash::*;
 
-pub use crate::ffi::{ecdsa::*, schnorr::*, *};
+pub use crate::ffi::{ecdh::*, ecdsa::*, schnorr::*, *};
 
 #[cfg(test)]
 mod tests {
@@ -0,0 +1,219 @@
+//! Bitcoin signed messages, with the [BIP137](https://github.com/bitcoin/bips/blob/master/bip-0137.mediawiki) header
+//! byte telling which kind of address signed.
+//!
+//! The signature is the Base64 of `header || r || s`, over `SHA256d(varstr("Bitcoin Signed Message:\n") || varstr(msg))`.
+
+use crate::address::{self, Address, Payload};
+use crate::base64;
+use crate::hash::{hash160, sha256d};
+use crate::network::Network;
+use crate::secp256k1::{PrivateKey, PublicKey, RecoverableSignature};
+use std::fmt;
+
+const MESSAGE_PREFIX: &[u8] = b"Bitcoin Signed Message:\n";
+
+/// The kind of address the signing key is used in, which selects the header byte.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AddressType {
+    P2pkhUncompressed,
+    P2pkhCompressed,
+    P2shP2wpkh,
+    P2wpkh,
+}
+
+impl AddressType {
+    fn header_base(self) -> u8 {
+        match self {
+            AddressType::P2pkhUncompressed => 27,
+            AddressType::P2pkhCompressed => 31,
+            AddressType::P2shP2wpkh => 35,
+            AddressType::P2wpkh => 39,
+        }
+    }
+
+    fn from_header(header: u8) -> Option<AddressType> {
+        match header {
+            27..=30 => Some(AddressType::P2pkhUncompressed),
+            31..=34 => Some(AddressType::P2pkhCompressed),
+            35..=38 => Some(AddressType::P2shP2wpkh),
+            39..=42 => Some(AddressType::P2wpkh),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Error {
+    InvalidBase64,
+    /// The decoded signature should be 65 bytes.
+    InvalidLength(usize),
+    /// The header byte should be between 27 and 42.
+    InvalidHeader(u8),
+    InvalidSignature,
+    Address(address::Error),
+    /// Only P2PKH, P2SH-P2WPKH and P2WPKH addresses can be verified.
+    UnsupportedAddress,
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Error::InvalidBase64 => write!(f, "Invalid Base64 signature"),
+            Error::InvalidLength(len) => write!(f, "A signature should be 65 bytes, got {}", len),
+            Error::InvalidHeader(header) => write!(f, "Invalid header byte {}", header),
+            Error::InvalidSignature => write!(f, "Invalid signature"),
+            Error::Address(e) => write!(f, "{}", e),
+            Error::UnsupportedAddress => write!(f, "Unsupported address type"),
+        }
+    }
+}
+
+impl From<address::Error> for Error {
+    fn from(e: address::Error) -> Self {
+        Error::Address(e)
+    }
+}
+
+/// Bitcoin's `CompactSize` length prefix.
+pub(crate) fn compact_size(n: u64) -> Vec<u8> {
+    match n {
+        0..=0xfc => vec![n as u8],
+        0xfd..=0xffff => [&[0xfd][..], &(n as u16).to_le_bytes()].concat(),
+        0x1_0000..=0xffff_ffff => [&[0xfe][..], &(n as u32).to_le_bytes()].concat(),
+        _ => [&[0xff][..], &n.to_le_bytes()].concat(),
+    }
+}
+
+/// The hash that is actually signed.
+pub fn message_hash(msg: &str) -> [u8; 32] {
+    let mut data = compact_size(MESSAGE_PREFIX.len() as u64);
+    data.extend_from_slice(MESSAGE_PREFIX);
+    data.extend(compact_size(msg.len() as u64));
+    data.extend_from_slice(msg.as_bytes());
+    sha256d(&data)
+}
+
+pub fn sign_message(key: &PrivateKey, msg: &str, address_type: AddressType) -> String {
+    let sig = key.sign_recoverable(&message_hash(msg), false);
+    let mut res = [0u8; 65];
+    res[0] = address_type.header_base() + sig.recovery_id();
+    res[1..].copy_from_slice(&sig.to_standard().serialize());
+    base64::encode(&res)
+}
+
+/// Recovers the public key from the signature and checks it hashes to `address`.
+///
+/// Like most wallets, the header only decides whether the key is compressed,
+/// so a segwit address signed with a compressed P2PKH header is still accepted.
+pub fn verify_message(address: &str, msg: &str, sig: &str) -> Result<bool, Error> {
+    let address: Address = address.parse()?;
+    let (pubkey, address_type) = recover_pubkey(msg, sig)?;
+    let is_compressed = address_type != AddressType::P2pkhUncompressed;
+
+    let expected = match &address.payload {
+        Payload::PubkeyHash(hash) if is_compressed => return Ok(pubkey.hash160() == *hash),
+        Payload::PubkeyHash(hash) => return Ok(hash160(&pubkey.uncompressed()) == *hash),
+        Payload::ScriptHash(_) => Address::p2sh_p2wpkh(&pubkey, address.network),
+        Payload::WitnessProgram { version: 0, program } if program.len() == 20 => Address::p2wpkh(&pubkey, address.network),
+        Payload::WitnessProgram { .. } => return Err(Error::UnsupportedAddress),
+    };
+    Ok(is_compressed && expected == address)
+}
+
+/// Recovers the public key that signed `msg`, and the address type from the header.
+pub fn recover_pubkey(msg: &str, sig: &str) -> Result<(PublicKey, AddressType), Error> {
+    let sig = base64::decode(sig).map_err(|_| Error::InvalidBase64)?;
+    if sig.len() != 65 {
+        return Err(Error::InvalidLength(sig.len()));
+    }
+    let address_type = AddressType::from_header(sig[0]).ok_or(Error::InvalidHeader(sig[0]))?;
+    let mut compact = [0u8; 64];
+    compact.copy_from_slice(&sig[1..]);
+    let sig = RecoverableSignature::from_compact(compact, sig[0] - address_type.header_base()).map_err(|_| Error::InvalidSignature)?;
+    let pubkey = PublicKey::recover(&message_hash(msg), &sig, false).map_err(|_| Error::InvalidSignature)?;
+    Ok((pubkey, address_type))
+}
+
+/// The address of `pubkey` for an address type.
+pub fn address_of(pubkey: &PublicKey, address_type: AddressType, network: Network) -> Address {
+    match address_type {
+        AddressType::P2pkhUncompressed => Address { network, payload: Payload::PubkeyHash(hash160(&pubkey.clone().uncompressed())) },
+        AddressType::P2pkhCompressed => Address::p2pkh(pubkey, network),
+        AddressType::P2shP2wpkh => Address::p2sh_p2wpkh(pubkey, network),
+        AddressType::P2wpkh => Address::p2wpkh(pubkey, network),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rustc_hex::ToHex;
+
+    // The P2PKH signature is from bitcoinjs-message, the segwit ones only change the header.
+    #[test]
+    fn test_sign_message() {
+        let (key, _, _) = PrivateKey::from_wif("L4rK1yDtCWekvXuE6oXD9jCYfFNV2cWRpVuPLBcCU2z8TrisoyY1").unwrap();
+        let msg = "This is an example of a signed message.";
+        let vectors = [
+            (
+                AddressType::P2pkhCompressed,
+                "1F3sAm6ZtwLAUnj7d38pGFxtP3RVEvtsbV",
+                "H9L5yLFjti0QTHhPyFrZCT1V/MMnBtXKmoiKDZ78NDBjERki6ZTQZdSMCtkgoNmp17By9ItJr8o7ChX0XxY91nk=",
+            ),
+            (
+                AddressType::P2shP2wpkh,
+                "3DnW8JGpPViEZdpqat8qky1zc26EKbXnmM",
+                "I9L5yLFjti0QTHhPyFrZCT1V/MMnBtXKmoiKDZ78NDBjERki6ZTQZdSMCtkgoNmp17By9ItJr8o7ChX0XxY91nk=",
+            ),
+            (
+                AddressType::P2wpkh,
+                "bc1qngw83fg8dz0k749cg7k3emc7v98wy0c74dlrkd",
+                "J9L5yLFjti0QTHhPyFrZCT1V/MMnBtXKmoiKDZ78NDBjERki6ZTQZdSMCtkgoNmp17By9ItJr8o7ChX0XxY91nk=",
+            ),
+        ];
+        let pubkey = key.generate_pubkey();
+        for (address_type, address, sig) in vectors.iter() {
+            assert_eq!(address_of(&pubkey, *address_type, Network::Mainnet).to_string(), *address);
+            assert_eq!(sign_message(&key, msg, *address_type), *sig);
+            assert_eq!(verify_message(address, msg, sig), Ok(true));
+            assert_eq!(verify_message(address, "This is an example of a signed message!", sig), Ok(false));
+        }
+        // A segwit address with a P2PKH header, as signed by Electrum.
+        assert_eq!(verify_message(vectors[2].1, msg, vectors[0].2), Ok(true));
+        // The wrong address.
+        assert_eq!(
+            verify_message(vectors[0].1, msg, &sign_message(&PrivateKey::new(1u8), msg, AddressType::P2pkhCompressed)),
+            Ok(false)
+        );
+    }
+
+    #[test]
+    fn test_uncompressed() {
+        let key = PrivateKey::new(1u8);
+        let address = address_of(&key.generate_pubkey(), AddressType::P2pkhUncompressed, Network::Mainnet).to_string();
+        assert_eq!(address, "1EHNa6Q4Jz2uvNExL497mE43ikXhwF6kZm");
+        let sig = sign_message(&key, "Hello", AddressType::P2pkhUncompressed);
+        assert_eq!(verify_message(&address, "Hello", &sig), Ok(true));
+        // The compressed key hashes to another address.
+        assert_eq!(verify_message("1BgGZ9tcN4rm9KBzDn7KprQz87SZ26SAMH", "Hello", &sig), Ok(false));
+        assert_eq!(verify_message(&address, "Hello", &sign_message(&key, "Hello", AddressType::P2pkhCompressed)), Ok(false));
+    }
+
+    #[test]
+    fn test_invalid_signatures() {
+        let address = "1F3sAm6ZtwLAUnj7d38pGFxtP3RVEvtsbV";
+        assert_eq!(verify_message(address, "", "H9L5yLFjti0QTHhPyFrZCT1V"), Err(Error::InvalidLength(18)));
+        assert_eq!(verify_message(address, "", "H9L5yLFjti0QTHhPyFrZCT1V*"), Err(Error::InvalidBase64));
+        let sig = base64::encode(&[43u8; 65]);
+        assert_eq!(verify_message(address, "", &sig), Err(Error::InvalidHeader(43)));
+        let sig = base64::encode(&[31u8; 65]);
+        assert!(verify_message("bc1p5cyxnuxmeuwuvkwfem96lqzszd02n6xdcjrs20cac6yqjjwudpxqkedrcr", "", &sig).is_err());
+        assert!(matches!(verify_message("1F3sAm6ZtwLAUnj7d38pGFxtP3RVEvtsbW", "", &sig), Err(Error::Address(_))));
+
+        assert_eq!(message_hash("").to_hex::<String>(), sha256d(b"\x18Bitcoin Signed Message:\n\x00").to_hex::<String>());
+        assert_eq!(compact_size(0xfc), [0xfc]);
+        assert_eq!(compact_size(0xfd), [0xfd, 0xfd, 0x00]);
+        assert_eq!(compact_size(0x1_0000), [0xfe, 0x00, 0x00, 0x01, 0x00]);
+        assert_eq!(compact_size(0x1_0000_0000), [0xff, 0, 0, 0, 0, 1, 0, 0, 0]);
+    }
+}
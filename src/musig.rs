@@ -0,0 +1,803 @@
+//! MuSig2 n-of-n Schnorr multi-signatures, following [BIP327](https://github.com/bitcoin/bips/blob/master/bip-0327.mediawiki).
+//!
+//! The aggregated signature is a regular BIP340 signature over the (possibly tweaked) aggregated x-only key,
+//! and can be checked with [`PublicKey::verify_bip340`].
+
+use crate::field::FieldElement;
+use crate::hash::HashDigest;
+use crate::point::Point;
+use crate::secp256k1::{get_context, get_e_bip340, negate_scalar, scalar_mod_order, PrivateKey, PublicKey, SchnorrSignature};
+use num_bigint::BigInt;
+use std::fmt;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Error {
+    /// The aggregated public key is the point at infinity.
+    InvalidAggregateKey,
+    /// The tweak is not a valid scalar or results in the point at infinity.
+    InvalidTweak,
+    /// A public nonce is not a pair of valid compressed points.
+    InvalidPubNonce,
+    /// An aggregated nonce is not a pair of valid compressed points (or zeroes).
+    InvalidAggNonce,
+    /// The secret nonce is zeroed, out of range or belongs to a different key.
+    InvalidSecNonce,
+    InvalidSecretKey,
+    InvalidPartialSignature,
+    /// The signer's public key is not part of the aggregated keys.
+    PubkeyNotInList,
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let s = match self {
+            Error::InvalidAggregateKey => "The aggregated public key is infinity",
+            Error::InvalidTweak => "Invalid tweak",
+            Error::InvalidPubNonce => "Invalid public nonce",
+            Error::InvalidAggNonce => "Invalid aggregated nonce",
+            Error::InvalidSecNonce => "Invalid secret nonce",
+            Error::InvalidSecretKey => "Invalid secret key",
+            Error::InvalidPartialSignature => "Invalid partial signature",
+            Error::PubkeyNotInList => "The public key isn't part of the aggregated keys",
+        };
+        write!(f, "{}", s)
+    }
+}
+
+/// Sorts the public keys lexicographically by their compressed encoding (`KeySort` in BIP327).
+pub fn key_sort(pubkeys: &mut [PublicKey]) {
+    pubkeys.sort_by_cached_key(|pk| pk.clone().compressed());
+}
+
+/// The aggregated public key together with the tweaks applied to it.
+#[derive(Clone, Debug)]
+pub struct KeyAggCache {
+    pubkeys: Vec<[u8; 33]>,
+    pk_hash: [u8; 32],
+    second_key: [u8; 33],
+    q: Point,
+    gacc: FieldElement,
+    tacc: FieldElement,
+}
+
+impl KeyAggCache {
+    pub fn new(pubkeys: &[PublicKey]) -> Result<KeyAggCache, Error> {
+        let secp = get_context();
+        let serialized: Vec<[u8; 33]> = pubkeys.iter().map(|pk| pk.clone().compressed()).collect();
+        let pk_hash = hash_keys(&serialized);
+        let second_key = serialized.iter().find(|pk| **pk != serialized[0]).copied().unwrap_or([0u8; 33]);
+
+        let mut q = secp.generator().gen_zero();
+        for (pk, ser) in pubkeys.iter().zip(&serialized) {
+            let a = key_agg_coeff_internal(&pk_hash, ser, &second_key);
+            q += &(&a.num * &pk.point);
+        }
+        if q.is_on_infinity() {
+            return Err(Error::InvalidAggregateKey);
+        }
+        Ok(KeyAggCache {
+            pubkeys: serialized,
+            pk_hash,
+            second_key,
+            q,
+            gacc: FieldElement::new(1u8, secp.order.clone()),
+            tacc: FieldElement::new(0u8, secp.order.clone()),
+        })
+    }
+
+    /// The x-only aggregated public key, as used in BIP340 and Taproot outputs.
+    pub fn agg_pk(&self) -> [u8; 32] {
+        self.q.x.clone().serialize_num()
+    }
+
+    /// The aggregated public key including the parity of y, useful for plain (BIP32) tweaking.
+    pub fn agg_pubkey(&self) -> PublicKey {
+        PublicKey::from(self.q.clone())
+    }
+
+    /// Adds `tweak * G` to the aggregated key and returns the result.
+    pub fn pubkey_tweak_add(&mut self, tweak: &[u8; 32]) -> Result<PublicKey, Error> {
+        self.apply_tweak(tweak, false)
+    }
+
+    /// Adds `tweak * G` to the x-only aggregated key (i.e. after negating it if y is odd), as done in Taproot.
+    pub fn pubkey_xonly_tweak_add(&mut self, tweak: &[u8; 32]) -> Result<PublicKey, Error> {
+        self.apply_tweak(tweak, true)
+    }
+
+    fn apply_tweak(&mut self, tweak: &[u8; 32], is_xonly: bool) -> Result<PublicKey, Error> {
+        let secp = get_context();
+        let t = FieldElement::from_serialize(tweak, secp.order.clone());
+        if t.num >= secp.order {
+            return Err(Error::InvalidTweak);
+        }
+        let (mut q, gacc, tacc) = if is_xonly && !self.q.y.is_even() {
+            (-self.q.clone(), negate_scalar(self.gacc.clone()), negate_scalar(self.tacc.clone()))
+        } else {
+            (self.q.clone(), self.gacc.clone(), self.tacc.clone())
+        };
+        q += &(&t.num * secp.generator());
+        if q.is_on_infinity() {
+            return Err(Error::InvalidTweak);
+        }
+        self.q = q;
+        self.gacc = gacc;
+        self.tacc = t + tacc;
+        Ok(self.agg_pubkey())
+    }
+
+    fn key_agg_coeff(&self, pk: &[u8; 33]) -> Result<FieldElement, Error> {
+        if !self.pubkeys.contains(pk) {
+            return Err(Error::PubkeyNotInList);
+        }
+        Ok(key_agg_coeff_internal(&self.pk_hash, pk, &self.second_key))
+    }
+
+    // g = 1 if Q has an even y, otherwise -1.
+    fn parity_factor(&self) -> FieldElement {
+        let one = FieldElement::new(1u8, get_context().order.clone());
+        if self.q.y.is_even() {
+            one
+        } else {
+            negate_scalar(one)
+        }
+    }
+}
+
+fn hash_keys(pubkeys: &[[u8; 33]]) -> [u8; 32] {
+    let mut hasher = HashDigest::tagged(b"KeyAgg list");
+    for pk in pubkeys {
+        hasher.input(pk);
+    }
+    hasher.result()
+}
+
+fn key_agg_coeff_internal(pk_hash: &[u8; 32], pk: &[u8; 33], second_key: &[u8; 33]) -> FieldElement {
+    let order = &get_context().order;
+    if pk == second_key {
+        return FieldElement::new(1u8, order.clone());
+    }
+    let mut hasher = HashDigest::tagged(b"KeyAgg coefficient");
+    hasher.input(pk_hash);
+    hasher.input(pk);
+    scalar_mod_order(&hasher.result())
+}
+
+/// The secret part of a nonce pair: `k1 || k2 || pk`.
+///
+/// This *must* never be used for more than one signature, that's why [`Session::partial_sign`] consumes it.
+pub struct SecNonce([u8; 97]);
+
+impl SecNonce {
+    pub fn serialize(&self) -> [u8; 97] {
+        self.0
+    }
+
+    pub fn parse(ser: [u8; 97]) -> SecNonce {
+        SecNonce(ser)
+    }
+}
+
+/// The public part of a nonce pair, sent to the other signers.
+#[derive(Clone, Debug, PartialEq)]
+pub struct PubNonce {
+    r1: Point,
+    r2: Point,
+}
+
+impl PubNonce {
+    pub fn serialize(&self) -> [u8; 66] {
+        let mut res = [0u8; 66];
+        res[..33].copy_from_slice(&PublicKey::from(self.r1.clone()).compressed());
+        res[33..].copy_from_slice(&PublicKey::from(self.r2.clone()).compressed());
+        res
+    }
+
+    pub fn parse(ser: &[u8]) -> Result<PubNonce, Error> {
+        if ser.len() != 66 {
+            return Err(Error::InvalidPubNonce);
+        }
+        let r1 = PublicKey::from_compressed(&ser[..33]).map_err(|_| Error::InvalidPubNonce)?;
+        let r2 = PublicKey::from_compressed(&ser[33..]).map_err(|_| Error::InvalidPubNonce)?;
+        Ok(PubNonce { r1: r1.point, r2: r2.point })
+    }
+}
+
+/// The sum of all the signers' public nonces. Unlike [`PubNonce`] each half may be the point at infinity.
+#[derive(Clone, Debug, PartialEq)]
+pub struct AggNonce {
+    r1: Point,
+    r2: Point,
+}
+
+impl AggNonce {
+    pub fn serialize(&self) -> [u8; 66] {
+        let mut res = [0u8; 66];
+        res[..33].copy_from_slice(&serialize_point_ext(&self.r1));
+        res[33..].copy_from_slice(&serialize_point_ext(&self.r2));
+        res
+    }
+
+    pub fn parse(ser: &[u8]) -> Result<AggNonce, Error> {
+        if ser.len() != 66 {
+            return Err(Error::InvalidAggNonce);
+        }
+        let r1 = parse_point_ext(&ser[..33]).ok_or(Error::InvalidAggNonce)?;
+        let r2 = parse_point_ext(&ser[33..]).ok_or(Error::InvalidAggNonce)?;
+        Ok(AggNonce { r1, r2 })
+    }
+}
+
+fn serialize_point_ext(point: &Point) -> [u8; 33] {
+    if point.is_on_infinity() {
+        [0u8; 33]
+    } else {
+        PublicKey::from(point.clone()).compressed()
+    }
+}
+
+fn parse_point_ext(ser: &[u8]) -> Option<Point> {
+    if ser.iter().all(|b| *b == 0) {
+        Some(get_context().generator().gen_zero())
+    } else {
+        PublicKey::from_compressed(ser).ok().map(|pk| pk.point)
+    }
+}
+
+/// Generates a fresh nonce pair (`NonceGen` in BIP327).
+///
+/// `rand` *must* be 32 bytes of fresh randomness, every other argument is optional hardening against bad randomness.
+pub fn nonce_gen(
+    rand: [u8; 32],
+    sk: Option<&PrivateKey>,
+    pk: &PublicKey,
+    agg_pk: Option<&[u8; 32]>,
+    msg: Option<&[u8]>,
+    extra_in: Option<&[u8]>,
+) -> Result<(SecNonce, PubNonce), Error> {
+    let secp = get_context();
+    let rand = match sk {
+        Some(sk) => {
+            let mut aux = HashDigest::tagged(b"MuSig/aux");
+            aux.input(&rand);
+            let mut aux = aux.result();
+            aux.iter_mut().zip(sk.serialize().iter()).for_each(|(a, b)| *a ^= b);
+            aux
+        }
+        None => rand,
+    };
+    let pk = pk.clone().compressed();
+
+    let mut data = Vec::with_capacity(256);
+    data.extend_from_slice(&rand);
+    push_prefixed(&mut data, 1, &pk);
+    push_prefixed(&mut data, 1, agg_pk.map(|a| &a[..]).unwrap_or(&[]));
+    match msg {
+        Some(msg) => {
+            data.push(1);
+            push_prefixed(&mut data, 8, msg);
+        }
+        None => data.push(0),
+    }
+    push_prefixed(&mut data, 4, extra_in.unwrap_or(&[]));
+
+    let mut secnonce = [0u8; 97];
+    let mut points = Vec::with_capacity(2);
+    for i in 0..2u8 {
+        let mut hasher = HashDigest::tagged(b"MuSig/nonce");
+        hasher.input(&data);
+        hasher.input(&[i]);
+        let k = scalar_mod_order(&hasher.result());
+        if k.is_zero() {
+            return Err(Error::InvalidSecNonce);
+        }
+        points.push(&k.num * secp.generator());
+        let i = i as usize;
+        secnonce[i * 32..(i + 1) * 32].copy_from_slice(&k.serialize_num());
+    }
+    secnonce[64..].copy_from_slice(&pk);
+    let r2 = points.pop().unwrap();
+    let r1 = points.pop().unwrap();
+
+    Ok((SecNonce(secnonce), PubNonce { r1, r2 }))
+}
+
+fn push_prefixed(data: &mut Vec<u8>, len_size: usize, input: &[u8]) {
+    let len = (input.len() as u64).to_be_bytes();
+    data.extend_from_slice(&len[8 - len_size..]);
+    data.extend_from_slice(input);
+}
+
+/// Sums the public nonces of all the signers (`NonceAgg` in BIP327).
+pub fn nonce_agg(pubnonces: &[PubNonce]) -> AggNonce {
+    let zero = get_context().generator().gen_zero();
+    let mut agg = AggNonce { r1: zero.clone(), r2: zero };
+    for nonce in pubnonces {
+        agg.r1 += &nonce.r1;
+        agg.r2 += &nonce.r2;
+    }
+    agg
+}
+
+/// A 32 bytes partial signature produced by a single signer.
+#[derive(Clone, Debug, PartialEq)]
+pub struct PartialSignature(FieldElement);
+
+impl PartialSignature {
+    pub fn serialize(&self) -> [u8; 32] {
+        self.0.clone().serialize_num()
+    }
+
+    pub fn parse(ser: &[u8; 32]) -> Result<PartialSignature, Error> {
+        let order = &get_context().order;
+        let s = FieldElement::from_serialize(ser, order.clone());
+        if &s.num >= order {
+            return Err(Error::InvalidPartialSignature);
+        }
+        Ok(PartialSignature(s))
+    }
+}
+
+/// The values shared by all signers once the aggregated nonce and the message are known.
+#[derive(Clone, Debug)]
+pub struct Session {
+    b: FieldElement,
+    r: Point,
+    e: FieldElement,
+}
+
+impl Session {
+    #[allow(non_snake_case)]
+    pub fn new(aggnonce: &AggNonce, msg: &[u8], cache: &KeyAggCache) -> Session {
+        let G = get_context().generator();
+        let agg_pk = cache.agg_pk();
+
+        let mut b = HashDigest::tagged(b"MuSig/noncecoef");
+        b.input(&aggnonce.serialize());
+        b.input(&agg_pk);
+        b.input(msg);
+        let b = scalar_mod_order(&b.result());
+
+        let mut r = aggnonce.r1.clone();
+        r += &(&b.num * &aggnonce.r2);
+        if r.is_on_infinity() {
+            r = G;
+        }
+        let e = get_e_bip340(&r.x.clone().serialize_num(), &agg_pk, msg);
+        Session { b, r, e }
+    }
+
+    /// Produces this signer's partial signature, consuming the secret nonce so it can't be reused.
+    pub fn partial_sign(&self, secnonce: SecNonce, sk: &PrivateKey, cache: &KeyAggCache) -> Result<PartialSignature, Error> {
+        let secp = get_context();
+        let order = &secp.order;
+        let SecNonce(secnonce) = secnonce;
+        let mut k1 = FieldElement::from_serialize(&secnonce[..32], order.clone());
+        let mut k2 = FieldElement::from_serialize(&secnonce[32..64], order.clone());
+        if k1.is_zero() || k2.is_zero() || &k1.num >= order || &k2.num >= order {
+            return Err(Error::InvalidSecNonce);
+        }
+        if !self.r.y.is_even() {
+            k1 = negate_scalar(k1);
+            k2 = negate_scalar(k2);
+        }
+        if sk.scalar <= BigInt::from(0u8) || &sk.scalar >= order {
+            return Err(Error::InvalidSecretKey);
+        }
+        let pk = sk.generate_pubkey().compressed();
+        if secnonce[64..] != pk[..] {
+            return Err(Error::InvalidSecNonce);
+        }
+        let a = cache.key_agg_coeff(&pk)?;
+        let d = cache.parity_factor() * &cache.gacc * &sk.scalar;
+
+        let s = k1 + (k2 * &self.b.num) + (self.e.clone() * &a * &d);
+        Ok(PartialSignature(s))
+    }
+
+    /// Checks a single signer's partial signature against their public nonce and public key.
+    #[allow(non_snake_case)]
+    pub fn partial_verify(&self, psig: &PartialSignature, pubnonce: &PubNonce, pk: &PublicKey, cache: &KeyAggCache) -> bool {
+        let G = get_context().generator();
+        let a = match cache.key_agg_coeff(&pk.clone().compressed()) {
+            Ok(a) => a,
+            Err(_) => return false,
+        };
+        let mut Re = pubnonce.r1.clone();
+        Re += &(&self.b.num * &pubnonce.r2);
+        if !self.r.y.is_even() {
+            Re = -Re;
+        }
+        let g = cache.parity_factor() * &cache.gacc;
+        let challenge = self.e.clone() * &a * &g;
+
+        let lhs = &psig.0.num * G;
+        let rhs = Re + (challenge.num * &pk.point);
+        lhs == rhs
+    }
+
+    /// Sums the partial signatures into a BIP340 signature for the aggregated key (`PartialSigAgg` in BIP327).
+    pub fn partial_sig_agg(&self, psigs: &[PartialSignature], cache: &KeyAggCache) -> SchnorrSignature {
+        let mut s = self.e.clone() * cache.parity_factor() * &cache.tacc;
+        for psig in psigs {
+            s = s + &psig.0;
+        }
+        SchnorrSignature::new(&self.r.x.clone().serialize_num(), &s.serialize_num())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rustc_hex::FromHex;
+
+    fn hex(hex: &str) -> Vec<u8> {
+        hex.from_hex().unwrap()
+    }
+
+    fn hex32(hex_str: &str) -> [u8; 32] {
+        let mut res = [0u8; 32];
+        res.copy_from_slice(&hex(hex_str));
+        res
+    }
+
+    fn pubkey(hex_str: &str) -> Result<PublicKey, &'static str> {
+        PublicKey::from_compressed(&hex(hex_str))
+    }
+
+    fn pubkeys(all: &[&str], indices: &[usize]) -> Vec<PublicKey> {
+        indices.iter().map(|&i| pubkey(all[i]).unwrap()).collect()
+    }
+
+    fn pubnonces(all: &[&str], indices: &[usize]) -> Vec<PubNonce> {
+        indices.iter().map(|&i| PubNonce::parse(&hex(all[i])).unwrap()).collect()
+    }
+
+    fn secnonce(hex_str: &str) -> SecNonce {
+        let mut res = [0u8; 97];
+        res.copy_from_slice(&hex(hex_str));
+        SecNonce::parse(res)
+    }
+
+    type Tweaks<'a> = &'a [(&'a str, bool)];
+
+    fn apply_tweaks(cache: &mut KeyAggCache, tweaks: Tweaks) -> Result<(), Error> {
+        for (tweak, is_xonly) in tweaks {
+            if *is_xonly {
+                cache.pubkey_xonly_tweak_add(&hex32(tweak))?;
+            } else {
+                cache.pubkey_tweak_add(&hex32(tweak))?;
+            }
+        }
+        Ok(())
+    }
+
+    const PUBKEYS: [&str; 4] = [
+        "03935F972DA013F80AE011890FA89B67A27B7BE6CCB24D3274D18B2D4067F261A9",
+        "02F9308A019258C31049344F85F89D5229B531C845836F99B08601F113BCE036F9",
+        "02DFF1D77F2A671C5F36183726DB2341BE58FEAE1DA2DECED843240F7B502BA661",
+        "020000000000000000000000000000000000000000000000000000000000000007",
+    ];
+    const SK: &str = "7FB9E0E687ADA1EEBF7ECFE2F21E73EBDB51A7D450948DFE8D76D7F2D1007671";
+    const SECNONCES: [&str; 2] = [
+        "508B81A611F100A6B2B6B29656590898AF488BCF2E1F55CF22E5CFB84421FE61FA27FD49B1D50085B481285E1CA205D55C82CC1B31FF5CD54A489829355901F703935F972DA013F80AE011890FA89B67A27B7BE6CCB24D3274D18B2D4067F261A9",
+        "0000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000003935F972DA013F80AE011890FA89B67A27B7BE6CCB24D3274D18B2D4067F261A9",
+    ];
+    const PUBNONCES: [&str; 5] = [
+        "0337C87821AFD50A8644D820A8F3E02E499C931865C2360FB43D0A0D20DAFE07EA0287BF891D2A6DEAEBADC909352AA9405D1428C15F4B75F04DAE642A95C2548480",
+        "0279BE667EF9DCBBAC55A06295CE870B07029BFCDB2DCE28D959F2815B16F817980279BE667EF9DCBBAC55A06295CE870B07029BFCDB2DCE28D959F2815B16F81798",
+        "032DE2662628C90B03F5E720284EB52FF7D71F4284F627B68A853D78C78E1FFE9303E4C5524E83FFE1493B9077CF1CA6BEB2090C93D930321071AD40B2F44E599046",
+        "0237C87821AFD50A8644D820A8F3E02E499C931865C2360FB43D0A0D20DAFE07EA0387BF891D2A6DEAEBADC909352AA9405D1428C15F4B75F04DAE642A95C2548480",
+        "0200000000000000000000000000000000000000000000000000000000000000090287BF891D2A6DEAEBADC909352AA9405D1428C15F4B75F04DAE642A95C2548480",
+    ];
+    const AGGNONCES: [&str; 5] = [
+        "028465FCF0BBDBCF443AABCCE533D42B4B5A10966AC09A49655E8C42DAAB8FCD61037496A3CC86926D452CAFCFD55D25972CA1675D549310DE296BFF42F72EEEA8C9",
+        "000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000",
+        "048465FCF0BBDBCF443AABCCE533D42B4B5A10966AC09A49655E8C42DAAB8FCD61037496A3CC86926D452CAFCFD55D25972CA1675D549310DE296BFF42F72EEEA8C9",
+        "028465FCF0BBDBCF443AABCCE533D42B4B5A10966AC09A49655E8C42DAAB8FCD61020000000000000000000000000000000000000000000000000000000000000009",
+        "028465FCF0BBDBCF443AABCCE533D42B4B5A10966AC09A49655E8C42DAAB8FCD6102FFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFEFFFFFC30",
+    ];
+    const MSG: &str = "F95466D086770E689964664219266FE5ED215C92AE20BAB5C9D79ADDDDF3C0CF";
+
+    #[test]
+    fn test_key_agg_vectors() {
+        let keys = [
+            "02F9308A019258C31049344F85F89D5229B531C845836F99B08601F113BCE036F9",
+            "03DFF1D77F2A671C5F36183726DB2341BE58FEAE1DA2DECED843240F7B502BA659",
+            "023590A94E768F8E1815C2F24B4D80A8E3149316C3518CE7B7AD338368D038CA66",
+            "020000000000000000000000000000000000000000000000000000000000000005",
+            "02FFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFEFFFFFC30",
+            "04F9308A019258C31049344F85F89D5229B531C845836F99B08601F113BCE036F9",
+            "03935F972DA013F80AE011890FA89B67A27B7BE6CCB24D3274D18B2D4067F261A9",
+        ];
+        let tweaks = [
+            "FFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFEBAAEDCE6AF48A03BBFD25E8CD0364141",
+            "252E4BD67410A76CDF933D30EAA1608214037F1B105A013ECCD3C5C184A6110B",
+        ];
+        let valid: [(&[usize], &str); 4] = [
+            (&[0, 1, 2], "90539EEDE565F5D054F32CC0C220126889ED1E5D193BAF15AEF344FE59D4610C"),
+            (&[2, 1, 0], "6204DE8B083426DC6EAF9502D27024D53FC826BF7D2012148A0575435DF54B2B"),
+            (&[0, 0, 0], "B436E3BAD62B8CD409969A224731C193D051162D8C5AE8B109306127DA3AA935"),
+            (&[0, 0, 1, 1], "69BC22BFA5D106306E48A20679DE1D7389386124D07571D0D872686028C26A3E"),
+        ];
+        for (indices, expected) in &valid {
+            let cache = KeyAggCache::new(&pubkeys(&keys, indices)).unwrap();
+            assert_eq!(cache.agg_pk(), hex32(expected));
+        }
+
+        // Invalid public keys: not on the curve, x exceeds the field size and a wrong prefix.
+        for &i in &[3, 4, 5] {
+            assert!(pubkey(keys[i]).is_err());
+        }
+
+        // Tweak is out of range.
+        let mut cache = KeyAggCache::new(&pubkeys(&keys, &[0, 1])).unwrap();
+        assert_eq!(cache.pubkey_xonly_tweak_add(&hex32(tweaks[0])), Err(Error::InvalidTweak));
+
+        // Intermediate tweaking result is the point at infinity.
+        let mut cache = KeyAggCache::new(&pubkeys(&keys, &[6])).unwrap();
+        assert_eq!(cache.pubkey_tweak_add(&hex32(tweaks[1])), Err(Error::InvalidTweak));
+    }
+
+    #[test]
+    fn test_key_sort() {
+        let mut keys = pubkeys(&PUBKEYS, &[0, 1, 2]);
+        key_sort(&mut keys);
+        assert_eq!(keys, pubkeys(&PUBKEYS, &[2, 1, 0]));
+    }
+
+    #[test]
+    fn test_nonce_gen_vectors() {
+        let pk = pubkey("024D4B6CD1361032CA9BD2AEB9D900AA4D45D9EAD80AC9423374C451A7254D0766").unwrap();
+        let sk = PrivateKey::from_serialized(&[0x02; 32]);
+        let (secnonce, pubnonce) =
+            nonce_gen([0x0F; 32], Some(&sk), &pk, Some(&[0x07; 32]), Some(&[0x01; 32]), Some(&[0x08; 32])).unwrap();
+        assert_eq!(
+            &secnonce.serialize()[..],
+            &hex("B114E502BEAA4E301DD08A50264172C84E41650E6CB726B410C0694D59EFFB6495B5CAF28D045B973D63E3C99A44B807BDE375FD6CB39E46DC4A511708D0E9D2024D4B6CD1361032CA9BD2AEB9D900AA4D45D9EAD80AC9423374C451A7254D0766")[..]
+        );
+        assert_eq!(
+            &pubnonce.serialize()[..],
+            &hex("02F7BE7089E8376EB355272368766B17E88E7DB72047D05E56AA881EA52B3B35DF02C29C8046FDD0DED4C7E55869137200FBDBFE2EB654267B6D7013602CAED3115A")[..]
+        );
+
+        let pk = pubkey("02F9308A019258C31049344F85F89D5229B531C845836F99B08601F113BCE036F9").unwrap();
+        let (secnonce, pubnonce) = nonce_gen([0x0F; 32], None, &pk, None, None, None).unwrap();
+        assert_eq!(
+            &secnonce.serialize()[..],
+            &hex("89BDD787D0284E5E4D5FC572E49E316BAB7E21E3B1830DE37DFE80156FA41A6D0B17AE8D024C53679699A6FD7944D9C4A366B514BAF43088E0708B1023DD289702F9308A019258C31049344F85F89D5229B531C845836F99B08601F113BCE036F9")[..]
+        );
+        assert_eq!(
+            &pubnonce.serialize()[..],
+            &hex("02C96E7CB1E8AA5DAC64D872947914198F607D90ECDE5200DE52978AD5DED63C000299EC5117C2D29EDEE8A2092587C3909BE694D5CFF0667D6C02EA4059F7CD9786")[..]
+        );
+    }
+
+    #[test]
+    fn test_nonce_agg_vectors() {
+        let pnonces = [
+            "020151C80F435648DF67A22B749CD798CE54E0321D034B92B709B567D60A42E66603BA47FBC1834437B3212E89A84D8425E7BF12E0245D98262268EBDCB385D50641",
+            "03FF406FFD8ADB9CD29877E4985014F66A59F6CD01C0E88CAA8E5F3166B1F676A60248C264CDD57D3C24D79990B0F865674EB62A0F9018277A95011B41BFC193B833",
+            "020151C80F435648DF67A22B749CD798CE54E0321D034B92B709B567D60A42E6660279BE667EF9DCBBAC55A06295CE870B07029BFCDB2DCE28D959F2815B16F81798",
+            "03FF406FFD8ADB9CD29877E4985014F66A59F6CD01C0E88CAA8E5F3166B1F676A60379BE667EF9DCBBAC55A06295CE870B07029BFCDB2DCE28D959F2815B16F81798",
+            "04FF406FFD8ADB9CD29877E4985014F66A59F6CD01C0E88CAA8E5F3166B1F676A60248C264CDD57D3C24D79990B0F865674EB62A0F9018277A95011B41BFC193B833",
+            "03FF406FFD8ADB9CD29877E4985014F66A59F6CD01C0E88CAA8E5F3166B1F676A60248C264CDD57D3C24D79990B0F865674EB62A0F9018277A95011B41BFC193B831",
+            "03FF406FFD8ADB9CD29877E4985014F66A59F6CD01C0E88CAA8E5F3166B1F676A602FFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFEFFFFFC30",
+        ];
+        let valid: [(&[usize], &str); 2] = [
+            (&[0, 1], "035FE1873B4F2967F52FEA4A06AD5A8ECCBE9D0FD73068012C894E2E87CCB5804B024725377345BDE0E9C33AF3C43C0A29A9249F2F2956FA8CFEB55C8573D0262DC8"),
+            // The second halves sum up to infinity.
+            (&[2, 3], "035FE1873B4F2967F52FEA4A06AD5A8ECCBE9D0FD73068012C894E2E87CCB5804B000000000000000000000000000000000000000000000000000000000000000000"),
+        ];
+        for (indices, expected) in &valid {
+            let aggnonce = nonce_agg(&pubnonces(&pnonces, indices));
+            assert_eq!(&aggnonce.serialize()[..], &hex(expected)[..]);
+            assert_eq!(AggNonce::parse(&hex(expected)), Ok(aggnonce));
+        }
+        for &i in &[4, 5, 6] {
+            assert_eq!(PubNonce::parse(&hex(pnonces[i])), Err(Error::InvalidPubNonce));
+        }
+    }
+
+    #[test]
+    fn test_sign_verify_vectors() {
+        let sk = PrivateKey::from_serialized(&hex(SK));
+        let msg = hex(MSG);
+        let valid: [(&[usize], usize, usize, &str); 4] = [
+            (&[0, 1, 2], 0, 0, "012ABBCB52B3016AC03AD82395A1A415C48B93DEF78718E62A7A90052FE224FB"),
+            (&[1, 0, 2], 0, 1, "9FF2F7AAA856150CC8819254218D3ADEEB0535269051897724F9DB3789513A52"),
+            (&[1, 2, 0], 0, 2, "FA23C359F6FAC4E7796BB93BC9F0532A95468C539BA20FF86D7C76ED92227900"),
+            // The aggregated nonce is infinity, so R = G.
+            (&[0, 1], 1, 0, "AE386064B26105404798F75DE2EB9AF5EDA5387B064B83D049CB7C5E08879531"),
+        ];
+        for (keys, aggnonce, signer, expected) in &valid {
+            let keys = pubkeys(&PUBKEYS, keys);
+            let cache = KeyAggCache::new(&keys).unwrap();
+            let session = Session::new(&AggNonce::parse(&hex(AGGNONCES[*aggnonce])).unwrap(), &msg, &cache);
+            let psig = session.partial_sign(secnonce(SECNONCES[0]), &sk, &cache).unwrap();
+            assert_eq!(psig.serialize(), hex32(expected));
+
+            let pubnonce = PubNonce::parse(&hex(PUBNONCES[0])).unwrap();
+            assert!(session.partial_verify(&psig, &pubnonce, &keys[*signer], &cache));
+        }
+
+        // The signer's key isn't in the list.
+        let cache = KeyAggCache::new(&pubkeys(&PUBKEYS, &[1, 2])).unwrap();
+        let session = Session::new(&AggNonce::parse(&hex(AGGNONCES[0])).unwrap(), &msg, &cache);
+        assert_eq!(session.partial_sign(secnonce(SECNONCES[0]), &sk, &cache), Err(Error::PubkeyNotInList));
+        // Invalid public key and invalid aggregated nonces.
+        assert!(pubkey(PUBKEYS[3]).is_err());
+        for aggnonce in &AGGNONCES[2..] {
+            assert_eq!(AggNonce::parse(&hex(aggnonce)), Err(Error::InvalidAggNonce));
+        }
+        // A zeroed secret nonce, as left behind after signing.
+        let cache = KeyAggCache::new(&pubkeys(&PUBKEYS, &[0, 1, 2])).unwrap();
+        let session = Session::new(&AggNonce::parse(&hex(AGGNONCES[0])).unwrap(), &msg, &cache);
+        assert_eq!(session.partial_sign(secnonce(SECNONCES[1]), &sk, &cache), Err(Error::InvalidSecNonce));
+
+        let verify_fail: [(&str, usize); 2] = [
+            // Wrong signature, which is equal to n - the valid one.
+            ("FED54434AD4CFE953FC527DC6A5E5BE8F6234907B7C187559557CE87A0541C46", 0),
+            // Wrong signer.
+            ("012ABBCB52B3016AC03AD82395A1A415C48B93DEF78718E62A7A90052FE224FB", 1),
+        ];
+        let keys = pubkeys(&PUBKEYS, &[0, 1, 2]);
+        let nonces = pubnonces(&PUBNONCES, &[0, 1, 2]);
+        let cache = KeyAggCache::new(&keys).unwrap();
+        let session = Session::new(&nonce_agg(&nonces), &msg, &cache);
+        for (psig, signer) in &verify_fail {
+            let psig = PartialSignature::parse(&hex32(psig)).unwrap();
+            assert!(!session.partial_verify(&psig, &nonces[*signer], &keys[*signer], &cache));
+        }
+        // Signature exceeds the group size.
+        assert_eq!(
+            PartialSignature::parse(&hex32("FFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFEBAAEDCE6AF48A03BBFD25E8CD0364141")),
+            Err(Error::InvalidPartialSignature)
+        );
+        // Invalid public nonce.
+        assert_eq!(PubNonce::parse(&hex(PUBNONCES[4])), Err(Error::InvalidPubNonce));
+    }
+
+    #[test]
+    fn test_tweak_vectors() {
+        let sk = PrivateKey::from_serialized(&hex(SK));
+        let msg = hex(MSG);
+        let aggnonce = AggNonce::parse(&hex(AGGNONCES[0])).unwrap();
+        let tweaks = [
+            "E8F791FF9225A2AF0102AFFF4A9A723D9612A682A25EBE79802B263CDFCD83BB",
+            "AE2EA797CC0FE72AC5B97B97F3C6957D7E4199A167A58EB08BCAFFDA70AC0455",
+            "F52ECBC565B3D8BEA2DFD5B75A4F457E54369809322E4120831626F290FA87E0",
+            "1969AD73CC177FA0B4FCED6DF1F7BF9907E665FDE9BA196A74FED0A3CF5AEF9D",
+            "FFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFEBAAEDCE6AF48A03BBFD25E8CD0364141",
+        ];
+        let valid: [(Tweaks, &str); 5] = [
+            (&[(tweaks[0], true)], "E28A5C66E61E178C2BA19DB77B6CF9F7E2F0F56C17918CD13135E60CC848FE91"),
+            (&[(tweaks[0], false)], "38B0767798252F21BF5702C48028B095428320F73A4B14DB1E25DE58543D2D2D"),
+            (&[(tweaks[0], false), (tweaks[1], true)], "408A0A21C4A0F5DACAF9646AD6EB6FECD7F7A11F03ED1F48DFFF2185BC2C2408"),
+            (
+                &[(tweaks[0], false), (tweaks[1], false), (tweaks[2], true), (tweaks[3], true)],
+                "45ABD206E61E3DF2EC9E264A6FEC8292141A633C28586388235541F9ADE75435",
+            ),
+            (
+                &[(tweaks[0], true), (tweaks[1], false), (tweaks[2], true), (tweaks[3], false)],
+                "B255FDCAC27B40C7CE7848E2D3B7BF5EA0ED756DA81565AC804CCCA3E1D5D239",
+            ),
+        ];
+        let keys = [
+            "03935F972DA013F80AE011890FA89B67A27B7BE6CCB24D3274D18B2D4067F261A9",
+            "02F9308A019258C31049344F85F89D5229B531C845836F99B08601F113BCE036F9",
+            "02DFF1D77F2A671C5F36183726DB2341BE58FEAE1DA2DECED843240F7B502BA659",
+        ];
+        let keys = pubkeys(&keys, &[1, 2, 0]);
+        let pubnonce = PubNonce::parse(&hex(PUBNONCES[0])).unwrap();
+        for (tweaks, expected) in &valid {
+            let mut cache = KeyAggCache::new(&keys).unwrap();
+            apply_tweaks(&mut cache, tweaks).unwrap();
+            let session = Session::new(&aggnonce, &msg, &cache);
+            let psig = session.partial_sign(secnonce(SECNONCES[0]), &sk, &cache).unwrap();
+            assert_eq!(psig.serialize(), hex32(expected));
+            assert!(session.partial_verify(&psig, &pubnonce, &keys[2], &cache));
+        }
+
+        let mut cache = KeyAggCache::new(&keys).unwrap();
+        assert_eq!(apply_tweaks(&mut cache, &[(tweaks[4], false)]), Err(Error::InvalidTweak));
+    }
+
+    #[test]
+    fn test_sig_agg_vectors() {
+        let keys = [
+            "03935F972DA013F80AE011890FA89B67A27B7BE6CCB24D3274D18B2D4067F261A9",
+            "02D2DC6F5DF7C56ACF38C7FA0AE7A759AE30E19B37359DFDE015872324C7EF6E05",
+            "03C7FB101D97FF930ACD0C6760852EF64E69083DE0B06AC6335724754BB4B0522C",
+            "02352433B21E7E05D3B452B81CAE566E06D2E003ECE16D1074AABA4289E0E3D581",
+        ];
+        let tweaks = [
+            "B511DA492182A91B0FFB9A98020D55F260AE86D7ECBD0399C7383D59A5F2AF7C",
+            "A815FE049EE3C5AAB66310477FBC8BCCCAC2F3395F59F921C364ACD78A2F48DC",
+            "75448A87274B056468B977BE06EB1E9F657577B7320B0A3376EA51FD420D18A8",
+        ];
+        let psigs = [
+            "B15D2CD3C3D22B04DAE438CE653F6B4ECF042F42CFDED7C41B64AAF9B4AF53FB",
+            "6193D6AC61B354E9105BBDC8937A3454A6D705B6D57322A5A472A02CE99FCB64",
+            "9A87D3B79EC67228CB97878B76049B15DBD05B8158D17B5B9114D3C226887505",
+            "66F82EA90923689B855D36C6B7E032FB9970301481B99E01CDB4D6AC7C347A15",
+            "4F5AEE41510848A6447DCD1BBC78457EF69024944C87F40250D3EF2C25D33EFE",
+            "DDEF427BBB847CC027BEFF4EDB01038148917832253EBC355FC33F4A8E2FCCE4",
+            "97B890A26C981DA8102D3BC294159D171D72810FDF7C6A691DEF02F0F7AF3FDC",
+            "53FA9E08BA5243CBCB0D797C5EE83BC6728E539EB76C2D0BF0F971EE4E909971",
+            "FFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFEBAAEDCE6AF48A03BBFD25E8CD0364141",
+        ];
+        let msg = hex("599C67EA410D005B9DA90817CF03ED3B1C868E4DA4EDF00A5880B0082C237869");
+        #[allow(clippy::type_complexity)]
+        let valid: [(&[usize], Tweaks, &str, &[usize], &str); 4] = [
+            (
+                &[0, 1],
+                &[],
+                "0341432722C5CD0268D829C702CF0D1CBCE57033EED201FD335191385227C3210C03D377F2D258B64AADC0E16F26462323D701D286046A2EA93365656AFD9875982B",
+                &[0, 1],
+                "041DA22223CE65C92C9A0D6C2CAC828AAF1EEE56304FEC371DDF91EBB2B9EF0912F1038025857FEDEB3FF696F8B99FA4BB2C5812F6095A2E0004EC99CE18DE1E",
+            ),
+            (
+                &[0, 2],
+                &[],
+                "0224AFD36C902084058B51B5D36676BBA4DC97C775873768E58822F87FE437D792028CB15929099EEE2F5DAE404CD39357591BA32E9AF4E162B8D3E7CB5EFE31CB20",
+                &[2, 3],
+                "1069B67EC3D2F3C7C08291ACCB17A9C9B8F2819A52EB5DF8726E17E7D6B52E9F01800260A7E9DAC450F4BE522DE4CE12BA91AEAF2B4279219EF74BE1D286ADD9",
+            ),
+            (
+                &[0, 2],
+                &[(tweaks[0], false)],
+                "0208C5C438C710F4F96A61E9FF3C37758814B8C3AE12BFEA0ED2C87FF6954FF186020B1816EA104B4FCA2D304D733E0E19CEAD51303FF6420BFD222335CAA402916D",
+                &[4, 5],
+                "5C558E1DCADE86DA0B2F02626A512E30A22CF5255CAEA7EE32C38E9A71A0E9148BA6C0E6EC7683B64220F0298696F1B878CD47B107B81F7188812D593971E0CC",
+            ),
+            (
+                &[0, 3],
+                &[(tweaks[0], true), (tweaks[1], false), (tweaks[2], true)],
+                "02B5AD07AFCD99B6D92CB433FBD2A28FDEB98EAE2EB09B6014EF0F8197CD58403302E8616910F9293CF692C49F351DB86B25E352901F0E237BAFDA11F1C1CEF29FFD",
+                &[6, 7],
+                "839B08820B681DBA8DAF4CC7B104E8F2638F9388F8D7A555DC17B6E6971D7426CE07BF6AB01F1DB50E4E33719295F4094572B79868E440FB3DEFD3FAC1DB589E",
+            ),
+        ];
+        for (key_indices, tweaks, aggnonce, psig_indices, expected) in &valid {
+            let mut cache = KeyAggCache::new(&pubkeys(&keys, key_indices)).unwrap();
+            apply_tweaks(&mut cache, tweaks).unwrap();
+            let session = Session::new(&AggNonce::parse(&hex(aggnonce)).unwrap(), &msg, &cache);
+            let psigs: Vec<_> = psig_indices.iter().map(|&i| PartialSignature::parse(&hex32(psigs[i])).unwrap()).collect();
+            let sig = session.partial_sig_agg(&psigs, &cache);
+            assert_eq!(&sig.serialize()[..], &hex(expected)[..]);
+
+            let agg_pk = PublicKey::from_x_only(&cache.agg_pk()).unwrap();
+            assert!(agg_pk.verify_bip340(&msg, SchnorrSignature::parse_slice(&hex(expected))));
+        }
+        assert_eq!(PartialSignature::parse(&hex32(psigs[8])), Err(Error::InvalidPartialSignature));
+    }
+
+    #[test]
+    fn test_musig_full_session() {
+        let msg = b"Chancellor on brink of second bailout for banks";
+        let sks: Vec<_> = [3u32, 1337, 8_764_321].iter().map(|&i| PrivateKey::new(i)).collect();
+        let mut keys: Vec<_> = sks.iter().map(PrivateKey::generate_pubkey).collect();
+        key_sort(&mut keys);
+        let mut cache = KeyAggCache::new(&keys).unwrap();
+        cache.pubkey_xonly_tweak_add(&[0x42; 32]).unwrap();
+
+        let nonces: Vec<_> = sks
+            .iter()
+            .enumerate()
+            .map(|(i, sk)| nonce_gen([i as u8; 32], Some(sk), &sk.generate_pubkey(), Some(&cache.agg_pk()), Some(msg), None).unwrap())
+            .collect();
+        let pubnonces: Vec<_> = nonces.iter().map(|(_, pubnonce)| pubnonce.clone()).collect();
+        let session = Session::new(&nonce_agg(&pubnonces), msg, &cache);
+
+        let mut psigs = Vec::new();
+        for (sk, (secnonce, pubnonce)) in sks.iter().zip(nonces) {
+            let psig = session.partial_sign(secnonce, sk, &cache).unwrap();
+            assert!(session.partial_verify(&psig, &pubnonce, &sk.generate_pubkey(), &cache));
+            psigs.push(psig);
+        }
+        let sig = session.partial_sig_agg(&psigs, &cache);
+        let agg_pk = PublicKey::from_x_only(&cache.agg_pk()).unwrap();
+        assert!(agg_pk.verify_bip340(msg, sig));
+    }
+}
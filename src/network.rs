@@ -0,0 +1,7 @@
+/// The Bitcoin network a key or address belongs to, which selects the version bytes and prefixes of its encodings.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Network {
+    Mainnet,
+    /// Testnet, signet and regtest all share the same prefixes (except for the bech32 HRP of regtest).
+    Testnet,
+}
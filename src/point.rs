@@ -146,6 +146,17 @@ impl AddAssign<&Point> for Point {
     }
 }
 
+impl Neg for Point {
+    type Output = Self;
+    #[inline(always)]
+    fn neg(mut self) -> Self {
+        if !self.is_on_infinity() {
+            self.y.reflect();
+        }
+        self
+    }
+}
+
 macro_rules! mul_impl_point {
     ($($t:ty)*) => ($(
        impl Mul<$t> for Point {
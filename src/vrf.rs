@@ -0,0 +1,168 @@
+//! ECVRF verifiable random function, with the `ECVRF-SECP256K1-SHA256-TAI` suite.
+//!
+//! This follows the ECVRF construction of [RFC 9381](https://www.rfc-editor.org/rfc/rfc9381) with the encoding of
+//! draft-irtf-cfrg-vrf-05 (suite string `0xFE`, no public key in the challenge and no trailing zero bytes),
+//! which is what deployed secp256k1 VRFs (e.g. the `vrf` crate) use.
+//! The proof is a DLEQ proof that `Y = x*G` and `Gamma = x*H` with the challenge truncated to 16 bytes.
+
+use crate::field::FieldElement;
+use crate::hash::{HashDigest, HashTrait};
+use crate::point::Point;
+use crate::secp256k1::{get_context, PrivateKey, PublicKey};
+
+const SUITE: u8 = 0xFE;
+const C_LEN: usize = 16;
+
+/// A VRF proof, serialized as `Gamma || c || s` (33 + 16 + 32 = 81 bytes).
+#[derive(Debug, Clone, PartialEq)]
+pub struct VrfProof {
+    gamma: Point,
+    c: FieldElement,
+    s: FieldElement,
+}
+
+impl VrfProof {
+    pub fn serialize(&self) -> [u8; 81] {
+        let mut res = [0u8; 81];
+        res[..33].copy_from_slice(&PublicKey::from(self.gamma.clone()).compressed());
+        res[33..49].copy_from_slice(&self.c.clone().serialize_num()[32 - C_LEN..]);
+        res[49..].copy_from_slice(&self.s.clone().serialize_num());
+        res
+    }
+
+    pub fn parse(ser: &[u8]) -> Result<VrfProof, &'static str> {
+        let order = &get_context().order;
+        if ser.len() != 81 {
+            return Err("A VRF proof must be 81 bytes");
+        }
+        let gamma = PublicKey::from_compressed(&ser[..33])?.point;
+        let c = FieldElement::from_serialize(&ser[33..49], order.clone());
+        let s = FieldElement::from_serialize(&ser[49..], order.clone());
+        if &s.num >= order {
+            return Err("s is not a scalar");
+        }
+        Ok(VrfProof { gamma, c, s })
+    }
+
+    /// The VRF output (beta). Only meaningful once the proof was verified, see [`verify`].
+    pub fn to_hash(&self) -> [u8; 32] {
+        let mut hasher = HashDigest::new();
+        hasher.input(&[SUITE, 0x03]);
+        hasher.input(&PublicKey::from(self.gamma.clone()).compressed());
+        hasher.result()
+    }
+}
+
+/// Proves the VRF output for `alpha` under `key`.
+#[allow(non_snake_case)]
+pub fn prove(key: &PrivateKey, alpha: &[u8]) -> Result<VrfProof, &'static str> {
+    let secp = get_context();
+    let x = FieldElement::new(key.scalar.clone(), secp.order.clone());
+    let H = hash_to_curve_tai(&key.generate_pubkey(), alpha)?;
+    let h_string = PublicKey::from(H.clone()).compressed();
+    let gamma = &x.num * H.clone();
+
+    // RFC6979 with the hash of H as the message.
+    let h_hash = h_string[..].hash_digest();
+    let mut h1 = FieldElement::from_serialize(&h_hash, secp.order.clone());
+    h1.mod_num();
+    let k = key.deterministic_k_ecdsa(h1.serialize_num());
+
+    let U = &k.num * secp.generator();
+    let V = &k.num * H.clone();
+    let c = challenge(&H, &gamma, &U, &V);
+    let s = k + (c.clone() * &x);
+    Ok(VrfProof { gamma, c, s })
+}
+
+/// Verifies the proof and returns the VRF output.
+#[allow(non_snake_case)]
+pub fn verify(pubkey: &PublicKey, proof: &VrfProof, alpha: &[u8]) -> Result<[u8; 32], &'static str> {
+    let secp = get_context();
+    let H = hash_to_curve_tai(pubkey, alpha)?;
+    let neg_c = &secp.order - proof.c.clone();
+    // U = s*G - c*Y, V = s*H - c*Gamma
+    let U = (&proof.s.num * secp.generator()) + (&neg_c.num * &pubkey.point);
+    let V = (&proof.s.num * H.clone()) + (&neg_c.num * &proof.gamma);
+    if challenge(&H, &proof.gamma, &U, &V) != proof.c {
+        return Err("Invalid VRF proof");
+    }
+    Ok(proof.to_hash())
+}
+
+/// Returns the VRF output of a serialized proof without verifying it.
+pub fn proof_to_hash(proof: &[u8]) -> Result<[u8; 32], &'static str> {
+    Ok(VrfProof::parse(proof)?.to_hash())
+}
+
+// ECVRF_hash_to_curve_try_and_increment, hashes until the result is the x coordinate of a point with an even y.
+fn hash_to_curve_tai(pubkey: &PublicKey, alpha: &[u8]) -> Result<Point, &'static str> {
+    let secp = get_context();
+    let group = &secp.generator().group;
+    let pk = pubkey.clone().compressed();
+    for ctr in 0..=255u8 {
+        let mut hasher = HashDigest::new();
+        hasher.input(&[SUITE, 0x01]);
+        hasher.input(&pk);
+        hasher.input(alpha);
+        hasher.input(&[ctr]);
+        let x = FieldElement::from_serialize(&hasher.result(), secp.modulo.clone());
+        if x.num >= secp.modulo {
+            continue;
+        }
+        let mut y = group.get_y(&x);
+        if !y.is_even() {
+            y.reflect();
+        }
+        let point = Point { x, y, group: group.clone() };
+        if point.is_on_curve() {
+            return Ok(point);
+        }
+    }
+    Err("Failed hashing to the curve")
+}
+
+fn challenge(h: &Point, gamma: &Point, u: &Point, v: &Point) -> FieldElement {
+    let mut hasher = HashDigest::new();
+    hasher.input(&[SUITE, 0x02]);
+    for point in &[h, gamma, u, v] {
+        hasher.input(&PublicKey::from((*point).clone()).compressed());
+    }
+    FieldElement::from_serialize(&hasher.result()[..C_LEN], get_context().order.clone())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rustc_hex::FromHex;
+
+    fn hex(hex: &str) -> Vec<u8> {
+        hex.from_hex().unwrap()
+    }
+
+    // Test vectors from the `vrf` crate.
+    #[test]
+    fn test_vrf_vectors() {
+        let key = PrivateKey::from_serialized(&hex("c9afa9d845ba75166b5c215767b1d6934e50c3db36e89b127b8a622b120f6721"));
+        let pubkey = PublicKey::from_compressed(&hex("032c8c31fc9f990c6b55e3865a184a4ce50e09481f2eaeb3e60ec1cea13a6ae645")).unwrap();
+        let beta = hex("612065e309e937ef46c2ef04d5886b9c6efd2991ac484ec64a9b014366fc5d81");
+        assert_eq!(key.generate_pubkey(), pubkey);
+
+        let proof = prove(&key, b"sample").unwrap();
+        assert_eq!(
+            &proof.serialize()[..],
+            &hex("031f4dbca087a1972d04a07a779b7df1caa99e0f5db2aa21f3aecc4f9e10e85d08748c9fbe6b95d17359707bfb8e8ab0c93ba0c515333adcb8b64f372c535e115ccf66ebf5abe6fadb01b5efb37c0a0ec9")[..]
+        );
+        assert_eq!(&verify(&pubkey, &proof, b"sample").unwrap()[..], &beta[..]);
+
+        // A proof generated with a different nonce.
+        let pi = hex("031f4dbca087a1972d04a07a779b7df1caa99e0f5db2aa21f3aecc4f9e10e85d0814faa89697b482daa377fb6b4a8b0191a65d34a6d90a8a2461e5db9205d4cf0bb4b2c31b5ef6997a585a9f1a72517b6f");
+        let proof = VrfProof::parse(&pi).unwrap();
+        assert_eq!(&verify(&pubkey, &proof, b"sample").unwrap()[..], &beta[..]);
+        assert_eq!(&proof_to_hash(&pi).unwrap()[..], &beta[..]);
+        assert!(verify(&pubkey, &proof, b"notsample").is_err());
+        assert!(verify(&PrivateKey::new(2u8).generate_pubkey(), &proof, b"sample").is_err());
+
+        assert!(VrfProof::parse(&[0u8; 16]).is_err());
+    }
+}
@@ -0,0 +1,35 @@
+//! Wallet Import Format, a private key in Base58Check. See `PrivateKey::to_wif` and `PrivateKey::from_wif`.
+
+use crate::base58;
+use std::fmt;
+
+pub(crate) const MAINNET: u8 = 0x80;
+pub(crate) const TESTNET: u8 = 0xEF;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Error {
+    Base58(base58::Error),
+    /// The payload should be 33 bytes, or 34 bytes ending with `0x01` for a compressed public key.
+    InvalidLength(usize),
+    /// The version byte doesn't match any known network.
+    UnknownVersion(u8),
+    /// The encoded private key is zero or not lower than the order.
+    InvalidPrivateKey,
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Error::Base58(e) => write!(f, "{}", e),
+            Error::InvalidLength(len) => write!(f, "Invalid WIF payload length: {} bytes", len),
+            Error::UnknownVersion(version) => write!(f, "Unknown WIF version byte {:#04x}", version),
+            Error::InvalidPrivateKey => write!(f, "Invalid private key"),
+        }
+    }
+}
+
+impl From<base58::Error> for Error {
+    fn from(e: base58::Error) -> Self {
+        Error::Base58(e)
+    }
+}
@@ -84,7 +84,7 @@ fn test_cmp_verify_der_compressed() {
 
     // Verify with This library
 
-    let sig = Signature::parse_der(&sig.serialize_der());
+    let sig = Signature::parse_der(&sig.serialize_der()).unwrap();
     let pubkey = PublicKey::from_compressed(&pubkey).unwrap();
     assert!(pubkey.verify(&orig_msg, sig, true));
 }